@@ -0,0 +1,24 @@
+/// Error
+#[derive(Debug, PartialEq)]
+#[repr(i8)]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidIdentity,
+    TimeIndexNotFound,
+    //the cell dep has no type script
+    NoTypeScript,
+    InvalidCodeHash,
+    InvalidHashType,
+    InvalidArgs,
+    InvalidCellData,
+    TimeInfoNotFound,
+    //the header of the block committing the cell dep is not in header deps
+    HeaderNotFound,
+    //a cell dep typed as a time info cell holds invalid data
+    InvalidTimeInfo,
+    //more than one time info cell of the current index in cell deps
+    AmbiguousTimeInfo,
+}
@@ -0,0 +1,223 @@
+//! Helpers for scripts reading the time index cell from cell deps.
+//!
+//! Any cell can carry the data of a time index cell, so a consumer checks the type script of the cell dep
+//! against the identity of the time index cell it trusts before reading its index.
+//!
+//! Cell deps are read after the node expands dep groups, so the same helpers find the time index cell and the
+//! time info cell whether they are attached one by one or as members of one dep group.
+
+#![no_std]
+
+extern crate alloc;
+
+mod error;
+mod loader;
+
+pub use error::Error;
+pub use loader::{CellDepLoader, Script};
+
+use alloc::vec::Vec;
+use time_index_state_validation::helper::{
+    check_cell_data, get_cell_data_epoch, get_cell_data_lap, get_clock_mode,
+};
+
+pub use time_index_state_validation::helper::{ClockMode, Epoch};
+
+/// Length of the args of the time index cell, the out point of the first input of the create transaction
+pub const TIME_INDEX_ARGS_LEN: usize = 36;
+
+/// Length of the args of a time index cell with an update interval, `out point | interval as u64`
+pub const TIME_INDEX_ARGS_WITH_INTERVAL_LEN: usize = 44;
+
+/// Length of the args of a time index cell with an update interval and its metric, `out point | interval as u64 | metric`
+pub const TIME_INDEX_ARGS_WITH_METRIC_LEN: usize = 45;
+
+/// Length of the data of a time info cell, `index | timestamp as u64 in little endian`
+pub const TIME_INFO_CELL_DATA_LEN: usize = 9;
+
+/// Hash type of a script, the values are the ones of molecule `Script.hash_type`
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[repr(u8)]
+pub enum HashType {
+    Data = 0,
+    Type = 1,
+}
+
+/// Type script of the trusted time index cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeIndexIdentity {
+    /// Data hash of the binary, or the type id hash of the code cell
+    pub code_hash: [u8; 32],
+    pub hash_type: HashType,
+    /// Out point of the first input of the create transaction, unique to one time index cell,
+    /// followed by the update interval and its metric for a clock with one
+    pub args: Vec<u8>,
+}
+
+impl TimeIndexIdentity {
+    pub fn new(code_hash: [u8; 32], hash_type: u8, args: &[u8]) -> Result<Self, Error> {
+        let hash_type = match hash_type {
+            0 => HashType::Data,
+            1 => HashType::Type,
+            _ => return Err(Error::InvalidIdentity),
+        };
+        get_clock_mode(args).map_err(|_| Error::InvalidIdentity)?;
+        Ok(TimeIndexIdentity {
+            code_hash,
+            hash_type,
+            args: args.to_vec(),
+        })
+    }
+
+    /// Update interval of the clock and its metric, none for a clock without interval
+    pub fn mode(&self) -> Option<ClockMode> {
+        get_clock_mode(&self.args).unwrap_or(None)
+    }
+
+    fn matches(&self, script: &Script) -> bool {
+        script.code_hash == self.code_hash
+            && script.hash_type == self.hash_type as u8
+            && script.args[..] == self.args[..]
+    }
+}
+
+/// State of a trusted time index cell
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeIndexState {
+    pub index: u8,
+    pub n: u8,
+    /// Turns of the index around the ring, none for cell data older than the lap.
+    /// `(lap, index)` tells apart a state from the same index of an earlier lap, e.g. after a reorg.
+    pub lap: Option<u64>,
+    /// Epoch of the last update of an epoch clock
+    pub epoch: Option<Epoch>,
+}
+
+fn load_state<L: CellDepLoader>(loader: &L, position: usize) -> Result<TimeIndexState, Error> {
+    let data = loader.load_cell_dep_data(position)?;
+    check_cell_data(&data).map_err(|_| Error::InvalidCellData)?;
+    Ok(TimeIndexState {
+        index: data[0],
+        n: data[1],
+        lap: get_cell_data_lap(&data),
+        epoch: get_cell_data_epoch(&data).map(Epoch::from_u64),
+    })
+}
+
+/// Check the cell dep at `position` is the time index cell of `identity` and return its state.
+/// Each mismatch of the type script has its own error, so an impostor cell is told apart from a missing one.
+pub fn verify_time_index_cell_dep<L: CellDepLoader>(
+    loader: &L,
+    position: usize,
+    identity: &TimeIndexIdentity,
+) -> Result<TimeIndexState, Error> {
+    let script = match loader.load_cell_dep_type(position) {
+        Ok(Some(script)) => script,
+        Ok(None) => return Err(Error::NoTypeScript),
+        Err(Error::IndexOutOfBound) => return Err(Error::TimeIndexNotFound),
+        Err(err) => return Err(err),
+    };
+    if script.code_hash != identity.code_hash {
+        return Err(Error::InvalidCodeHash);
+    }
+    if script.hash_type != identity.hash_type as u8 {
+        return Err(Error::InvalidHashType);
+    }
+    if script.args[..] != identity.args[..] {
+        return Err(Error::InvalidArgs);
+    }
+    load_state(loader, position)
+}
+
+/// Find the time index cell of `identity` in cell deps, return its position and state
+pub fn find_time_index_cell_dep<L: CellDepLoader>(
+    loader: &L,
+    identity: &TimeIndexIdentity,
+) -> Result<(usize, TimeIndexState), Error> {
+    let mut position = 0;
+    loop {
+        match loader.load_cell_dep_type(position) {
+            Ok(Some(script)) if identity.matches(&script) => {
+                return Ok((position, load_state(loader, position)?));
+            }
+            Ok(_) => {}
+            Err(Error::IndexOutOfBound) => return Err(Error::TimeIndexNotFound),
+            Err(err) => return Err(err),
+        }
+        position += 1;
+    }
+}
+
+/// Find the time info cell of `index` typed by `type_hash` in cell deps, return its position and timestamp.
+/// Anyone can create a cell under any lock, so the time info cell is told by the hash of its type script,
+/// time_info_type_script bound to the time index cell, and a second cell of the same index is rejected.
+pub fn find_time_info_cell_dep<L: CellDepLoader>(
+    loader: &L,
+    index: u8,
+    type_hash: &[u8; 32],
+) -> Result<(usize, u64), Error> {
+    let mut found = None;
+    let mut position = 0;
+    loop {
+        match loader.load_cell_dep_type_hash(position) {
+            Ok(Some(hash)) if &hash == type_hash => {
+                let data = loader
+                    .load_small_cell_dep_data(position, TIME_INFO_CELL_DATA_LEN)?
+                    .filter(|data| data.len() == TIME_INFO_CELL_DATA_LEN)
+                    .ok_or(Error::InvalidTimeInfo)?;
+                if data[0] == index {
+                    if found.is_some() {
+                        return Err(Error::AmbiguousTimeInfo);
+                    }
+                    let mut timestamp = [0u8; 8];
+                    timestamp.copy_from_slice(&data[1..]);
+                    found = Some((position, u64::from_le_bytes(timestamp)));
+                }
+            }
+            Ok(_) => {}
+            Err(Error::IndexOutOfBound) => return found.ok_or(Error::TimeInfoNotFound),
+            Err(err) => return Err(err),
+        }
+        position += 1;
+    }
+}
+
+/// Positions of the time index cell and the current time info cell in cell deps
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TimeCellDeps {
+    pub time_index_position: usize,
+    pub state: TimeIndexState,
+    pub time_info_position: usize,
+    pub timestamp: u64,
+}
+
+/// Find the time index cell of `identity` and the time info cell of its current index typed by
+/// `time_info_type_hash`, e.g. among the members of a dep group. Anyone can publish a dep group,
+/// so its members are authenticated as any other cell dep.
+pub fn resolve_time_cell_deps<L: CellDepLoader>(
+    loader: &L,
+    identity: &TimeIndexIdentity,
+    time_info_type_hash: &[u8; 32],
+) -> Result<TimeCellDeps, Error> {
+    let (time_index_position, state) = find_time_index_cell_dep(loader, identity)?;
+    let (time_info_position, timestamp) =
+        find_time_info_cell_dep(loader, state.index, time_info_type_hash)?;
+    Ok(TimeCellDeps {
+        time_index_position,
+        state,
+        time_info_position,
+        timestamp,
+    })
+}
+
+/// Number of the block committing the time index cell at `position`, the block where the index last advanced.
+/// The header of the block should be in header deps, so a block number clock serves as a height oracle.
+pub fn load_time_index_block_number<L: CellDepLoader>(
+    loader: &L,
+    position: usize,
+) -> Result<u64, Error> {
+    match loader.load_cell_dep_block_number(position) {
+        Err(Error::ItemMissing) => Err(Error::HeaderNotFound),
+        result => result,
+    }
+}
@@ -0,0 +1,34 @@
+use alloc::vec::Vec;
+
+use crate::error::Error;
+
+/// Type script of a cell dep
+#[derive(Debug, Clone, PartialEq)]
+pub struct Script {
+    pub code_hash: [u8; 32],
+    pub hash_type: u8,
+    pub args: Vec<u8>,
+}
+
+/// Loads the cell deps of the transaction
+pub trait CellDepLoader {
+    /// Type script of the cell dep, none if it has no type script.
+    /// Return `Error::IndexOutOfBound` after the last cell dep.
+    fn load_cell_dep_type(&self, index: usize) -> Result<Option<Script>, Error>;
+
+    fn load_cell_dep_data(&self, index: usize) -> Result<Vec<u8>, Error>;
+
+    /// Data of the cell dep if it is not longer than `max_len`, none otherwise,
+    /// so code cells are never loaded as a whole while looking for a small cell
+    fn load_small_cell_dep_data(
+        &self,
+        index: usize,
+        max_len: usize,
+    ) -> Result<Option<Vec<u8>>, Error>;
+
+    /// Hash of the type script of the cell dep, none if it has no type script
+    fn load_cell_dep_type_hash(&self, index: usize) -> Result<Option<[u8; 32]>, Error>;
+
+    /// Number of the block committing the cell dep, its header should be in header deps
+    fn load_cell_dep_block_number(&self, index: usize) -> Result<u64, Error>;
+}
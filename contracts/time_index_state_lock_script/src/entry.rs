@@ -0,0 +1,46 @@
+use core::result::Result;
+
+use ckb_std::ckb_constants::Source;
+use ckb_std::high_level::{load_cell_type_hash, load_script_hash};
+
+use crate::error::Error;
+use crate::helper::{
+    check_continuation_cell, check_owner_lock_in_inputs, check_reward_capacity,
+    check_since_interval, check_time_info_cell, get_group_cell_count, load_lock_mode, LockMode,
+};
+
+pub fn main() -> Result<(), Error> {
+    let lock_mode = load_lock_mode()?;
+
+    //should only one time index cell locked by this script in input
+    if get_group_cell_count(Source::GroupInput) != 1 {
+        return Err(Error::InvalidTimeIndexInput);
+    }
+    //the locked cell should be a time index cell, which always has a type script
+    let type_hash = match load_cell_type_hash(0, Source::GroupInput)? {
+        Some(type_hash) => type_hash,
+        None => return Err(Error::InvalidTimeIndexInput),
+    };
+
+    match lock_mode {
+        //the owner should authorize the update with a cell of its own lock
+        LockMode::Owner(owner_lock_hash) => check_owner_lock_in_inputs(owner_lock_hash)?,
+        //anyone can update once the interval passed since the last update
+        LockMode::Permissionless { interval, .. } => check_since_interval(interval)?,
+    }
+
+    //the time index cell should be carried to output with the same lock and type
+    let lock_hash = load_script_hash()?;
+    let continuation_index = check_continuation_cell(lock_hash, type_hash)?;
+
+    if let LockMode::Permissionless {
+        reward,
+        time_info_type_hash,
+        ..
+    } = lock_mode
+    {
+        check_reward_capacity(continuation_index, reward)?;
+        check_time_info_cell(continuation_index, time_info_type_hash)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,137 @@
+use core::convert::TryInto;
+
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::*,
+};
+
+use crate::error::Error;
+
+pub const OWNER_LOCK_HASH_LEN: usize = 32;
+pub const PERMISSIONLESS_ARGS_LEN: usize = 48;
+
+const SINCE_RELATIVE_FLAG: u64 = 0x8000_0000_0000_0000;
+const SINCE_METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+const SINCE_METRIC_TIMESTAMP: u64 = 0x4000_0000_0000_0000;
+const SINCE_RESERVED_MASK: u64 = 0x1f00_0000_0000_0000;
+const SINCE_VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+
+pub enum LockMode {
+    //only the owner can update, args is the owner lock hash
+    Owner([u8; 32]),
+    //anyone can update after interval seconds, and take at most reward shannons from the cell.
+    //the update should also move the time info cell of the new index, typed by time_info_type_hash
+    Permissionless {
+        interval: u64,
+        reward: u64,
+        time_info_type_hash: [u8; 32],
+    },
+}
+
+pub fn load_lock_mode() -> Result<LockMode, Error> {
+    let script = load_script()?;
+    let script_args: Bytes = script.args().unpack();
+    match script_args.len() {
+        OWNER_LOCK_HASH_LEN => {
+            let mut owner_lock_hash = [0u8; 32];
+            owner_lock_hash.copy_from_slice(&script_args[..]);
+            Ok(LockMode::Owner(owner_lock_hash))
+        }
+        PERMISSIONLESS_ARGS_LEN => {
+            let interval = u64::from_le_bytes(script_args[..8].try_into().unwrap());
+            let reward = u64::from_le_bytes(script_args[8..16].try_into().unwrap());
+            let mut time_info_type_hash = [0u8; 32];
+            time_info_type_hash.copy_from_slice(&script_args[16..]);
+            //a zero interval would let anyone update at any time
+            if interval == 0 {
+                return Err(Error::InvalidArgument);
+            }
+            Ok(LockMode::Permissionless {
+                interval,
+                reward,
+                time_info_type_hash,
+            })
+        }
+        _ => Err(Error::InvalidArgument),
+    }
+}
+
+pub fn get_group_cell_count(source: Source) -> usize {
+    QueryIter::new(load_cell, source).count()
+}
+
+pub fn check_owner_lock_in_inputs(owner_lock_hash: [u8; 32]) -> Result<(), Error> {
+    if QueryIter::new(load_cell_lock_hash, Source::Input)
+        .any(|lock_hash| lock_hash == owner_lock_hash)
+    {
+        return Ok(());
+    }
+    Err(Error::OwnerLockNotFound)
+}
+
+pub fn check_continuation_cell(lock_hash: [u8; 32], type_hash: [u8; 32]) -> Result<usize, Error> {
+    //the only output with this lock should be the time index cell itself
+    let mut continuation_index = None;
+    for (index, output_lock_hash) in QueryIter::new(load_cell_lock_hash, Source::Output).enumerate()
+    {
+        if output_lock_hash != lock_hash {
+            continue;
+        }
+        if load_cell_type_hash(index, Source::Output)? != Some(type_hash)
+            || continuation_index.is_some()
+        {
+            return Err(Error::InvalidTimeIndexOutput);
+        }
+        continuation_index = Some(index);
+    }
+    continuation_index.ok_or(Error::InvalidTimeIndexOutput)
+}
+
+pub fn check_since_interval(interval: u64) -> Result<(), Error> {
+    //the time index cell should be older than interval seconds, measured by relative timestamp since.
+    //the value is compared in seconds, ckb-std would convert it to milliseconds without overflow check
+    let since = load_input_since(0, Source::GroupInput)?;
+    if since & SINCE_RELATIVE_FLAG == 0
+        || since & SINCE_METRIC_MASK != SINCE_METRIC_TIMESTAMP
+        || since & SINCE_RESERVED_MASK != 0
+        || since & SINCE_VALUE_MASK < interval
+    {
+        return Err(Error::InvalidSince);
+    }
+    Ok(())
+}
+
+pub fn check_reward_capacity(continuation_index: usize, reward: u64) -> Result<(), Error> {
+    //the updater can take at most reward from the cell, the rest stays in the time index cell
+    let input_capacity = load_cell_capacity(0, Source::GroupInput)?;
+    let output_capacity = load_cell_capacity(continuation_index, Source::Output)?;
+    if output_capacity < input_capacity && input_capacity - output_capacity > reward {
+        return Err(Error::InvalidCapacity);
+    }
+    Ok(())
+}
+
+pub fn check_time_info_cell(
+    continuation_index: usize,
+    time_info_type_hash: [u8; 32],
+) -> Result<(), Error> {
+    //exactly one output should be the time info cell of the new index, so an update can't skip the time info
+    let index = *load_cell_data(continuation_index, Source::Output)?
+        .first()
+        .ok_or(Error::InvalidTimeIndexOutput)?;
+    let mut found = false;
+    for (position, type_hash) in QueryIter::new(load_cell_type_hash, Source::Output).enumerate() {
+        if type_hash != Some(time_info_type_hash) {
+            continue;
+        }
+        if found || load_cell_data(position, Source::Output)?.first() != Some(&index) {
+            return Err(Error::InvalidTimeInfoOutput);
+        }
+        found = true;
+    }
+    if !found {
+        return Err(Error::InvalidTimeInfoOutput);
+    }
+    Ok(())
+}
@@ -1,27 +1,14 @@
 use ckb_std::error::SysError;
 
-/// Error
-#[repr(i8)]
-pub enum Error {
-    IndexOutOfBound = 1,
-    ItemMissing,
-    LengthNotEnough,
-    Encoding,
-    InvalidArgument,
-    InvalidTimeIndexInput,
-    InvalidTimeIndexOutput,
-    InvalidCellData,
-}
+pub use time_index_state_validation::Error;
 
-impl From<SysError> for Error {
-    fn from(err: SysError) -> Self {
-        use SysError::*;
-        match err {
-            IndexOutOfBound => Self::IndexOutOfBound,
-            ItemMissing => Self::ItemMissing,
-            LengthNotEnough(_) => Self::LengthNotEnough,
-            Encoding => Self::Encoding,
-            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
-        }
+pub fn from_sys_error(err: SysError) -> Error {
+    use SysError::*;
+    match err {
+        IndexOutOfBound => Error::IndexOutOfBound,
+        ItemMissing => Error::ItemMissing,
+        LengthNotEnough(_) => Error::LengthNotEnough,
+        Encoding => Error::Encoding,
+        Unknown(err_code) => panic!("unexpected sys error {}", err_code),
     }
 }
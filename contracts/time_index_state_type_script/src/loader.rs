@@ -0,0 +1,74 @@
+use alloc::vec::Vec;
+
+use ckb_std::{ckb_constants, ckb_types::prelude::*, high_level::*};
+use time_index_state_validation::{CellLoader, Source};
+
+use crate::error::{from_sys_error, Error};
+
+/// Loads cells with syscalls
+pub struct SyscallLoader;
+
+fn to_ckb_source(source: Source) -> ckb_constants::Source {
+    match source {
+        Source::Input => ckb_constants::Source::Input,
+        Source::Output => ckb_constants::Source::Output,
+    }
+}
+
+impl CellLoader for SyscallLoader {
+    fn load_script_hash(&self) -> Result<[u8; 32], Error> {
+        load_script_hash().map_err(from_sys_error)
+    }
+
+    fn load_script_args(&self) -> Result<Vec<u8>, Error> {
+        let script = load_script().map_err(from_sys_error)?;
+        Ok(script.args().raw_data().to_vec())
+    }
+
+    fn load_cell_type_hashes(&self, source: Source) -> Result<Vec<Option<[u8; 32]>>, Error> {
+        Ok(QueryIter::new(load_cell_type_hash, to_ckb_source(source)).collect())
+    }
+
+    fn load_cell_type_args(&self, index: usize, source: Source) -> Result<Option<Vec<u8>>, Error> {
+        let cell = load_cell(index, to_ckb_source(source)).map_err(from_sys_error)?;
+        Ok(cell
+            .type_()
+            .to_opt()
+            .map(|type_script| type_script.args().raw_data().to_vec()))
+    }
+
+    fn load_cell_data(&self, index: usize, source: Source) -> Result<Vec<u8>, Error> {
+        load_cell_data(index, to_ckb_source(source)).map_err(from_sys_error)
+    }
+
+    fn load_input_out_point(&self, index: usize) -> Result<Vec<u8>, Error> {
+        let out_point =
+            load_input_out_point(index, ckb_constants::Source::Input).map_err(from_sys_error)?;
+        Ok(out_point.as_bytes().to_vec())
+    }
+
+    fn load_input_since(&self, index: usize) -> Result<u64, Error> {
+        load_input_since(index, ckb_constants::Source::Input).map_err(from_sys_error)
+    }
+
+    fn load_input_block_number(&self, index: usize) -> Result<u64, Error> {
+        let header = load_header(index, ckb_constants::Source::Input).map_err(from_sys_error)?;
+        Ok(header.raw().number().unpack())
+    }
+
+    fn load_header_dep_block_numbers(&self) -> Result<Vec<u64>, Error> {
+        Ok(
+            QueryIter::new(load_header, ckb_constants::Source::HeaderDep)
+                .map(|header| header.raw().number().unpack())
+                .collect(),
+        )
+    }
+
+    fn load_header_dep_epochs(&self) -> Result<Vec<u64>, Error> {
+        Ok(
+            QueryIter::new(load_header, ckb_constants::Source::HeaderDep)
+                .map(|header| header.raw().epoch().unpack())
+                .collect(),
+        )
+    }
+}
@@ -13,11 +13,9 @@
 use ckb_std::default_alloc;
 
 // define modules
-mod create;
 mod entry;
 mod error;
-mod helper;
-mod update;
+mod loader;
 
 ckb_std::entry!(program_entry);
 default_alloc!();
@@ -0,0 +1,107 @@
+use alloc::vec::Vec;
+use core::convert::TryInto;
+use core::result::Result;
+
+use ckb_std::ckb_constants::Source;
+use ckb_std::ckb_types::{bytes::Bytes, prelude::*};
+use ckb_std::high_level::{
+    load_cell_data, load_cell_type_hash, load_input_since, load_script, QueryIter,
+};
+use time_index_state_validation::helper::{
+    SINCE_METRIC_MASK, SINCE_METRIC_TIMESTAMP, SINCE_RELATIVE_FLAG, SINCE_RESERVED_MASK,
+    SINCE_VALUE_MASK, TIME_INFO_TIMESTAMP_TOLERANCE,
+};
+
+use crate::error::Error;
+
+const TYPE_HASH_LEN: usize = 32;
+const TIME_INDEX_CELL_DATA_MIN_LEN: usize = 2;
+const TIME_INFO_CELL_DATA_LEN: usize = 9;
+
+// The type script of the time info cells of one time index cell.
+// The args are the type hash of the time index cell. Time info cells are only created, updated or destroyed
+// by a transaction creating or updating that time index cell, so a consumer trusting the time index cell
+// tells the time info cells apart from any other cell by their type hash, whatever their lock.
+// An update is bounded by the chain time through the since of its input. Time info cells always follow
+// a time index cell in outputs, so they can never be destroyed.
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    if args.len() != TYPE_HASH_LEN {
+        return Err(Error::InvalidArgument);
+    }
+
+    //the type script of the time index cell checks its own transition
+    let time_index_data =
+        find_time_index_data(&args, Source::Output)?.ok_or(Error::TimeIndexNotFound)?;
+    if time_index_data.len() < TIME_INDEX_CELL_DATA_MIN_LEN {
+        return Err(Error::TimeIndexNotFound);
+    }
+    let (index, n) = (time_index_data[0], time_index_data[1]);
+
+    let inputs = load_time_info_cells(Source::GroupInput, n)?;
+    let outputs = load_time_info_cells(Source::GroupOutput, n)?;
+    if find_time_index_data(&args, Source::Input)?.is_some() {
+        //update, the time info cell of the new index is replaced, its timestamp never goes back
+        match (&inputs[..], &outputs[..]) {
+            ([(input_index, input_timestamp)], [(output_index, output_timestamp)])
+                if *input_index == index && *output_index == index =>
+            {
+                if output_timestamp < input_timestamp {
+                    return Err(Error::InvalidTimestamp);
+                }
+                check_timestamp_since(*output_timestamp)?;
+            }
+            _ => return Err(Error::InvalidIndex),
+        }
+    } else {
+        //create, one time info cell per index at most
+        if !inputs.is_empty() {
+            return Err(Error::InvalidIndex);
+        }
+        for (position, (index, _)) in outputs.iter().enumerate() {
+            if outputs[..position].iter().any(|(other, _)| other == index) {
+                return Err(Error::InvalidIndex);
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_timestamp_since(timestamp: u64) -> Result<(), Error> {
+    //the input can't be committed before the median time reaches the absolute timestamp since in seconds,
+    //so the new timestamp is at most the tolerance ahead of the chain
+    let since = load_input_since(0, Source::GroupInput)?;
+    if since & (SINCE_RELATIVE_FLAG | SINCE_METRIC_MASK | SINCE_RESERVED_MASK)
+        != SINCE_METRIC_TIMESTAMP
+    {
+        return Err(Error::InvalidSince);
+    }
+    if timestamp > (since & SINCE_VALUE_MASK) + TIME_INFO_TIMESTAMP_TOLERANCE {
+        return Err(Error::InvalidTimestamp);
+    }
+    Ok(())
+}
+
+fn find_time_index_data(type_hash: &[u8], source: Source) -> Result<Option<Vec<u8>>, Error> {
+    let position = QueryIter::new(load_cell_type_hash, source).position(|hash| match hash {
+        Some(hash) => hash[..] == type_hash[..],
+        None => false,
+    });
+    match position {
+        Some(position) => Ok(Some(load_cell_data(position, source)?)),
+        None => Ok(None),
+    }
+}
+
+//time_info_cell_data = index | timestamp as u64 in little endian
+fn load_time_info_cells(source: Source, n: u8) -> Result<Vec<(u8, u64)>, Error> {
+    QueryIter::new(load_cell_data, source)
+        .map(|data| {
+            if data.len() != TIME_INFO_CELL_DATA_LEN || data[0] >= n {
+                return Err(Error::InvalidCellData);
+            }
+            Ok((data[0], u64::from_le_bytes(data[1..].try_into().unwrap())))
+        })
+        .collect()
+}
@@ -0,0 +1,32 @@
+use ckb_std::error::SysError;
+
+/// Error
+#[repr(i8)]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidArgument,
+    //the time index cell is not created or updated by the transaction
+    TimeIndexNotFound,
+    InvalidCellData,
+    //the time info cells don't follow the index of the time index cell
+    InvalidIndex,
+    InvalidTimestamp,
+    //the input of an update is not locked by an absolute timestamp since
+    InvalidSince,
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        use SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
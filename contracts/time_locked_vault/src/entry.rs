@@ -0,0 +1,24 @@
+use core::result::Result;
+
+use ckb_std::ckb_constants::Source;
+
+use crate::error::Error;
+use crate::helper::{get_group_cell_count, load_current_timestamp, load_vault_args};
+
+// A vault holding its capacity until a timestamp, told by the time index cell and time info cells.
+// The vault can be created at any time, but withdrawn only when the current timestamp is not less than
+// the unlock timestamp in args.
+pub fn main() -> Result<(), Error> {
+    let args = load_vault_args()?;
+
+    //no vault in inputs, the vault is created
+    if get_group_cell_count(Source::GroupInput) == 0 {
+        return Ok(());
+    }
+
+    let timestamp = load_current_timestamp(&args)?;
+    if timestamp < args.unlock_timestamp {
+        return Err(Error::VaultLocked);
+    }
+    Ok(())
+}
@@ -0,0 +1,48 @@
+use ckb_std::error::SysError;
+
+/// Error
+#[repr(i8)]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidArgument,
+    TimeIndexNotFound,
+    TimeInfoNotFound,
+    VaultLocked,
+    InvalidTimeIndex,
+    InvalidTimeInfo,
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        use SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
+
+impl From<time_index_consumer::Error> for Error {
+    fn from(err: time_index_consumer::Error) -> Self {
+        use time_index_consumer::Error::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing | HeaderNotFound => Self::ItemMissing,
+            LengthNotEnough => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            TimeIndexNotFound => Self::TimeIndexNotFound,
+            TimeInfoNotFound => Self::TimeInfoNotFound,
+            //a cell dep pretending to be the time index cell
+            InvalidIdentity | NoTypeScript | InvalidCodeHash | InvalidHashType | InvalidArgs
+            | InvalidCellData => Self::InvalidTimeIndex,
+            //a time info cell with invalid data, or two of the current index
+            InvalidTimeInfo | AmbiguousTimeInfo => Self::InvalidTimeInfo,
+        }
+    }
+}
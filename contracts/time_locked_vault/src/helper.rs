@@ -0,0 +1,69 @@
+use core::convert::TryInto;
+use core::result::Result;
+
+use ckb_std::{
+    ckb_constants::Source,
+    ckb_types::{bytes::Bytes, prelude::*},
+    high_level::{load_cell_lock_hash, load_script, QueryIter},
+};
+
+use time_index_consumer::{
+    resolve_time_cell_deps, TimeIndexIdentity, TIME_INDEX_ARGS_LEN,
+    TIME_INDEX_ARGS_WITH_INTERVAL_LEN, TIME_INDEX_ARGS_WITH_METRIC_LEN,
+};
+
+use crate::error::Error;
+use crate::loader::SyscallLoader;
+
+const HASH_LEN: usize = 32;
+const HASH_TYPE_LEN: usize = 1;
+const TIMESTAMP_LEN: usize = 8;
+const VAULT_ARGS_FIXED_LEN: usize = HASH_LEN + HASH_TYPE_LEN + HASH_LEN + TIMESTAMP_LEN;
+
+pub struct VaultArgs {
+    pub time_index: TimeIndexIdentity,
+    pub time_info_type_hash: [u8; 32],
+    pub unlock_timestamp: u64,
+}
+
+//vault_args = time_index_code_hash | time_index_hash_type | time_index_args | time_info_type_hash | unlock_timestamp,
+//the time index args are with or without update interval and metric, the unlock timestamp is u64 in little endian
+pub fn load_vault_args() -> Result<VaultArgs, Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    let time_index_args_len = args.len().saturating_sub(VAULT_ARGS_FIXED_LEN);
+    if time_index_args_len != TIME_INDEX_ARGS_LEN
+        && time_index_args_len != TIME_INDEX_ARGS_WITH_INTERVAL_LEN
+        && time_index_args_len != TIME_INDEX_ARGS_WITH_METRIC_LEN
+    {
+        return Err(Error::InvalidArgument);
+    }
+    let time_index_identity_len = HASH_LEN + HASH_TYPE_LEN + time_index_args_len;
+    let time_index = TimeIndexIdentity::new(
+        args[..HASH_LEN].try_into().unwrap(),
+        args[HASH_LEN],
+        &args[HASH_LEN + HASH_TYPE_LEN..time_index_identity_len],
+    )
+    .map_err(|_| Error::InvalidArgument)?;
+    let type_hash_end = time_index_identity_len + HASH_LEN;
+    Ok(VaultArgs {
+        time_index,
+        time_info_type_hash: args[time_index_identity_len..type_hash_end]
+            .try_into()
+            .unwrap(),
+        unlock_timestamp: u64::from_le_bytes(args[type_hash_end..].try_into().unwrap()),
+    })
+}
+
+pub fn get_group_cell_count(source: Source) -> usize {
+    QueryIter::new(load_cell_lock_hash, source).count()
+}
+
+//the timestamp of the current time info cell, the time index cell and the time info cell are found in
+//cell deps, attached one by one or as members of a dep group. The lock of a cell proves nothing about who
+//created it, so the time info cell is the one typed by time_info_type_script of the time index cell
+pub fn load_current_timestamp(args: &VaultArgs) -> Result<u64, Error> {
+    let time_cell_deps =
+        resolve_time_cell_deps(&SyscallLoader, &args.time_index, &args.time_info_type_hash)?;
+    Ok(time_cell_deps.timestamp)
+}
@@ -0,0 +1,64 @@
+use alloc::{vec, vec::Vec};
+
+use ckb_std::{
+    ckb_constants::Source, ckb_types::prelude::*, error::SysError, high_level::*, syscalls,
+};
+use time_index_consumer::{CellDepLoader, Error, Script};
+
+fn from_sys_error(err: SysError) -> Error {
+    use SysError::*;
+    match err {
+        IndexOutOfBound => Error::IndexOutOfBound,
+        ItemMissing => Error::ItemMissing,
+        LengthNotEnough(_) => Error::LengthNotEnough,
+        Encoding => Error::Encoding,
+        Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+    }
+}
+
+/// Loads cell deps with syscalls
+pub struct SyscallLoader;
+
+impl CellDepLoader for SyscallLoader {
+    fn load_cell_dep_type(&self, index: usize) -> Result<Option<Script>, Error> {
+        let script = load_cell_type(index, Source::CellDep).map_err(from_sys_error)?;
+        Ok(script.map(|script| {
+            let mut code_hash = [0u8; 32];
+            code_hash.copy_from_slice(script.code_hash().as_slice());
+            Script {
+                code_hash,
+                hash_type: script.hash_type().into(),
+                args: script.args().raw_data().to_vec(),
+            }
+        }))
+    }
+
+    fn load_cell_dep_data(&self, index: usize) -> Result<Vec<u8>, Error> {
+        load_cell_data(index, Source::CellDep).map_err(from_sys_error)
+    }
+
+    fn load_small_cell_dep_data(
+        &self,
+        index: usize,
+        max_len: usize,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let mut data = vec![0u8; max_len];
+        match syscalls::load_cell_data(&mut data, 0, index, Source::CellDep) {
+            Ok(len) => {
+                data.truncate(len);
+                Ok(Some(data))
+            }
+            Err(SysError::LengthNotEnough(_)) => Ok(None),
+            Err(err) => Err(from_sys_error(err)),
+        }
+    }
+
+    fn load_cell_dep_type_hash(&self, index: usize) -> Result<Option<[u8; 32]>, Error> {
+        load_cell_type_hash(index, Source::CellDep).map_err(from_sys_error)
+    }
+
+    fn load_cell_dep_block_number(&self, index: usize) -> Result<u64, Error> {
+        let header = load_header(index, Source::CellDep).map_err(from_sys_error)?;
+        Ok(header.raw().number().unpack())
+    }
+}
@@ -0,0 +1,76 @@
+use core::result::Result;
+
+use ckb_std::ckb_constants::Source;
+use ckb_std::ckb_types::{bytes::Bytes, prelude::*};
+use ckb_std::error::SysError;
+use ckb_std::high_level::{load_cell_data, load_cell_type_hash, load_script, QueryIter};
+use ckb_std::syscalls;
+use time_index_state_validation::helper::check_cell_data;
+
+use crate::error::Error;
+
+const TYPE_HASH_LEN: usize = 32;
+const TIME_INFO_CELL_DATA_LEN: usize = 9;
+
+// A sample consumer of the time index cell.
+// The args are the type hash of the time index cell and the type hash of its time info cells,
+// `time_index_type_hash | time_info_type_hash`. Every output of the script should hold the timestamp of the
+// current time info cell, `timestamp as u64` in little endian.
+pub fn main() -> Result<(), Error> {
+    let script = load_script()?;
+    let args: Bytes = script.args().unpack();
+    if args.len() != TYPE_HASH_LEN * 2 {
+        return Err(Error::InvalidArgument);
+    }
+    let (time_index_type_hash, time_info_type_hash) = args.split_at(TYPE_HASH_LEN);
+
+    //the time index cell in cell deps tells the index of the current time info cell
+    let time_index_position = QueryIter::new(load_cell_type_hash, Source::CellDep)
+        .position(|type_hash| match type_hash {
+            Some(type_hash) => type_hash[..] == time_index_type_hash[..],
+            None => false,
+        })
+        .ok_or(Error::TimeIndexNotFound)?;
+    let time_index_data = load_cell_data(time_index_position, Source::CellDep)?;
+    check_cell_data(&time_index_data).map_err(|_| Error::InvalidTimeIndex)?;
+    let index = time_index_data[0];
+
+    let time_info_data = find_time_info_data(time_info_type_hash, index)?;
+    let timestamp = &time_info_data[1..];
+
+    for data in QueryIter::new(load_cell_data, Source::GroupOutput) {
+        if data[..] != timestamp[..] {
+            return Err(Error::InvalidTimestamp);
+        }
+    }
+    Ok(())
+}
+
+//anyone can create a cell with the data of a time info cell under any lock, so the time info cell is the one
+//typed by time_info_type_script of the time index cell, and a second one of the same index is rejected.
+//The data is loaded into a buffer of the time info cell data, so the code cells are never loaded as a whole
+fn find_time_info_data(
+    type_hash: &[u8],
+    index: u8,
+) -> Result<[u8; TIME_INFO_CELL_DATA_LEN], Error> {
+    let mut found = None;
+    for (i, cell_type_hash) in QueryIter::new(load_cell_type_hash, Source::CellDep).enumerate() {
+        match cell_type_hash {
+            Some(cell_type_hash) if cell_type_hash[..] == type_hash[..] => {}
+            _ => continue,
+        }
+        let mut data = [0u8; TIME_INFO_CELL_DATA_LEN];
+        match syscalls::load_cell_data(&mut data, 0, i, Source::CellDep) {
+            Ok(len) if len == TIME_INFO_CELL_DATA_LEN => {}
+            Ok(_) | Err(SysError::LengthNotEnough(_)) => return Err(Error::InvalidTimeInfo),
+            Err(err) => return Err(err.into()),
+        }
+        if data[0] == index {
+            if found.is_some() {
+                return Err(Error::InvalidTimeInfo);
+            }
+            found = Some(data);
+        }
+    }
+    found.ok_or(Error::TimeInfoNotFound)
+}
@@ -0,0 +1,31 @@
+use ckb_std::error::SysError;
+
+/// Error
+#[repr(i8)]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidArgument,
+    TimeIndexNotFound,
+    TimeInfoNotFound,
+    InvalidTimestamp,
+    //the time index cell holds invalid data
+    InvalidTimeIndex,
+    //a time info cell holds invalid data, or two time info cells of the current index
+    InvalidTimeInfo,
+}
+
+impl From<SysError> for Error {
+    fn from(err: SysError) -> Self {
+        use SysError::*;
+        match err {
+            IndexOutOfBound => Self::IndexOutOfBound,
+            ItemMissing => Self::ItemMissing,
+            LengthNotEnough(_) => Self::LengthNotEnough,
+            Encoding => Self::Encoding,
+            Unknown(err_code) => panic!("unexpected sys error {}", err_code),
+        }
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    fs::{self, File},
+    path::{Path, PathBuf},
+    process::{Child, Command, Stdio},
+    thread,
+    time::{Duration, Instant},
+};
+
+use ckb_tool::{
+    ckb_jsonrpc_types::Status,
+    ckb_types::{
+        bytes::Bytes,
+        core::{BlockView, HeaderView, TransactionView},
+        packed::{self, Byte32, CellOutput, OutPoint},
+        prelude::*,
+    },
+    rpc_client::RpcClient,
+};
+
+use crate::error::Error;
+
+// type hash of the secp256k1 blake160 sighash all lock in genesis
+pub const SECP256K1_CODE_HASH: &str =
+    "0x9bd7e06f3ecf4be0f2fcd2188b23f1b9fcc88e5d4b65a8637b17723bbda3cce8";
+const LIVE_CELL_STATUS: &str = "live";
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Ports and block interval of the dev chain
+pub struct DevChainConfig {
+    pub rpc_port: u16,
+    pub p2p_port: u16,
+    /// Delay of the dummy miner between two blocks, in milliseconds
+    pub block_interval: u64,
+    /// Lock args of the block assembler
+    pub block_assembler_args: String,
+}
+
+/// A CKB dev chain running from a local `ckb` binary, the node and miner are killed on drop
+pub struct DevChain {
+    dir: PathBuf,
+    node: Child,
+    miner: Option<Child>,
+    rpc_url: String,
+    rpc: RpcClient,
+}
+
+fn run_ckb(ckb_bin: &Path, args: &[&str]) -> Result<(), Error> {
+    let output = Command::new(ckb_bin).args(args).output()?;
+    if !output.status.success() {
+        return Err(Error::Process(format!(
+            "ckb {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr)
+        )));
+    }
+    Ok(())
+}
+
+fn spawn_ckb(ckb_bin: &Path, dir: &Path, subcommand: &str) -> Result<Child, Error> {
+    let log = File::create(dir.join(format!("{}.log", subcommand)))?;
+    let child = Command::new(ckb_bin)
+        .arg(subcommand)
+        .arg("-C")
+        .arg(dir)
+        .stdout(Stdio::from(log.try_clone()?))
+        .stderr(Stdio::from(log))
+        .spawn()?;
+    Ok(child)
+}
+
+impl DevChain {
+    /// Init a dev chain in `dir` and start the node, the miner is started by `start_miner`
+    pub fn start(ckb_bin: &Path, dir: &Path, config: &DevChainConfig) -> Result<Self, Error> {
+        fs::create_dir_all(dir)?;
+        let dir_arg = dir.to_string_lossy();
+        let rpc_port = config.rpc_port.to_string();
+        let p2p_port = config.p2p_port.to_string();
+        run_ckb(
+            ckb_bin,
+            &[
+                "init",
+                "-C",
+                &dir_arg,
+                "--chain",
+                "dev",
+                "--rpc-port",
+                &rpc_port,
+                "--p2p-port",
+                &p2p_port,
+                "--ba-code-hash",
+                SECP256K1_CODE_HASH,
+                "--ba-hash-type",
+                "type",
+                "--ba-arg",
+                &config.block_assembler_args,
+                "--force",
+            ],
+        )?;
+        //the dummy worker waits 5 seconds between blocks by default
+        let miner_config_path = dir.join("ckb-miner.toml");
+        let miner_config = fs::read_to_string(&miner_config_path)?.replace(
+            "value = 5000",
+            &format!("value = {}", config.block_interval),
+        );
+        fs::write(&miner_config_path, miner_config)?;
+
+        let node = spawn_ckb(ckb_bin, dir, "run")?;
+        let rpc_url = format!("http://127.0.0.1:{}", config.rpc_port);
+        let chain = DevChain {
+            dir: dir.to_path_buf(),
+            node,
+            miner: None,
+            rpc: RpcClient::new(&rpc_url),
+            rpc_url,
+        };
+        chain.wait_for(Duration::from_secs(30), "node rpc", || {
+            Ok(chain.rpc.inner().get_tip_block_number().is_ok())
+        })?;
+        Ok(chain)
+    }
+
+    pub fn start_miner(&mut self, ckb_bin: &Path) -> Result<(), Error> {
+        if self.miner.is_none() {
+            self.miner = Some(spawn_ckb(ckb_bin, &self.dir, "miner")?);
+        }
+        Ok(())
+    }
+
+    pub fn rpc(&self) -> &RpcClient {
+        &self.rpc
+    }
+
+    pub fn rpc_url(&self) -> &str {
+        &self.rpc_url
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    pub fn genesis_block(&self) -> Result<BlockView, Error> {
+        self.rpc
+            .inner()
+            .get_block_by_number(0.into())
+            .map_err(|err| Error::Rpc(err.to_string()))?
+            .map(Into::into)
+            .ok_or_else(|| Error::Rpc("genesis block not found".to_string()))
+    }
+
+    pub fn tip_header(&self) -> Result<HeaderView, Error> {
+        self.rpc
+            .inner()
+            .get_tip_header()
+            .map(Into::into)
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    pub fn get_transaction(&self, tx_hash: &Byte32) -> Result<Option<TransactionView>, Error> {
+        Ok(self
+            .rpc
+            .inner()
+            .get_transaction(tx_hash.unpack())
+            .map_err(|err| Error::Rpc(err.to_string()))?
+            .map(|tx| packed::Transaction::from(tx.transaction.inner).into_view()))
+    }
+
+    pub fn get_live_cell(
+        &self,
+        out_point: &OutPoint,
+    ) -> Result<Option<(CellOutput, Bytes)>, Error> {
+        let cell_with_status = self
+            .rpc
+            .inner()
+            .get_live_cell(out_point.clone().into(), true)
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        if cell_with_status.status != LIVE_CELL_STATUS {
+            return Ok(None);
+        }
+        Ok(cell_with_status.cell.map(|cell| {
+            let data = cell
+                .data
+                .map(|data| data.content.into_bytes())
+                .unwrap_or_default();
+            (cell.output.into(), data)
+        }))
+    }
+
+    /// Submit the transaction to the pool, the error of a rejected transaction is returned as is
+    pub fn send_transaction(&self, tx: &TransactionView) -> Result<Byte32, Error> {
+        self.rpc
+            .inner()
+            .send_transaction(tx.data().into())
+            .map(|tx_hash| tx_hash.pack())
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    pub fn wait_committed(&self, tx_hash: &Byte32, timeout: Duration) -> Result<(), Error> {
+        self.wait_for(timeout, &format!("transaction {}", tx_hash), || {
+            Ok(self
+                .rpc
+                .inner()
+                .get_transaction(tx_hash.unpack())
+                .map_err(|err| Error::Rpc(err.to_string()))?
+                .map(|tx| tx.tx_status.status == Status::Committed)
+                .unwrap_or(false))
+        })
+    }
+
+    fn wait_for<F>(&self, timeout: Duration, what: &str, mut ready: F) -> Result<(), Error>
+    where
+        F: FnMut() -> Result<bool, Error>,
+    {
+        let start = Instant::now();
+        while start.elapsed() < timeout {
+            if ready()? {
+                return Ok(());
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+        Err(Error::Timeout(what.to_string()))
+    }
+}
+
+impl Drop for DevChain {
+    fn drop(&mut self) {
+        if let Some(miner) = self.miner.as_mut() {
+            let _ = miner.kill();
+            let _ = miner.wait();
+        }
+        let _ = self.node.kill();
+        let _ = self.node.wait();
+    }
+}
@@ -0,0 +1,236 @@
+use std::{collections::HashMap, fs, path::Path};
+
+use ckb_tool::{
+    ckb_chain_spec::consensus::TYPE_ID_CODE_HASH,
+    ckb_hash::new_blake2b,
+    ckb_types::{
+        bytes::Bytes,
+        core::{Capacity, DepType, ScriptHashType, TransactionBuilder},
+        h256,
+        packed::{Byte32, CellDep, CellInput, CellOutput, OutPoint, Script},
+        prelude::*,
+        H256,
+    },
+};
+use serde::Deserialize;
+use tx_builder::build_dep_group_data;
+
+use crate::{chain::DevChain, error::Error, wallet::Wallet};
+
+/// `deployment.toml` of capsule, the lock is ignored as the cells are deployed by the wallet
+#[derive(Deserialize)]
+pub struct DeploymentConfig {
+    pub cells: Vec<CellConfig>,
+    #[serde(default)]
+    pub dep_groups: Vec<DepGroupConfig>,
+}
+
+#[derive(Deserialize)]
+pub struct CellConfig {
+    pub name: String,
+    pub enable_type_id: bool,
+    pub location: Location,
+}
+
+#[derive(Deserialize)]
+#[serde(untagged)]
+pub enum Location {
+    File { file: String },
+    OnChain { tx_hash: H256, index: u32 },
+}
+
+#[derive(Deserialize)]
+pub struct DepGroupConfig {
+    pub name: String,
+    pub cells: Vec<String>,
+}
+
+impl DeploymentConfig {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        toml::from_str(&content).map_err(|err| Error::Deployment(err.to_string()))
+    }
+}
+
+/// A cell deployed or referenced by the deployment
+pub struct DeployedCell {
+    pub out_point: OutPoint,
+    pub data_hash: Byte32,
+    /// Type id script of the cell if `enable_type_id`
+    pub type_id: Option<Script>,
+}
+
+impl DeployedCell {
+    /// Script referencing the code of the cell, by the type id if it has one
+    pub fn script(&self, args: Bytes) -> Script {
+        let (code_hash, hash_type) = match &self.type_id {
+            Some(type_id) => (type_id.calc_script_hash(), ScriptHashType::Type),
+            None => (self.data_hash.clone(), ScriptHashType::Data),
+        };
+        Script::new_builder()
+            .code_hash(code_hash)
+            .hash_type(hash_type.into())
+            .args(args.pack())
+            .build()
+    }
+}
+
+/// Out points of the deployed cells and dep groups by name
+pub struct Deployment {
+    pub cells: HashMap<String, DeployedCell>,
+    pub dep_groups: HashMap<String, OutPoint>,
+}
+
+impl Deployment {
+    pub fn dep_group(&self, name: &str) -> Option<CellDep> {
+        self.dep_groups.get(name).map(|out_point| {
+            CellDep::new_builder()
+                .out_point(out_point.clone())
+                .dep_type(DepType::DepGroup.into())
+                .build()
+        })
+    }
+}
+
+fn type_id_args(first_input: &CellInput, output_index: u64) -> Bytes {
+    let mut blake2b = new_blake2b();
+    blake2b.update(first_input.as_slice());
+    blake2b.update(&output_index.to_le_bytes());
+    let mut args = [0u8; 32];
+    blake2b.finalize(&mut args);
+    Bytes::from(args.to_vec())
+}
+
+// genesis transactions holding the system cells, e.g. `secp256k1_data`
+const TESTNET_GENESIS_TX_HASH: H256 =
+    h256!("0x8f8c79eb6671709633fe6a46de93c0fedc9c1b8a6527a18d3983879542635c9f");
+const MAINNET_GENESIS_TX_HASH: H256 =
+    h256!("0xe2fb199810d49a4d8beec56718ba2593b665db9d52299a0f9e6e75416d73ff5c");
+
+// a system cell of testnet or mainnet is looked up at the same index of the first genesis transaction
+// of the dev chain, any other location should be on the dev chain
+fn resolve_on_chain_cell(
+    chain: &DevChain,
+    tx_hash: &H256,
+    index: u32,
+) -> Result<DeployedCell, Error> {
+    let tx = match chain.get_transaction(&tx_hash.pack())? {
+        Some(tx) => tx,
+        None if tx_hash == &TESTNET_GENESIS_TX_HASH || tx_hash == &MAINNET_GENESIS_TX_HASH => {
+            chain.genesis_block()?.transactions()[0].clone()
+        }
+        None => {
+            return Err(Error::Deployment(format!(
+                "transaction {:#x} not found",
+                tx_hash
+            )))
+        }
+    };
+    let data = tx
+        .outputs_data()
+        .get(index as usize)
+        .ok_or_else(|| Error::Deployment(format!("no output {} in {}", index, tx.hash())))?;
+    Ok(DeployedCell {
+        out_point: OutPoint::new(tx.hash(), index),
+        data_hash: CellOutput::calc_data_hash(&data.raw_data()),
+        type_id: None,
+    })
+}
+
+/// Deploy the cells of `config` with files under `root` in one transaction, then the dep groups in another
+pub fn deploy(
+    chain: &DevChain,
+    wallet: &mut Wallet,
+    config: &DeploymentConfig,
+    root: &Path,
+) -> Result<Deployment, Error> {
+    let first_input = CellInput::new(wallet.change().clone(), 0);
+    let mut builder = TransactionBuilder::default().input(first_input.clone());
+    let mut files = Vec::new();
+    let mut cells = HashMap::new();
+    for cell in &config.cells {
+        match &cell.location {
+            Location::File { file } => {
+                let data = Bytes::from(fs::read(root.join(file))?);
+                let type_id = if cell.enable_type_id {
+                    Some(
+                        Script::new_builder()
+                            .code_hash(TYPE_ID_CODE_HASH.pack())
+                            .hash_type(ScriptHashType::Type.into())
+                            .args(type_id_args(&first_input, files.len() as u64).pack())
+                            .build(),
+                    )
+                } else {
+                    None
+                };
+                let output = CellOutput::new_builder()
+                    .lock(wallet.lock().clone())
+                    .type_(type_id.clone().pack())
+                    .build();
+                let capacity = output
+                    .occupied_capacity(Capacity::bytes(data.len()).map_err(|_| Error::Capacity)?)
+                    .map_err(|_| Error::Capacity)?;
+                builder = builder
+                    .output(output.as_builder().capacity(capacity.pack()).build())
+                    .output_data(data.pack());
+                files.push((
+                    cell.name.clone(),
+                    CellOutput::calc_data_hash(&data),
+                    type_id,
+                ));
+            }
+            Location::OnChain { tx_hash, index } => {
+                cells.insert(
+                    cell.name.clone(),
+                    resolve_on_chain_cell(chain, tx_hash, *index)?,
+                );
+            }
+        }
+    }
+    let tx_hash = wallet.send(chain, builder.build())?;
+    for (index, (name, data_hash, type_id)) in files.into_iter().enumerate() {
+        cells.insert(
+            name,
+            DeployedCell {
+                out_point: OutPoint::new(tx_hash.clone(), index as u32),
+                data_hash,
+                type_id,
+            },
+        );
+    }
+
+    let mut dep_groups = HashMap::new();
+    if !config.dep_groups.is_empty() {
+        let mut builder = TransactionBuilder::default();
+        for dep_group in &config.dep_groups {
+            let out_points = dep_group
+                .cells
+                .iter()
+                .map(|name| {
+                    cells
+                        .get(name)
+                        .map(|cell| cell.out_point.clone())
+                        .ok_or_else(|| Error::Deployment(format!("unknown cell {}", name)))
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+            let data = build_dep_group_data(&out_points);
+            let output = CellOutput::new_builder()
+                .lock(wallet.lock().clone())
+                .build();
+            let capacity = output
+                .occupied_capacity(Capacity::bytes(data.len()).map_err(|_| Error::Capacity)?)
+                .map_err(|_| Error::Capacity)?;
+            builder = builder
+                .output(output.as_builder().capacity(capacity.pack()).build())
+                .output_data(data.pack());
+        }
+        let tx_hash = wallet.send(chain, builder.build())?;
+        for (index, dep_group) in config.dep_groups.iter().enumerate() {
+            dep_groups.insert(
+                dep_group.name.clone(),
+                OutPoint::new(tx_hash.clone(), index as u32),
+            );
+        }
+    }
+    Ok(Deployment { cells, dep_groups })
+}
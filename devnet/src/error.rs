@@ -0,0 +1,59 @@
+use std::{fmt, io};
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Process(String),
+    Rpc(String),
+    Timeout(String),
+    Deployment(String),
+    Capacity,
+    Updater(updater::Error),
+    TxBuilder(tx_builder::Error),
+    Migrator(migrator::Error),
+    Check(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Process(err) => write!(f, "process error: {}", err),
+            Self::Rpc(err) => write!(f, "rpc error: {}", err),
+            Self::Timeout(err) => write!(f, "timeout: {}", err),
+            Self::Deployment(err) => write!(f, "deployment error: {}", err),
+            Self::Capacity => write!(f, "not enough capacity"),
+            Self::Updater(err) => write!(f, "updater error: {}", err),
+            Self::TxBuilder(err) => write!(f, "tx builder error: {:?}", err),
+            Self::Migrator(err) => write!(f, "migrator error: {}", err),
+            Self::Check(err) => write!(f, "check failed: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<io::Error> for Error {
+    fn from(err: io::Error) -> Self {
+        Self::Io(err)
+    }
+}
+
+impl From<updater::Error> for Error {
+    fn from(err: updater::Error) -> Self {
+        Self::Updater(err)
+    }
+}
+
+impl From<tx_builder::Error> for Error {
+    fn from(err: tx_builder::Error) -> Self {
+        Self::TxBuilder(err)
+    }
+}
+
+impl From<migrator::Error> for Error {
+    fn from(err: migrator::Error) -> Self {
+        Self::Migrator(err)
+    }
+}
@@ -0,0 +1,9 @@
+//! Integration harness running the contracts on a local CKB dev chain.
+
+pub mod chain;
+pub mod deployment;
+mod error;
+pub mod scenario;
+pub mod wallet;
+
+pub use error::Error;
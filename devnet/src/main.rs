@@ -0,0 +1,68 @@
+use std::{env, path::PathBuf, process};
+
+use clap::{App, Arg};
+use devnet::scenario::{run, ScenarioConfig};
+
+fn main() {
+    let matches = App::new("devnet")
+        .about("Run time_index_state_type_script on a local CKB dev chain")
+        .arg(
+            Arg::with_name("ckb")
+                .long("ckb")
+                .takes_value(true)
+                .required(true)
+                .help("Path of the ckb binary"),
+        )
+        .arg(
+            Arg::with_name("root")
+                .long("root")
+                .takes_value(true)
+                .default_value(".")
+                .help("Project root with deployment.toml and build/release"),
+        )
+        .arg(
+            Arg::with_name("dir")
+                .long("dir")
+                .takes_value(true)
+                .help("Data directory of the dev chain, a temporary one by default"),
+        )
+        .arg(
+            Arg::with_name("updates")
+                .long("updates")
+                .takes_value(true)
+                .help("Number of updates of the time index cell"),
+        )
+        .get_matches();
+
+    let dir = match matches.value_of("dir") {
+        Some(dir) => PathBuf::from(dir),
+        None => env::temp_dir().join(format!("time-index-devnet-{}", process::id())),
+    };
+    let mut config = ScenarioConfig::new(
+        PathBuf::from(matches.value_of("ckb").expect("ckb")),
+        PathBuf::from(matches.value_of("root").expect("root")),
+        dir,
+    );
+    if let Some(updates) = matches.value_of("updates") {
+        config.updates = match updates.parse() {
+            Ok(updates) => updates,
+            Err(err) => {
+                eprintln!("invalid updates: {}", err);
+                process::exit(2);
+            }
+        };
+    }
+
+    match run(&config) {
+        Ok(report) => {
+            for (index, timestamp) in report.updates {
+                println!("time info cell {}: {}", index, timestamp);
+            }
+            println!("consumer read timestamp {}", report.consumer_timestamp);
+        }
+        Err(err) => {
+            eprintln!("{}, logs of the node are in {}", err, config.dir.display());
+            process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    path::PathBuf,
+    thread,
+    time::{Duration, Instant},
+};
+
+use ckb_tool::{
+    ckb_crypto::secp::Privkey,
+    ckb_types::{
+        bytes::Bytes,
+        core::{Capacity, ScriptHashType, TransactionBuilder},
+        packed::{CellDep, CellOutput, OutPoint, Script},
+        prelude::*,
+        H256,
+    },
+};
+use migrator::{build_time_index_cells_tx, TimeInfoCells, TIME_INFO_CELL_DATA_LEN};
+use tx_builder::{Contract, TIME_INDEX_CELL_DATA_N};
+use updater::{rpc::HttpRpc, signer::Signer, Updater, UpdaterConfig};
+
+use crate::{
+    chain::{DevChain, DevChainConfig},
+    deployment::{deploy, Deployment, DeploymentConfig},
+    error::Error,
+    wallet::{lock_args, Wallet},
+};
+
+/// Key of the cell issued in the genesis of the dev chain spec
+pub const DEV_CHAIN_PRIVKEY: &str =
+    "d00c06bfd800d27397002dca6fb0993d5ba6399b4238b2f29ee9deb97593d2bc";
+pub const TIME_INDEX_CONTRACT: &str = "time_index_state_type_script";
+pub const TIME_INFO_CONTRACT: &str = "time_info_type_script";
+pub const DEP_GROUP: &str = "dep_group";
+pub const CONSUMER_BINARY: &str = "build/release/timestamp_reader";
+
+// capacity of the time index cell to pay the fee of the updates, in CKB
+const TIME_INDEX_FEE_RESERVE: u64 = 1_000;
+const TICK_TIMEOUT: Duration = Duration::from_secs(120);
+const COMMIT_TIMEOUT: Duration = Duration::from_secs(120);
+// exit code of timestamp_reader for a wrong timestamp
+const CONSUMER_INVALID_TIMESTAMP: i8 = 8;
+
+pub struct ScenarioConfig {
+    pub ckb_bin: PathBuf,
+    /// Project root with `deployment.toml` and `build/release`
+    pub root: PathBuf,
+    /// Data directory of the dev chain
+    pub dir: PathBuf,
+    pub privkey: Privkey,
+    pub chain: DevChainConfig,
+    /// Update transactions committed before the consumer reads the time
+    pub updates: usize,
+    /// Fee of each transaction in shannons
+    pub fee: u64,
+}
+
+impl ScenarioConfig {
+    pub fn new(ckb_bin: PathBuf, root: PathBuf, dir: PathBuf) -> Self {
+        let privkey: H256 = DEV_CHAIN_PRIVKEY.parse().expect("privkey");
+        let privkey = Privkey::from(privkey);
+        let block_assembler_args = format!("0x{}", to_hex(&lock_args(&privkey)));
+        ScenarioConfig {
+            ckb_bin,
+            root,
+            dir,
+            privkey,
+            chain: DevChainConfig {
+                rpc_port: 18114,
+                p2p_port: 18115,
+                block_interval: 500,
+                block_assembler_args,
+            },
+            updates: TIME_INDEX_CELL_DATA_N as usize + 2,
+            fee: 1_000_000,
+        }
+    }
+}
+
+/// States seen along the scenario
+pub struct ScenarioReport {
+    /// Index and timestamp of the time info cell after each update
+    pub updates: Vec<(u8, u64)>,
+    /// Timestamp read by the consumer
+    pub consumer_timestamp: u64,
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn check(condition: bool, message: String) -> Result<(), Error> {
+    if condition {
+        Ok(())
+    } else {
+        Err(Error::Check(message))
+    }
+}
+
+fn decode_time_info(data: &[u8]) -> Result<(u8, u64), Error> {
+    if data.len() != TIME_INFO_CELL_DATA_LEN {
+        return Err(Error::Check(format!(
+            "invalid time info data {}",
+            to_hex(data)
+        )));
+    }
+    let mut timestamp = [0u8; 8];
+    timestamp.copy_from_slice(&data[1..]);
+    Ok((data[0], u64::from_le_bytes(timestamp)))
+}
+
+fn live_cell(chain: &DevChain, out_point: &OutPoint) -> Result<(CellOutput, Bytes), Error> {
+    chain
+        .get_live_cell(out_point)?
+        .ok_or_else(|| Error::Check(format!("cell {} is not live", out_point)))
+}
+
+/// Start a dev chain, deploy `deployment.toml`, create the time index cell and the time info cells,
+/// advance them with the updater, then check the sample consumer reads the current timestamp
+// a contract deployed with a type id, referenced through the dep group
+fn deployed_contract(deployment: &Deployment, name: &str) -> Result<Contract, Error> {
+    let type_id = deployment
+        .cells
+        .get(name)
+        .ok_or_else(|| Error::Deployment(format!("{} is not deployed", name)))?
+        .type_id
+        .as_ref()
+        .ok_or_else(|| Error::Deployment(format!("{} has no type id", name)))?;
+    Ok(Contract {
+        code_hash: type_id.calc_script_hash(),
+        hash_type: ScriptHashType::Type,
+        cell_dep: deployment
+            .dep_group(DEP_GROUP)
+            .ok_or_else(|| Error::Deployment(format!("{} is not deployed", DEP_GROUP)))?,
+    })
+}
+
+pub fn run(config: &ScenarioConfig) -> Result<ScenarioReport, Error> {
+    let mut chain = DevChain::start(&config.ckb_bin, &config.dir, &config.chain)?;
+    chain.start_miner(&config.ckb_bin)?;
+    let mut wallet = Wallet::from_genesis(&chain, config.privkey.clone(), config.fee)?;
+
+    // deploy the contracts and the dep group as `capsule deploy` does
+    let deployment_config = DeploymentConfig::load(&config.root.join("deployment.toml"))?;
+    let deployment = deploy(&chain, &mut wallet, &deployment_config, &config.root)?;
+    let contract = deployed_contract(&deployment, TIME_INDEX_CONTRACT)?;
+    let time_info = TimeInfoCells {
+        contract: &deployed_contract(&deployment, TIME_INFO_CONTRACT)?,
+        lock: wallet.lock().clone(),
+        timestamp: chain.tip_header()?.timestamp() / 1000,
+    };
+
+    // create the time index cell at index 0 and N time info cells
+    let tx = build_time_index_cells_tx(
+        &contract,
+        wallet.change().clone(),
+        wallet.lock().clone(),
+        &time_info,
+        Capacity::bytes(TIME_INDEX_FEE_RESERVE as usize)
+            .map_err(|_| Error::Capacity)?
+            .as_u64(),
+        None,
+        None,
+    )?;
+    let create_tx_hash = wallet.send(&chain, tx)?;
+    let time_info_cells = (1..=TIME_INDEX_CELL_DATA_N as u32)
+        .map(|index| OutPoint::new(create_tx_hash.clone(), index))
+        .collect();
+
+    // advance the time index cell across blocks with the updater
+    let mut updater = Updater::new(
+        HttpRpc::new(chain.rpc_url()),
+        Signer::new(config.privkey.clone()),
+        UpdaterConfig {
+            time_index_cell: OutPoint::new(create_tx_hash, 0),
+            time_info_cells,
+            cell_deps: vec![contract.cell_dep.clone(), wallet.secp256k1_dep().clone()],
+            fee: config.fee,
+            state_file: None,
+        },
+    );
+    let mut updates = Vec::new();
+    let mut last_timestamp = 0;
+    for step in 1..=config.updates {
+        let start = Instant::now();
+        let tx_hash = loop {
+            if let Some(tx_hash) = updater.tick()? {
+                break tx_hash;
+            }
+            if start.elapsed() > TICK_TIMEOUT {
+                return Err(Error::Timeout("time index cell is not live".to_string()));
+            }
+            thread::sleep(Duration::from_secs(1));
+        };
+        chain.wait_committed(&tx_hash, COMMIT_TIMEOUT)?;
+
+        let expected_index = (step % TIME_INDEX_CELL_DATA_N as usize) as u8;
+        let (_, index_data) = live_cell(&chain, updater.time_index_cell())?;
+        check(
+            index_data[0] == expected_index,
+            format!("time index {} after update {}", index_data[0], step),
+        )?;
+        let (_, info_data) =
+            live_cell(&chain, &updater.time_info_cells()[expected_index as usize])?;
+        let (index, timestamp) = decode_time_info(&info_data)?;
+        check(
+            index == expected_index && timestamp >= last_timestamp,
+            format!("time info {} at {} after update {}", index, timestamp, step),
+        )?;
+        last_timestamp = timestamp;
+        updates.push((index, timestamp));
+    }
+
+    // the sample consumer reads the timestamp through the time index cell
+    let consumer_bin = Bytes::from(std::fs::read(config.root.join(CONSUMER_BINARY))?);
+    let consumer_code_hash = CellOutput::calc_data_hash(&consumer_bin);
+    let consumer_output = CellOutput::new_builder()
+        .lock(wallet.lock().clone())
+        .build();
+    let consumer_capacity = consumer_output
+        .occupied_capacity(Capacity::bytes(consumer_bin.len()).map_err(|_| Error::Capacity)?)
+        .map_err(|_| Error::Capacity)?;
+    let consumer_tx_hash = wallet.send(
+        &chain,
+        TransactionBuilder::default()
+            .output(
+                consumer_output
+                    .as_builder()
+                    .capacity(consumer_capacity.pack())
+                    .build(),
+            )
+            .output_data(consumer_bin.pack())
+            .build(),
+    )?;
+
+    let (index_output, index_data) = live_cell(&chain, updater.time_index_cell())?;
+    let time_index_type_hash = index_output
+        .type_()
+        .to_opt()
+        .ok_or_else(|| Error::Check("time index cell has no type script".to_string()))?
+        .calc_script_hash();
+    let info_out_point = updater.time_info_cells()[index_data[0] as usize].clone();
+    let (info_output, info_data) = live_cell(&chain, &info_out_point)?;
+    let (_, timestamp) = decode_time_info(&info_data)?;
+    let time_info_type_hash = info_output
+        .type_()
+        .to_opt()
+        .ok_or_else(|| Error::Check("time info cell has no type script".to_string()))?
+        .calc_script_hash();
+
+    // consumer_args = time_index_type_hash | time_info_type_hash
+    let mut consumer_args = time_index_type_hash.as_bytes().to_vec();
+    consumer_args.extend_from_slice(&time_info_type_hash.as_bytes());
+    let consumer_script = Script::new_builder()
+        .code_hash(consumer_code_hash)
+        .hash_type(ScriptHashType::Data.into())
+        .args(Bytes::from(consumer_args).pack())
+        .build();
+    let consumer_tx = |timestamp: u64| {
+        let output = CellOutput::new_builder()
+            .lock(wallet.lock().clone())
+            .type_(Some(consumer_script.clone()).pack())
+            .build();
+        let capacity = output
+            .occupied_capacity(Capacity::bytes(8).expect("capacity"))
+            .expect("capacity");
+        TransactionBuilder::default()
+            .output(output.as_builder().capacity(capacity.pack()).build())
+            .output_data(Bytes::from(timestamp.to_le_bytes().to_vec()).pack())
+            .cell_dep(
+                CellDep::new_builder()
+                    .out_point(OutPoint::new(consumer_tx_hash.clone(), 0))
+                    .build(),
+            )
+            .cell_dep(
+                CellDep::new_builder()
+                    .out_point(updater.time_index_cell().clone())
+                    .build(),
+            )
+            .cell_dep(
+                CellDep::new_builder()
+                    .out_point(info_out_point.clone())
+                    .build(),
+            )
+            .build()
+    };
+    let forged_tx = consumer_tx(timestamp + 1);
+    let tx = consumer_tx(timestamp);
+
+    // a wrong timestamp is rejected by the consumer
+    match wallet.try_send(&chain, forged_tx) {
+        Err(Error::Rpc(err))
+            if err.contains(&format!(
+                "ValidationFailure({})",
+                CONSUMER_INVALID_TIMESTAMP
+            )) => {}
+        Err(err) => return Err(err),
+        Ok(tx_hash) => {
+            return Err(Error::Check(format!(
+                "forged timestamp accepted in {}",
+                tx_hash
+            )))
+        }
+    }
+    wallet.send(&chain, tx)?;
+
+    Ok(ScenarioReport {
+        updates,
+        consumer_timestamp: timestamp,
+    })
+}
@@ -0,0 +1,150 @@
+use std::time::Duration;
+
+use ckb_tool::{
+    ckb_crypto::secp::Privkey,
+    ckb_hash::blake2b_256,
+    ckb_types::{
+        core::{Capacity, DepType, ScriptHashType, TransactionView},
+        packed::{Byte32, CellDep, CellInput, CellOutput, OutPoint, Script},
+        prelude::*,
+        H256,
+    },
+};
+use updater::signer::Signer;
+
+use crate::{
+    chain::{DevChain, SECP256K1_CODE_HASH},
+    error::Error,
+};
+
+// the secp256k1 dep group is the first output of the second genesis transaction
+const SECP256K1_DEP_GROUP_TX_INDEX: usize = 1;
+const COMMIT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Lock args of the secp256k1 blake160 sighash all lock of `privkey`
+pub fn lock_args(privkey: &Privkey) -> [u8; 20] {
+    let pubkey = privkey.pubkey().expect("pubkey");
+    let mut args = [0u8; 20];
+    args.copy_from_slice(&blake2b_256(pubkey.serialize())[..20]);
+    args
+}
+
+/// A key owning one cell on the dev chain, every transaction spends the cell and sends the change back
+pub struct Wallet {
+    signer: Signer,
+    lock: Script,
+    secp256k1_dep: CellDep,
+    change: OutPoint,
+    fee: u64,
+}
+
+impl Wallet {
+    /// Take the cell issued to `privkey` in genesis
+    pub fn from_genesis(chain: &DevChain, privkey: Privkey, fee: u64) -> Result<Self, Error> {
+        let code_hash: H256 = SECP256K1_CODE_HASH[2..].parse().expect("code hash");
+        let lock = Script::new_builder()
+            .code_hash(code_hash.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(lock_args(&privkey).to_vec().pack())
+            .build();
+
+        let genesis = chain.genesis_block()?;
+        let transactions = genesis.transactions();
+        let issued = transactions[0]
+            .outputs()
+            .into_iter()
+            .position(|output| output.lock() == lock)
+            .ok_or_else(|| Error::Deployment("no genesis cell of the key".to_string()))?;
+        let secp256k1_dep = CellDep::new_builder()
+            .out_point(OutPoint::new(
+                transactions[SECP256K1_DEP_GROUP_TX_INDEX].hash(),
+                0,
+            ))
+            .dep_type(DepType::DepGroup.into())
+            .build();
+        Ok(Wallet {
+            signer: Signer::new(privkey),
+            lock,
+            secp256k1_dep,
+            change: OutPoint::new(transactions[0].hash(), issued as u32),
+            fee,
+        })
+    }
+
+    pub fn lock(&self) -> &Script {
+        &self.lock
+    }
+
+    pub fn secp256k1_dep(&self) -> &CellDep {
+        &self.secp256k1_dep
+    }
+
+    /// Out point of the cell spent by the next transaction
+    pub fn change(&self) -> &OutPoint {
+        &self.change
+    }
+
+    /// Spend the change cell in `tx`, add the change output, sign and send it, then wait until committed.
+    /// `tx` has no inputs or only the change cell, e.g. a create transaction built on `change()`.
+    pub fn send(&mut self, chain: &DevChain, tx: TransactionView) -> Result<Byte32, Error> {
+        let tx = self.complete(chain, tx)?;
+        let tx_hash = chain.send_transaction(&tx)?;
+        chain.wait_committed(&tx_hash, COMMIT_TIMEOUT)?;
+        self.change = OutPoint::new(tx_hash.clone(), tx.outputs().len() as u32 - 1);
+        Ok(tx_hash)
+    }
+
+    /// Same as `send`, but return the error of the node without waiting, the change is kept if rejected
+    pub fn try_send(&mut self, chain: &DevChain, tx: TransactionView) -> Result<Byte32, Error> {
+        let tx = self.complete(chain, tx)?;
+        let tx_hash = chain.send_transaction(&tx)?;
+        self.change = OutPoint::new(tx_hash.clone(), tx.outputs().len() as u32 - 1);
+        Ok(tx_hash)
+    }
+
+    fn complete(&self, chain: &DevChain, tx: TransactionView) -> Result<TransactionView, Error> {
+        let inputs: Vec<CellInput> = tx.inputs().into_iter().collect();
+        let tx = match inputs.len() {
+            0 => tx
+                .as_advanced_builder()
+                .input(CellInput::new(self.change.clone(), 0))
+                .build(),
+            1 if inputs[0].previous_output() == self.change => tx,
+            _ => {
+                return Err(Error::Deployment(
+                    "only the change cell can be spent".to_string(),
+                ))
+            }
+        };
+        let (change_output, _) = chain
+            .get_live_cell(&self.change)?
+            .ok_or_else(|| Error::Deployment(format!("change cell {} is not live", self.change)))?;
+        let input_capacity: u64 = change_output.capacity().unpack();
+        let output_capacity = tx
+            .outputs()
+            .into_iter()
+            .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+            .sum::<u64>();
+        let change_capacity = input_capacity
+            .checked_sub(output_capacity)
+            .and_then(|capacity| capacity.checked_sub(self.fee))
+            .ok_or(Error::Capacity)?;
+        let change = CellOutput::new_builder()
+            .lock(self.lock.clone())
+            .capacity(Capacity::shannons(change_capacity).pack())
+            .build();
+
+        let mut builder = tx
+            .as_advanced_builder()
+            .output(change)
+            .output_data(Default::default());
+        if !tx
+            .cell_deps()
+            .into_iter()
+            .any(|dep| dep == self.secp256k1_dep)
+        {
+            builder = builder.cell_dep(self.secp256k1_dep.clone());
+        }
+        Ok(self.signer.sign_tx(builder.build(), &[0])?)
+    }
+}
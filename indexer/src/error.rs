@@ -0,0 +1,35 @@
+use std::fmt;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    Rpc(String),
+    Db(String),
+    BlockNotFound(u64),
+    TypeScriptMismatch,
+    InvalidRecord,
+    Config(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "rpc error: {}", err),
+            Self::Db(err) => write!(f, "db error: {}", err),
+            Self::BlockNotFound(number) => write!(f, "block {} not found", number),
+            Self::TypeScriptMismatch => {
+                write!(f, "the database indexes another time index type script")
+            }
+            Self::InvalidRecord => write!(f, "invalid record in the database"),
+            Self::Config(err) => write!(f, "config error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<ckb_rocksdb::Error> for Error {
+    fn from(err: ckb_rocksdb::Error) -> Self {
+        Self::Db(err.to_string())
+    }
+}
@@ -0,0 +1,115 @@
+use ckb_tool::ckb_types::{
+    core::BlockView,
+    packed::{OutPoint, Script},
+};
+use tx_builder::{cell_data_lap, check_cell_data};
+
+use crate::{
+    error::Error,
+    source::BlockSource,
+    store::{StateRecord, Store},
+};
+
+/// Follows one time index cell along the blocks of `source` and saves its state transitions in `store`.
+/// Only blocks with `confirmations` blocks on top are indexed, so a fork shorter than that is never seen.
+pub struct Indexer<S> {
+    source: S,
+    store: Store,
+    type_script: Script,
+    confirmations: u64,
+    start_block: u64,
+}
+
+impl<S: BlockSource> Indexer<S> {
+    /// The store is bound to `type_script` on first use and cannot index another one
+    pub fn new(
+        source: S,
+        store: Store,
+        type_script: Script,
+        confirmations: u64,
+    ) -> Result<Self, Error> {
+        match store.type_script()? {
+            Some(indexed) if indexed != type_script => return Err(Error::TypeScriptMismatch),
+            Some(_) => {}
+            None => store.set_type_script(&type_script)?,
+        }
+        Ok(Indexer {
+            source,
+            store,
+            type_script,
+            confirmations,
+            start_block: 0,
+        })
+    }
+
+    /// Start indexing an empty store at `start_block`, e.g. the block creating the time index cell,
+    /// instead of the genesis block. A store already indexed goes on after its tip.
+    pub fn from_block(mut self, start_block: u64) -> Self {
+        self.start_block = start_block;
+        self
+    }
+
+    pub fn store(&self) -> &Store {
+        &self.store
+    }
+
+    /// Index the confirmed blocks after the last indexed one, return the number of indexed blocks
+    pub fn sync(&mut self) -> Result<u64, Error> {
+        let tip = self.source.get_tip_block_number()?;
+        let target = match tip.checked_sub(self.confirmations) {
+            Some(target) => target,
+            None => return Ok(0),
+        };
+        let start = match self.store.tip()? {
+            Some(number) => number + 1,
+            None => self.start_block,
+        };
+        let mut current = self.store.current()?.map(|record| record.out_point);
+        for number in start..=target {
+            let block = self
+                .source
+                .get_block(number)?
+                .ok_or(Error::BlockNotFound(number))?;
+            let records = self.index_block(&block, &mut current);
+            self.store.commit_block(number, &records)?;
+        }
+        Ok((target + 1).saturating_sub(start))
+    }
+
+    // a transaction creating a cell of the type script is a transition if it consumes the current cell,
+    // the first one creates the time index cell
+    fn index_block(&self, block: &BlockView, current: &mut Option<OutPoint>) -> Vec<StateRecord> {
+        let mut records = Vec::new();
+        for (tx_index, tx) in block.transactions().iter().enumerate() {
+            if let Some(out_point) = current.as_ref() {
+                if !tx.input_pts_iter().any(|input| &input == out_point) {
+                    continue;
+                }
+            }
+            let created = tx
+                .outputs_with_data_iter()
+                .enumerate()
+                .find(|(_, (output, _))| {
+                    output.type_().to_opt().as_ref() == Some(&self.type_script)
+                });
+            let (output_index, data) = match created {
+                Some((output_index, (_, data))) if check_cell_data(&data).is_ok() => {
+                    (output_index, data)
+                }
+                _ => continue,
+            };
+            let out_point = OutPoint::new(tx.hash(), output_index as u32);
+            records.push(StateRecord {
+                block_number: block.number(),
+                tx_index: tx_index as u32,
+                out_point: out_point.clone(),
+                index: data[0],
+                n: data[1],
+                timestamp: block.timestamp(),
+                lap: cell_data_lap(&data),
+            });
+            *current = Some(out_point);
+        }
+        records
+    }
+}
@@ -0,0 +1,7 @@
+mod error;
+mod indexer;
+pub mod source;
+pub mod store;
+
+pub use error::Error;
+pub use indexer::Indexer;
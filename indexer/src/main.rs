@@ -0,0 +1,214 @@
+use std::{path::Path, process, thread, time::Duration};
+
+use ckb_tool::ckb_types::{core::ScriptHashType, packed::Script, prelude::*, H256};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use indexer::{
+    source::RpcBlockSource,
+    store::{StateRecord, Store},
+    Error, Indexer,
+};
+use tx_builder::decode_hex;
+
+const DEFAULT_CONFIRMATIONS: &str = "24";
+
+fn db_arg<'a, 'b>() -> Arg<'a, 'b> {
+    Arg::with_name("db")
+        .long("db")
+        .takes_value(true)
+        .required(true)
+        .help("Path of the database")
+}
+
+fn main() {
+    let matches = App::new("indexer")
+        .about("Index the state transitions of a time index cell")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("sync")
+                .about("Index the blocks of a CKB node")
+                .arg(db_arg())
+                .arg(
+                    Arg::with_name("rpc")
+                        .long("rpc")
+                        .takes_value(true)
+                        .required(true)
+                        .help("RPC url of the CKB node"),
+                )
+                .arg(
+                    Arg::with_name("code-hash")
+                        .long("code-hash")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Code hash of the type script"),
+                )
+                .arg(
+                    Arg::with_name("hash-type")
+                        .long("hash-type")
+                        .takes_value(true)
+                        .possible_values(&["data", "type"])
+                        .default_value("type"),
+                )
+                .arg(
+                    Arg::with_name("args")
+                        .long("args")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Args of the type script"),
+                )
+                .arg(
+                    Arg::with_name("confirmations")
+                        .long("confirmations")
+                        .takes_value(true)
+                        .default_value(DEFAULT_CONFIRMATIONS)
+                        .help("Blocks on top of a block before it is indexed"),
+                )
+                .arg(
+                    Arg::with_name("from-block")
+                        .long("from-block")
+                        .takes_value(true)
+                        .help(
+                            "Block to start an empty database at, e.g. the block creating the cell",
+                        ),
+                )
+                .arg(
+                    Arg::with_name("watch")
+                        .long("watch")
+                        .takes_value(true)
+                        .help("Keep syncing every given seconds"),
+                ),
+        )
+        .subcommand(
+            SubCommand::with_name("current")
+                .about("Print the live time index cell")
+                .arg(db_arg()),
+        )
+        .subcommand(
+            SubCommand::with_name("at")
+                .about("Print the time index cell live at the end of a block")
+                .arg(db_arg())
+                .arg(Arg::with_name("block").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("gaps")
+                .about("Print the updates more than the given minutes apart")
+                .arg(db_arg())
+                .arg(
+                    Arg::with_name("minutes")
+                        .long("minutes")
+                        .takes_value(true)
+                        .required(true),
+                ),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("sync", Some(m)) => sync(m),
+        ("current", Some(m)) => current(m),
+        ("at", Some(m)) => state_at(m),
+        ("gaps", Some(m)) => gaps(m),
+        _ => Ok(()),
+    };
+    if let Err(err) = result {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn open_store(m: &ArgMatches) -> Result<Store, Error> {
+    Store::open(Path::new(m.value_of("db").expect("db")))
+}
+
+fn parse_number(m: &ArgMatches, name: &str) -> Result<u64, Error> {
+    let value = m.value_of(name).expect(name);
+    value
+        .parse()
+        .map_err(|_| Error::Config(format!("invalid {} {}", name, value)))
+}
+
+fn sync(m: &ArgMatches) -> Result<(), Error> {
+    let code_hash: H256 = m
+        .value_of("code-hash")
+        .expect("code hash")
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|_| Error::Config("invalid code hash".to_string()))?;
+    let hash_type = match m.value_of("hash-type") {
+        Some("data") => ScriptHashType::Data,
+        _ => ScriptHashType::Type,
+    };
+    let type_script = Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(hash_type.into())
+        .args(
+            decode_hex(m.value_of("args").expect("args"))
+                .map_err(|err| Error::Config(err.to_string()))?
+                .pack(),
+        )
+        .build();
+    let source = RpcBlockSource::new(m.value_of("rpc").expect("rpc"));
+    let confirmations = parse_number(m, "confirmations")?;
+    let mut indexer = Indexer::new(source, open_store(m)?, type_script, confirmations)?;
+    if m.is_present("from-block") {
+        indexer = indexer.from_block(parse_number(m, "from-block")?);
+    }
+    loop {
+        let blocks = indexer.sync()?;
+        if let Some(tip) = indexer.store().tip()? {
+            println!("indexed {} blocks, tip {}", blocks, tip);
+        }
+        match m.value_of("watch") {
+            Some(_) => thread::sleep(Duration::from_secs(parse_number(m, "watch")?)),
+            None => return Ok(()),
+        }
+    }
+}
+
+fn print_record(record: &StateRecord) {
+    let lap = match record.lap {
+        Some(lap) => format!(" lap {}", lap),
+        None => String::new(),
+    };
+    println!(
+        "block {} tx {} index {}/{}{} timestamp {} cell {}",
+        record.block_number,
+        record.tx_hash(),
+        record.index,
+        record.n,
+        lap,
+        record.timestamp,
+        record.out_point
+    );
+}
+
+fn current(m: &ArgMatches) -> Result<(), Error> {
+    match open_store(m)?.current()? {
+        Some(record) => print_record(&record),
+        None => println!("no time index cell indexed"),
+    }
+    Ok(())
+}
+
+fn state_at(m: &ArgMatches) -> Result<(), Error> {
+    let block_number = parse_number(m, "block")?;
+    match open_store(m)?.state_at(block_number)? {
+        Some(record) => print_record(&record),
+        None => println!("no time index cell at block {}", block_number),
+    }
+    Ok(())
+}
+
+fn gaps(m: &ArgMatches) -> Result<(), Error> {
+    let minutes = parse_number(m, "minutes")?;
+    let milliseconds = minutes
+        .checked_mul(60 * 1000)
+        .ok_or_else(|| Error::Config(format!("invalid minutes {}", minutes)))?;
+    for gap in open_store(m)?.gaps(milliseconds)? {
+        println!(
+            "{} minutes between block {} and block {}",
+            gap.duration / 60 / 1000,
+            gap.from.block_number,
+            gap.to.block_number
+        );
+    }
+    Ok(())
+}
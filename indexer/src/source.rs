@@ -0,0 +1,43 @@
+use ckb_tool::{ckb_types::core::BlockView, rpc_client::RpcClient};
+
+use crate::error::Error;
+
+/// Blocks read by the indexer, implemented by `RpcBlockSource` for a CKB node
+pub trait BlockSource {
+    /// Return number of the tip block
+    fn get_tip_block_number(&self) -> Result<u64, Error>;
+
+    /// Return the block on the main chain at `number`, or none if the chain is shorter
+    fn get_block(&self, number: u64) -> Result<Option<BlockView>, Error>;
+}
+
+/// JSON-RPC client of a CKB node
+pub struct RpcBlockSource {
+    client: RpcClient,
+}
+
+impl RpcBlockSource {
+    pub fn new(uri: &str) -> Self {
+        RpcBlockSource {
+            client: RpcClient::new(uri),
+        }
+    }
+}
+
+impl BlockSource for RpcBlockSource {
+    fn get_tip_block_number(&self) -> Result<u64, Error> {
+        self.client
+            .inner()
+            .get_tip_block_number()
+            .map(Into::into)
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    fn get_block(&self, number: u64) -> Result<Option<BlockView>, Error> {
+        self.client
+            .inner()
+            .get_block_by_number(number.into())
+            .map(|block| block.map(Into::into))
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+}
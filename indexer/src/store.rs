@@ -0,0 +1,207 @@
+use std::{convert::TryInto, path::Path};
+
+use ckb_rocksdb::{prelude::*, Direction, IteratorMode, WriteBatch, DB};
+use ckb_tool::ckb_types::{
+    packed::{Byte32, OutPoint, Script},
+    prelude::*,
+};
+
+use crate::error::Error;
+
+// keys are prefixed by the kind of the value, the state records are ordered by
+// `block_number | tx_index` in big endian
+const KEY_TYPE_SCRIPT: &[u8] = &[0];
+const KEY_TIP: &[u8] = &[1];
+const PREFIX_RECORD: u8 = 2;
+const RECORD_KEY_LEN: usize = 1 + 8 + 4;
+// tx_hash | output_index as u32 | index | n | block timestamp as u64, followed by the lap as u64
+// for cell data with a lap
+const RECORD_VALUE_LEN: usize = 32 + 4 + 1 + 1 + 8;
+const RECORD_VALUE_WITH_LAP_LEN: usize = RECORD_VALUE_LEN + 8;
+
+/// A state transition of the time index cell, the cell created by a transaction
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateRecord {
+    pub block_number: u64,
+    pub tx_index: u32,
+    /// Out point of the time index cell created by the transaction
+    pub out_point: OutPoint,
+    pub index: u8,
+    pub n: u8,
+    /// Timestamp of the block in milliseconds
+    pub timestamp: u64,
+    /// Lap of the index, none for cell data older than the lap
+    pub lap: Option<u64>,
+}
+
+impl StateRecord {
+    pub fn tx_hash(&self) -> Byte32 {
+        self.out_point.tx_hash()
+    }
+
+    fn key(&self) -> Vec<u8> {
+        record_key(self.block_number, self.tx_index)
+    }
+
+    fn value(&self) -> Vec<u8> {
+        let output_index: u32 = self.out_point.index().unpack();
+        let mut value = self.out_point.tx_hash().as_slice().to_vec();
+        value.extend_from_slice(&output_index.to_le_bytes());
+        value.push(self.index);
+        value.push(self.n);
+        value.extend_from_slice(&self.timestamp.to_le_bytes());
+        if let Some(lap) = self.lap {
+            value.extend_from_slice(&lap.to_le_bytes());
+        }
+        value
+    }
+
+    fn decode(key: &[u8], value: &[u8]) -> Result<Self, Error> {
+        if key.len() != RECORD_KEY_LEN
+            || (value.len() != RECORD_VALUE_LEN && value.len() != RECORD_VALUE_WITH_LAP_LEN)
+        {
+            return Err(Error::InvalidRecord);
+        }
+        let tx_hash = Byte32::from_slice(&value[..32]).map_err(|_| Error::InvalidRecord)?;
+        let output_index = u32::from_le_bytes(value[32..36].try_into().unwrap());
+        Ok(StateRecord {
+            block_number: u64::from_be_bytes(key[1..9].try_into().unwrap()),
+            tx_index: u32::from_be_bytes(key[9..].try_into().unwrap()),
+            out_point: OutPoint::new(tx_hash, output_index),
+            index: value[36],
+            n: value[37],
+            timestamp: u64::from_le_bytes(value[38..RECORD_VALUE_LEN].try_into().unwrap()),
+            lap: if value.len() == RECORD_VALUE_WITH_LAP_LEN {
+                Some(u64::from_le_bytes(
+                    value[RECORD_VALUE_LEN..].try_into().unwrap(),
+                ))
+            } else {
+                None
+            },
+        })
+    }
+}
+
+/// Time between two consecutive state transitions
+#[derive(Debug, Clone, PartialEq)]
+pub struct Gap {
+    pub from: StateRecord,
+    pub to: StateRecord,
+    /// Duration in milliseconds
+    pub duration: u64,
+}
+
+fn record_key(block_number: u64, tx_index: u32) -> Vec<u8> {
+    let mut key = vec![PREFIX_RECORD];
+    key.extend_from_slice(&block_number.to_be_bytes());
+    key.extend_from_slice(&tx_index.to_be_bytes());
+    key
+}
+
+/// State records of one time index cell in a RocksDB database
+pub struct Store {
+    db: DB,
+}
+
+impl Store {
+    pub fn open(path: &Path) -> Result<Self, Error> {
+        Ok(Store {
+            db: DB::open_default(path)?,
+        })
+    }
+
+    /// Type script of the indexed time index cell, set by the first sync
+    pub fn type_script(&self) -> Result<Option<Script>, Error> {
+        match self.db.get(KEY_TYPE_SCRIPT)? {
+            Some(value) => Script::from_slice(&value)
+                .map(Some)
+                .map_err(|_| Error::InvalidRecord),
+            None => Ok(None),
+        }
+    }
+
+    pub fn set_type_script(&self, type_script: &Script) -> Result<(), Error> {
+        self.db.put(KEY_TYPE_SCRIPT, type_script.as_slice())?;
+        Ok(())
+    }
+
+    /// Number of the last indexed block
+    pub fn tip(&self) -> Result<Option<u64>, Error> {
+        match self.db.get(KEY_TIP)? {
+            Some(value) => {
+                let number = value[..].try_into().map_err(|_| Error::InvalidRecord)?;
+                Ok(Some(u64::from_le_bytes(number)))
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Save the records of a block and move the tip to it in one write
+    pub fn commit_block(&self, block_number: u64, records: &[StateRecord]) -> Result<(), Error> {
+        let mut batch = WriteBatch::default();
+        for record in records {
+            batch.put(record.key(), record.value())?;
+        }
+        batch.put(KEY_TIP, block_number.to_le_bytes())?;
+        self.db.write(&batch)?;
+        Ok(())
+    }
+
+    /// All state records from the oldest
+    pub fn records(&self) -> Result<Vec<StateRecord>, Error> {
+        let start = [PREFIX_RECORD];
+        self.db
+            .iterator(IteratorMode::From(&start, Direction::Forward))
+            .take_while(|(key, _)| key[0] == PREFIX_RECORD)
+            .map(|(key, value)| StateRecord::decode(&key, &value))
+            .collect()
+    }
+
+    /// The live time index cell as of the last indexed block
+    pub fn current(&self) -> Result<Option<StateRecord>, Error> {
+        self.state_at(u64::MAX)
+    }
+
+    /// The time index cell live at the end of block `block_number`
+    pub fn state_at(&self, block_number: u64) -> Result<Option<StateRecord>, Error> {
+        let start = record_key(block_number, u32::MAX);
+        match self
+            .db
+            .iterator(IteratorMode::From(&start, Direction::Reverse))
+            .next()
+        {
+            Some((key, value)) if key[0] == PREFIX_RECORD => {
+                StateRecord::decode(&key, &value).map(Some)
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// The state `index` of `lap`, the same index of another lap is a different state
+    pub fn state_of(&self, lap: u64, index: u8) -> Result<Option<StateRecord>, Error> {
+        Ok(self
+            .records()?
+            .into_iter()
+            .find(|record| record.lap == Some(lap) && record.index == index))
+    }
+
+    /// Consecutive state transitions more than `min_duration` milliseconds apart
+    pub fn gaps(&self, min_duration: u64) -> Result<Vec<Gap>, Error> {
+        let records = self.records()?;
+        Ok(records
+            .windows(2)
+            .filter_map(|pair| {
+                let duration = pair[1].timestamp.saturating_sub(pair[0].timestamp);
+                if duration > min_duration {
+                    Some(Gap {
+                        from: pair[0].clone(),
+                        to: pair[1].clone(),
+                        duration,
+                    })
+                } else {
+                    None
+                }
+            })
+            .collect())
+    }
+}
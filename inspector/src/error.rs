@@ -0,0 +1,21 @@
+use std::fmt;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    Io(String),
+    Json(String),
+    Hex(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Json(err) => write!(f, "json error: {}", err),
+            Self::Hex(err) => write!(f, "hex error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,67 @@
+use std::fmt;
+
+/// Error codes returned by time_index_state_type_script
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ErrorCode {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidArgument,
+    InvalidTimeIndexInput,
+    InvalidTimeIndexOutput,
+    InvalidCellData,
+    InvalidDataVersion,
+    InvalidSince,
+    InvalidBlockNumber,
+    InvalidEpoch,
+    InvalidLap,
+}
+
+impl ErrorCode {
+    pub fn from_code(code: i8) -> Option<Self> {
+        use ErrorCode::*;
+        match code {
+            1 => Some(IndexOutOfBound),
+            2 => Some(ItemMissing),
+            3 => Some(LengthNotEnough),
+            4 => Some(Encoding),
+            5 => Some(InvalidArgument),
+            6 => Some(InvalidTimeIndexInput),
+            7 => Some(InvalidTimeIndexOutput),
+            8 => Some(InvalidCellData),
+            9 => Some(InvalidDataVersion),
+            10 => Some(InvalidSince),
+            11 => Some(InvalidBlockNumber),
+            12 => Some(InvalidEpoch),
+            13 => Some(InvalidLap),
+            _ => None,
+        }
+    }
+
+    pub fn code(self) -> i8 {
+        self as i8
+    }
+}
+
+impl From<tx_builder::Error> for ErrorCode {
+    fn from(err: tx_builder::Error) -> Self {
+        match err {
+            tx_builder::Error::InvalidDataVersion => ErrorCode::InvalidDataVersion,
+            tx_builder::Error::InvalidInterval => ErrorCode::InvalidArgument,
+            _ => ErrorCode::InvalidCellData,
+        }
+    }
+}
+
+impl From<time_index_state_validation::Error> for ErrorCode {
+    fn from(err: time_index_state_validation::Error) -> Self {
+        ErrorCode::from_code(err as i8).expect("error code")
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} ({:?})", self.code(), self)
+    }
+}
@@ -0,0 +1,187 @@
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::HeaderView,
+    packed::{Byte32, CellOutput, Script},
+    prelude::*,
+};
+use time_index_state_validation::{verify, CellLoader, Error, Source};
+
+use crate::{error_code::ErrorCode, mock_tx::MockTransaction};
+
+/// What the script does for the group, decided like `entry::main` of the contract
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    Create,
+    Update,
+}
+
+/// Result of a script group of time_index_state_type_script in the transaction
+pub struct GroupReport {
+    pub script: Script,
+    pub action: Action,
+    pub result: Result<(), ErrorCode>,
+}
+
+// runs the validation rules of the contract for `script` on the cells of the mock transaction
+struct MockTxLoader<'a> {
+    mock_tx: &'a MockTransaction,
+    script: &'a Script,
+}
+
+impl<'a> MockTxLoader<'a> {
+    fn cell(&self, index: usize, source: Source) -> Result<(CellOutput, Bytes), Error> {
+        match source {
+            Source::Input => self
+                .mock_tx
+                .inputs
+                .get(index)
+                .map(|input| (input.output.clone(), input.data.clone())),
+            Source::Output => self.mock_tx.tx.output_with_data(index),
+        }
+        .ok_or(Error::IndexOutOfBound)
+    }
+
+    // a header is only visible to the script if its hash is in header deps of the transaction
+    fn header_dep(&self, hash: &Byte32) -> Result<&HeaderView, Error> {
+        if !self.mock_tx.tx.header_deps_iter().any(|dep| &dep == hash) {
+            return Err(Error::ItemMissing);
+        }
+        self.mock_tx
+            .header_deps
+            .iter()
+            .find(|header| &header.hash() == hash)
+            .ok_or(Error::ItemMissing)
+    }
+
+    fn cells_len(&self, source: Source) -> usize {
+        match source {
+            Source::Input => self.mock_tx.inputs.len(),
+            Source::Output => self.mock_tx.tx.outputs().len(),
+        }
+    }
+}
+
+impl<'a> CellLoader for MockTxLoader<'a> {
+    fn load_script_hash(&self) -> Result<[u8; 32], Error> {
+        Ok(self.script.calc_script_hash().unpack())
+    }
+
+    fn load_script_args(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.script.args().raw_data().to_vec())
+    }
+
+    fn load_cell_type_hashes(&self, source: Source) -> Result<Vec<Option<[u8; 32]>>, Error> {
+        (0..self.cells_len(source))
+            .map(|index| {
+                let (output, _) = self.cell(index, source)?;
+                Ok(output
+                    .type_()
+                    .to_opt()
+                    .map(|script| script.calc_script_hash().unpack()))
+            })
+            .collect()
+    }
+
+    fn load_cell_type_args(&self, index: usize, source: Source) -> Result<Option<Vec<u8>>, Error> {
+        let (output, _) = self.cell(index, source)?;
+        Ok(output
+            .type_()
+            .to_opt()
+            .map(|script| script.args().raw_data().to_vec()))
+    }
+
+    fn load_cell_data(&self, index: usize, source: Source) -> Result<Vec<u8>, Error> {
+        let (_, data) = self.cell(index, source)?;
+        Ok(data.to_vec())
+    }
+
+    fn load_input_out_point(&self, index: usize) -> Result<Vec<u8>, Error> {
+        self.mock_tx
+            .tx
+            .inputs()
+            .get(index)
+            .map(|input| input.previous_output().as_slice().to_vec())
+            .ok_or(Error::IndexOutOfBound)
+    }
+
+    fn load_input_since(&self, index: usize) -> Result<u64, Error> {
+        self.mock_tx
+            .tx
+            .inputs()
+            .get(index)
+            .map(|input| input.since().unpack())
+            .ok_or(Error::IndexOutOfBound)
+    }
+
+    fn load_input_block_number(&self, index: usize) -> Result<u64, Error> {
+        let input = self
+            .mock_tx
+            .inputs
+            .get(index)
+            .ok_or(Error::IndexOutOfBound)?;
+        let hash = input.header.as_ref().ok_or(Error::ItemMissing)?;
+        Ok(self.header_dep(hash)?.number())
+    }
+
+    fn load_header_dep_block_numbers(&self) -> Result<Vec<u64>, Error> {
+        self.mock_tx
+            .tx
+            .header_deps_iter()
+            .map(|hash| Ok(self.header_dep(&hash)?.number()))
+            .collect()
+    }
+
+    fn load_header_dep_epochs(&self) -> Result<Vec<u64>, Error> {
+        self.mock_tx
+            .tx
+            .header_deps_iter()
+            .map(|hash| Ok(self.header_dep(&hash)?.epoch().full_value()))
+            .collect()
+    }
+}
+
+/// Check the transaction against the rules of `create()` and `update()` of the contract,
+/// one report for each type script with `code_hash`
+pub fn explain_tx(mock_tx: &MockTransaction, code_hash: &Byte32) -> Vec<GroupReport> {
+    let input_scripts = mock_tx
+        .inputs
+        .iter()
+        .filter_map(|input| input.output.type_().to_opt());
+    let output_scripts = mock_tx
+        .tx
+        .outputs()
+        .into_iter()
+        .filter_map(|output| output.type_().to_opt());
+
+    let mut scripts: Vec<Script> = Vec::new();
+    for script in input_scripts.chain(output_scripts) {
+        if &script.code_hash() == code_hash && !scripts.contains(&script) {
+            scripts.push(script);
+        }
+    }
+
+    scripts
+        .into_iter()
+        .map(|script| {
+            let is_update = mock_tx
+                .inputs
+                .iter()
+                .any(|input| input.output.type_().to_opt().as_ref() == Some(&script));
+            let action = if is_update {
+                Action::Update
+            } else {
+                Action::Create
+            };
+            let loader = MockTxLoader {
+                mock_tx,
+                script: &script,
+            };
+            let result = verify(&loader).map_err(Into::into);
+            GroupReport {
+                script,
+                action,
+                result,
+            }
+        })
+        .collect()
+}
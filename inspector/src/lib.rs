@@ -0,0 +1,63 @@
+use ckb_tool::ckb_types::{bytes::Bytes, packed::OutPoint, prelude::*};
+use time_index_state_validation::helper::{
+    check_cell_data, get_cell_data_epoch, get_cell_data_lap, get_cell_data_version, get_clock_mode,
+    TIME_INDEX_ARGS_LEN,
+};
+use tx_builder::next_cell_data;
+
+mod error;
+mod error_code;
+pub mod explain;
+pub mod mock_tx;
+
+pub use error::Error;
+pub use error_code::ErrorCode;
+pub use time_index_state_validation::helper::{ClockMode, Epoch};
+
+/// Decoded cell data of the time index cell
+#[derive(Debug, PartialEq)]
+pub struct TimeIndexState {
+    pub index: u8,
+    pub n: u8,
+    pub version: u8,
+    /// Turns of the index around the ring, from version 2
+    pub lap: Option<u64>,
+    /// Epoch recorded by an epoch clock
+    pub epoch: Option<Epoch>,
+}
+
+/// Decode and check the cell data with `helper::check_cell_data` of the contract
+pub fn decode_cell_data(data: &[u8]) -> Result<TimeIndexState, ErrorCode> {
+    check_cell_data(data).map_err(ErrorCode::from)?;
+    Ok(TimeIndexState {
+        index: data[0],
+        n: data[1],
+        version: get_cell_data_version(data),
+        lap: get_cell_data_lap(data),
+        epoch: get_cell_data_epoch(data).map(Epoch::from_u64),
+    })
+}
+
+/// Decoded args of the type script
+#[derive(Debug, PartialEq)]
+pub struct TimeIndexArgs {
+    /// Out point of the first input of the create transaction
+    pub out_point: OutPoint,
+    /// Update interval and its metric, none for a clock without interval
+    pub mode: Option<ClockMode>,
+}
+
+/// Decode the args of the type script with `helper::get_clock_mode` of the contract
+pub fn decode_args(args: &[u8]) -> Result<TimeIndexArgs, ErrorCode> {
+    let mode = get_clock_mode(args).map_err(ErrorCode::from)?;
+    let out_point = OutPoint::from_slice(&args[..TIME_INDEX_ARGS_LEN])
+        .map_err(|_| ErrorCode::InvalidArgument)?;
+    Ok(TimeIndexArgs { out_point, mode })
+}
+
+/// The only valid cell data the cell can be updated to, an epoch clock then records the epoch of a header dep.
+/// The cell at the last index of the last lap has none, the contract rejects the update with `InvalidLap`
+pub fn predict_next_cell_data(data: &[u8]) -> Result<Bytes, ErrorCode> {
+    check_cell_data(data).map_err(ErrorCode::from)?;
+    next_cell_data(data).map_err(|_| ErrorCode::InvalidLap)
+}
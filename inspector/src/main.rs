@@ -0,0 +1,142 @@
+use std::process;
+
+use ckb_tool::ckb_types::{packed::Byte32, prelude::*};
+use clap::{App, AppSettings, Arg, ArgMatches, SubCommand};
+use inspector::{
+    decode_args, decode_cell_data,
+    explain::{explain_tx, Action},
+    mock_tx::MockTransaction,
+    predict_next_cell_data, ClockMode, Error,
+};
+use tx_builder::decode_hex;
+
+fn main() {
+    let matches = App::new("inspector")
+        .about("Decode and check cells and transactions of time_index_state_type_script")
+        .setting(AppSettings::SubcommandRequiredElseHelp)
+        .subcommand(
+            SubCommand::with_name("data")
+                .about("Decode the cell data, check it and predict the next state")
+                .arg(Arg::with_name("hex").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("args")
+                .about("Decode the args of the type script")
+                .arg(Arg::with_name("hex").required(true)),
+        )
+        .subcommand(
+            SubCommand::with_name("tx")
+                .about("Explain whether a ckb-cli mock transaction passes the type script")
+                .arg(Arg::with_name("file").required(true))
+                .arg(
+                    Arg::with_name("code-hash")
+                        .long("code-hash")
+                        .takes_value(true)
+                        .required(true)
+                        .help("Code hash of the type script"),
+                ),
+        )
+        .get_matches();
+
+    let result = match matches.subcommand() {
+        ("data", Some(m)) => inspect_data(m),
+        ("args", Some(m)) => inspect_args(m),
+        ("tx", Some(m)) => inspect_tx(m),
+        _ => Ok(true),
+    };
+    match result {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(2);
+        }
+    }
+}
+
+fn hex_arg(m: &ArgMatches, name: &str) -> Result<Vec<u8>, Error> {
+    decode_hex(m.value_of(name).expect(name)).map_err(|err| Error::Hex(err.to_string()))
+}
+
+fn inspect_data(m: &ArgMatches) -> Result<bool, Error> {
+    let data = hex_arg(m, "hex")?;
+    match decode_cell_data(&data) {
+        Ok(state) => {
+            println!("index: {}", state.index);
+            println!("N: {}", state.n);
+            println!("version: {}", state.version);
+            if let Some(lap) = state.lap {
+                println!("lap: {}", lap);
+            }
+            if let Some(epoch) = state.epoch {
+                println!("epoch: {} {}/{}", epoch.number, epoch.index, epoch.length);
+            }
+            match predict_next_cell_data(&data) {
+                Ok(next_data) => println!("next: 0x{}", to_hex(&next_data)),
+                Err(code) => println!("next: none, error {}", code),
+            }
+            Ok(true)
+        }
+        Err(code) => {
+            println!("invalid cell data, error {}", code);
+            Ok(false)
+        }
+    }
+}
+
+fn inspect_args(m: &ArgMatches) -> Result<bool, Error> {
+    let args = hex_arg(m, "hex")?;
+    match decode_args(&args) {
+        Ok(args) => {
+            let index: u32 = args.out_point.index().unpack();
+            println!("tx_hash: {}", args.out_point.tx_hash());
+            println!("index: {}", index);
+            match args.mode {
+                Some(ClockMode::Timestamp(interval)) => println!("interval: {} seconds", interval),
+                Some(ClockMode::BlockNumber(interval)) => println!("interval: {} blocks", interval),
+                Some(ClockMode::Epoch(interval)) => println!("interval: {} epochs", interval),
+                None => {}
+            }
+            Ok(true)
+        }
+        Err(code) => {
+            println!("invalid args, error {}", code);
+            Ok(false)
+        }
+    }
+}
+
+fn inspect_tx(m: &ArgMatches) -> Result<bool, Error> {
+    let mock_tx = MockTransaction::load(m.value_of("file").expect("file"))?;
+    let code_hash = hex_arg(m, "code-hash")?;
+    let code_hash = Byte32::from_slice(&code_hash).map_err(|err| Error::Hex(err.to_string()))?;
+
+    let reports = explain_tx(&mock_tx, &code_hash);
+    if reports.is_empty() {
+        println!("no time index cell in the transaction");
+    }
+    let mut pass = true;
+    for report in reports {
+        let action = match report.action {
+            Action::Create => "create",
+            Action::Update => "update",
+        };
+        match report.result {
+            Ok(()) => println!("{} {}: pass", report.script.calc_script_hash(), action),
+            Err(code) => {
+                pass = false;
+                println!(
+                    "{} {}: fail, error {}",
+                    report.script.calc_script_hash(),
+                    action,
+                    code
+                );
+            }
+        }
+    }
+    Ok(pass)
+}
+
+fn to_hex(data: &[u8]) -> String {
+    faster_hex::hex_string(data).expect("hex")
+}
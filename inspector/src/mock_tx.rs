@@ -0,0 +1,151 @@
+use std::fs;
+
+use ckb_tool::{
+    ckb_jsonrpc_types as json_types,
+    ckb_types::{
+        bytes::Bytes,
+        core::{HeaderView, TransactionView},
+        packed::{Byte32, CellDep, CellInput, CellOutput, Transaction},
+        prelude::*,
+        H256,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+// json format of `ckb-cli mock-tx`
+#[derive(Serialize, Deserialize)]
+struct ReprMockInput {
+    input: json_types::CellInput,
+    output: json_types::CellOutput,
+    data: json_types::JsonBytes,
+    header: Option<H256>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReprMockCellDep {
+    cell_dep: json_types::CellDep,
+    output: json_types::CellOutput,
+    data: json_types::JsonBytes,
+    header: Option<H256>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReprMockInfo {
+    inputs: Vec<ReprMockInput>,
+    cell_deps: Vec<ReprMockCellDep>,
+    header_deps: Vec<json_types::HeaderView>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct ReprMockTransaction {
+    mock_info: ReprMockInfo,
+    tx: json_types::Transaction,
+}
+
+pub struct MockInput {
+    pub input: CellInput,
+    pub output: CellOutput,
+    pub data: Bytes,
+    pub header: Option<Byte32>,
+}
+
+pub struct MockCellDep {
+    pub cell_dep: CellDep,
+    pub output: CellOutput,
+    pub data: Bytes,
+    pub header: Option<Byte32>,
+}
+
+/// A transaction with all the cells and headers it references
+pub struct MockTransaction {
+    pub inputs: Vec<MockInput>,
+    pub cell_deps: Vec<MockCellDep>,
+    pub header_deps: Vec<HeaderView>,
+    pub tx: TransactionView,
+}
+
+impl MockTransaction {
+    pub fn from_json(json: &str) -> Result<Self, Error> {
+        let repr: ReprMockTransaction =
+            serde_json::from_str(json).map_err(|err| Error::Json(err.to_string()))?;
+        let inputs = repr
+            .mock_info
+            .inputs
+            .into_iter()
+            .map(|input| MockInput {
+                input: input.input.into(),
+                output: input.output.into(),
+                data: input.data.into_bytes(),
+                header: input.header.map(|hash| hash.pack()),
+            })
+            .collect();
+        let cell_deps = repr
+            .mock_info
+            .cell_deps
+            .into_iter()
+            .map(|cell_dep| MockCellDep {
+                cell_dep: cell_dep.cell_dep.into(),
+                output: cell_dep.output.into(),
+                data: cell_dep.data.into_bytes(),
+                header: cell_dep.header.map(|hash| hash.pack()),
+            })
+            .collect();
+        let header_deps = repr
+            .mock_info
+            .header_deps
+            .into_iter()
+            .map(Into::into)
+            .collect();
+        let tx: Transaction = repr.tx.into();
+        Ok(MockTransaction {
+            inputs,
+            cell_deps,
+            header_deps,
+            tx: tx.into_view(),
+        })
+    }
+
+    pub fn load(path: &str) -> Result<Self, Error> {
+        let json = fs::read_to_string(path).map_err(|err| Error::Io(err.to_string()))?;
+        Self::from_json(&json)
+    }
+
+    pub fn to_json(&self) -> Result<String, Error> {
+        let inputs = self
+            .inputs
+            .iter()
+            .map(|input| ReprMockInput {
+                input: input.input.clone().into(),
+                output: input.output.clone().into(),
+                data: json_types::JsonBytes::from_bytes(input.data.clone()),
+                header: input.header.as_ref().map(|hash| hash.unpack()),
+            })
+            .collect();
+        let cell_deps = self
+            .cell_deps
+            .iter()
+            .map(|cell_dep| ReprMockCellDep {
+                cell_dep: cell_dep.cell_dep.clone().into(),
+                output: cell_dep.output.clone().into(),
+                data: json_types::JsonBytes::from_bytes(cell_dep.data.clone()),
+                header: cell_dep.header.as_ref().map(|hash| hash.unpack()),
+            })
+            .collect();
+        let header_deps = self
+            .header_deps
+            .iter()
+            .map(|header| header.clone().into())
+            .collect();
+        let repr = ReprMockTransaction {
+            mock_info: ReprMockInfo {
+                inputs,
+                cell_deps,
+                header_deps,
+            },
+            tx: self.tx.data().into(),
+        };
+        serde_json::to_string_pretty(&repr).map_err(|err| Error::Json(err.to_string()))
+    }
+}
@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    Rpc(String),
+    Io(String),
+    Config(String),
+    Json(String),
+    Capacity,
+    TxBuilder(tx_builder::Error),
+    Sign(String),
+    Migration(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "rpc error: {}", err),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Config(err) => write!(f, "config error: {}", err),
+            Self::Json(err) => write!(f, "json error: {}", err),
+            Self::Capacity => write!(f, "not enough capacity"),
+            Self::TxBuilder(err) => write!(f, "tx builder error: {}", err),
+            Self::Sign(err) => write!(f, "sign error: {}", err),
+            Self::Migration(err) => write!(f, "migration error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
+
+impl From<tx_builder::Error> for Error {
+    fn from(err: tx_builder::Error) -> Self {
+        Self::TxBuilder(err)
+    }
+}
+
+impl From<updater::Error> for Error {
+    fn from(err: updater::Error) -> Self {
+        Self::Sign(err.to_string())
+    }
+}
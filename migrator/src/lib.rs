@@ -0,0 +1,11 @@
+mod error;
+mod migrate;
+mod migration;
+pub mod rpc;
+
+pub use error::Error;
+pub use migrate::{
+    build_time_index_cells_tx, build_time_info_cell_data, migrate, MigrationPlan, TimeInfoCells,
+    TIME_INFO_CELL_DATA_LEN,
+};
+pub use migration::Migration;
@@ -0,0 +1,114 @@
+use std::{env, fs, path::Path, process, thread, time::Duration};
+
+use ckb_tool::{
+    ckb_crypto::secp::Privkey,
+    ckb_jsonrpc_types::{CellDep, OutPoint, Script, ScriptHashType},
+    ckb_types::{prelude::*, H256},
+};
+use migrator::{migrate, rpc::HttpRpc, rpc::TxStatus, Error, MigrationPlan};
+use serde::Deserialize;
+use tx_builder::{ClockMode, Contract};
+use updater::signer::Signer;
+
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+const SHANNONS_PER_CKB: u64 = 100_000_000;
+
+#[derive(Deserialize)]
+struct ContractDescription {
+    code_hash: H256,
+    hash_type: ScriptHashType,
+    cell_dep: CellDep,
+}
+
+#[derive(Deserialize)]
+struct Description {
+    rpc_url: String,
+    private_key: H256,
+    input: OutPoint,
+    fee: u64,
+    fee_reserve: u64,
+    interval: Option<u64>,
+    metric: Option<String>,
+    contract: ContractDescription,
+    time_info_contract: ContractDescription,
+    cell_deps: Vec<CellDep>,
+    time_index_lock: Option<Script>,
+    time_info_lock: Option<Script>,
+}
+
+impl From<ContractDescription> for Contract {
+    fn from(contract: ContractDescription) -> Self {
+        Contract {
+            code_hash: contract.code_hash.pack(),
+            hash_type: contract.hash_type.into(),
+            cell_dep: contract.cell_dep.into(),
+        }
+    }
+}
+
+fn load_description(path: &str) -> Result<Description, Error> {
+    let content = fs::read_to_string(path).map_err(|err| Error::Config(err.to_string()))?;
+    toml::from_str(&content).map_err(|err| Error::Config(err.to_string()))
+}
+
+// the interval is in seconds unless the metric is "blocks" or "epochs"
+fn clock_mode(interval: Option<u64>, metric: Option<&str>) -> Result<Option<ClockMode>, Error> {
+    match (interval, metric) {
+        (None, None) => Ok(None),
+        (Some(interval), None) | (Some(interval), Some("seconds")) => {
+            Ok(Some(ClockMode::Timestamp(interval)))
+        }
+        (Some(interval), Some("blocks")) => Ok(Some(ClockMode::BlockNumber(interval))),
+        (Some(interval), Some("epochs")) => Ok(Some(ClockMode::Epoch(interval))),
+        _ => Err(Error::Config(
+            "metric should be seconds, blocks or epochs, with an interval".to_string(),
+        )),
+    }
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    if args.len() != 3 {
+        eprintln!("usage: migrator <description.toml> <migration.json>");
+        process::exit(1);
+    }
+    if let Err(err) = run(&args[1], Path::new(&args[2])) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn run(description_path: &str, migration_path: &Path) -> Result<(), Error> {
+    let description = load_description(description_path)?;
+    let plan = MigrationPlan {
+        contract: description.contract.into(),
+        time_info_contract: description.time_info_contract.into(),
+        input: description.input.into(),
+        time_index_lock: description.time_index_lock.map(Into::into),
+        time_info_lock: description.time_info_lock.map(Into::into),
+        fee_reserve: description
+            .fee_reserve
+            .checked_mul(SHANNONS_PER_CKB)
+            .ok_or(Error::Capacity)?,
+        mode: clock_mode(description.interval, description.metric.as_deref())?,
+        fee: description.fee,
+        cell_deps: description.cell_deps.into_iter().map(Into::into).collect(),
+    };
+    let signer = Signer::new(Privkey::from(description.private_key));
+    let mut rpc = HttpRpc::new(&description.rpc_url);
+
+    loop {
+        let (migration, status) = migrate(&mut rpc, &signer, &plan, migration_path)?;
+        match status {
+            TxStatus::Committed => {
+                println!("time index cell: {}", migration.time_index_cell);
+                for (index, out_point) in migration.time_info_cells.iter().enumerate() {
+                    println!("time info cell {}: {}", index, out_point);
+                }
+                return Ok(());
+            }
+            _ => println!("waiting for transaction {}", migration.tx.hash()),
+        }
+        thread::sleep(POLL_INTERVAL);
+    }
+}
@@ -0,0 +1,294 @@
+use std::path::Path;
+
+use ckb_tool::{
+    ckb_hash::new_blake2b,
+    ckb_types::{
+        bytes::Bytes,
+        core::{Capacity, HeaderView, TransactionView},
+        packed::{Byte32, CellDep, CellDepVec, CellOutput, OutPoint, Script, ScriptOpt},
+        prelude::*,
+    },
+};
+use tx_builder::{
+    build_create_tx_in_block, build_create_tx_with_mode, build_time_info_args, ClockMode, Contract,
+    TIME_INDEX_CELL_DATA_N,
+};
+use updater::signer::Signer;
+
+use crate::{
+    error::Error,
+    migration::Migration,
+    rpc::{Rpc, TxStatus},
+};
+
+pub const TIME_INFO_CELL_DATA_LEN: usize = 9;
+
+/// Cells to create and how to pay for them
+pub struct MigrationPlan {
+    pub contract: Contract,
+    /// The deployed time_info_type_script typing the time info cells
+    pub time_info_contract: Contract,
+    /// Cell paying the capacity, its out point becomes the args of the type script
+    pub input: OutPoint,
+    /// Lock of the time index cell, the lock of the input if none
+    pub time_index_lock: Option<Script>,
+    /// Lock of the time info cells, the lock of the input if none
+    pub time_info_lock: Option<Script>,
+    /// Capacity in shannons kept by the time index cell to pay the fees of the updates
+    pub fee_reserve: u64,
+    /// Interval the clock waits between two updates, appended to the args of the type script, none for no limit
+    pub mode: Option<ClockMode>,
+    /// Fee of the create transaction in shannons
+    pub fee: u64,
+    /// Cell deps of the lock of the input, e.g. the secp256k1 dep group
+    pub cell_deps: Vec<CellDep>,
+}
+
+impl MigrationPlan {
+    /// Hash of every field of the plan, a migration file records the plan it was built from
+    pub fn hash(&self) -> Byte32 {
+        let mut hasher = new_blake2b();
+        for contract in &[&self.contract, &self.time_info_contract] {
+            hasher.update(contract.code_hash.as_slice());
+            hasher.update(&[contract.hash_type as u8]);
+            hasher.update(contract.cell_dep.as_slice());
+        }
+        hasher.update(self.input.as_slice());
+        for lock in &[&self.time_index_lock, &self.time_info_lock] {
+            hasher.update(
+                ScriptOpt::new_builder()
+                    .set((*lock).clone())
+                    .build()
+                    .as_slice(),
+            );
+        }
+        hasher.update(&self.fee_reserve.to_le_bytes());
+        let (metric, interval) = match self.mode {
+            None => (0u8, 0),
+            Some(ClockMode::Timestamp(interval)) => (1, interval),
+            Some(ClockMode::BlockNumber(interval)) => (2, interval),
+            Some(ClockMode::Epoch(interval)) => (3, interval),
+        };
+        hasher.update(&[metric]);
+        hasher.update(&interval.to_le_bytes());
+        hasher.update(&self.fee.to_le_bytes());
+        hasher.update(
+            CellDepVec::new_builder()
+                .set(self.cell_deps.clone())
+                .build()
+                .as_slice(),
+        );
+        let mut hash = [0u8; 32];
+        hasher.finalize(&mut hash);
+        hash.pack()
+    }
+}
+
+/// Time info cells created along the time index cell
+pub struct TimeInfoCells<'a> {
+    /// The deployed time_info_type_script
+    pub contract: &'a Contract,
+    pub lock: Script,
+    /// Timestamp recorded by every time info cell, in seconds
+    pub timestamp: u64,
+}
+
+/// Time info cell data, `index | timestamp as u64 in little endian`
+pub fn build_time_info_cell_data(index: u8, timestamp: u64) -> Bytes {
+    let mut data = vec![index];
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    Bytes::from(data)
+}
+
+/// Build the unsigned transaction creating the time index cell at index 0 as output 0 and the N time info cells
+/// as outputs 1 to N, each with its occupied capacity plus `fee_reserve` for the time index cell.
+/// The time info cells are typed by time_info_type_script with the type hash of the time index cell.
+/// An epoch clock records the epoch of `block`. The caller adds the output for the change of the input.
+pub fn build_time_index_cells_tx(
+    contract: &Contract,
+    input: OutPoint,
+    time_index_lock: Script,
+    time_info: &TimeInfoCells,
+    fee_reserve: u64,
+    mode: Option<ClockMode>,
+    block: Option<&HeaderView>,
+) -> Result<TransactionView, Error> {
+    let tx = match block {
+        Some(block) => build_create_tx_in_block(
+            contract,
+            input,
+            time_index_lock,
+            TIME_INDEX_CELL_DATA_N,
+            mode,
+            block,
+        )?,
+        None => build_create_tx_with_mode(
+            contract,
+            input,
+            time_index_lock,
+            TIME_INDEX_CELL_DATA_N,
+            mode,
+        )?,
+    };
+    let index_output = tx.outputs().get(0).expect("time index output");
+    let index_capacity = Capacity::shannons(index_output.capacity().unpack())
+        .safe_add(Capacity::shannons(fee_reserve))
+        .map_err(|_| Error::Capacity)?;
+    let mut outputs = vec![index_output
+        .clone()
+        .as_builder()
+        .capacity(index_capacity.pack())
+        .build()];
+    let mut outputs_data = vec![tx.outputs_data().get(0).expect("time index data")];
+    let time_index_type_script = index_output.type_().to_opt().expect("type script");
+    let time_info_type_script = time_info
+        .contract
+        .type_script(build_time_info_args(&time_index_type_script));
+    for index in 0..TIME_INDEX_CELL_DATA_N {
+        let output = CellOutput::new_builder()
+            .lock(time_info.lock.clone())
+            .type_(Some(time_info_type_script.clone()).pack())
+            .build();
+        let capacity = output
+            .occupied_capacity(
+                Capacity::bytes(TIME_INFO_CELL_DATA_LEN).map_err(|_| Error::Capacity)?,
+            )
+            .map_err(|_| Error::Capacity)?;
+        outputs.push(output.as_builder().capacity(capacity.pack()).build());
+        outputs_data.push(build_time_info_cell_data(index, time_info.timestamp).pack());
+    }
+    //both contracts may be in one dep group
+    let mut builder = tx.as_advanced_builder();
+    if time_info.contract.cell_dep != contract.cell_dep {
+        builder = builder.cell_dep(time_info.contract.cell_dep.clone());
+    }
+    Ok(builder
+        .set_outputs(outputs)
+        .set_outputs_data(outputs_data)
+        .build())
+}
+
+// the create transaction with the change back to the lock of the input, signed by `signer`
+fn build_migration(
+    rpc: &impl Rpc,
+    signer: &Signer,
+    plan: &MigrationPlan,
+) -> Result<Migration, Error> {
+    let (input_output, _) = rpc
+        .get_live_cell(&plan.input)?
+        .ok_or_else(|| Error::Migration(format!("input {} is not live", plan.input)))?;
+    let input_lock = input_output.lock();
+    //the time info cells record the timestamp of the tip, an epoch clock starts at its epoch
+    let tip = rpc.get_tip_header()?;
+    let block = match plan.mode {
+        Some(ClockMode::Epoch(_)) => Some(&tip),
+        _ => None,
+    };
+    let time_info = TimeInfoCells {
+        contract: &plan.time_info_contract,
+        lock: plan
+            .time_info_lock
+            .clone()
+            .unwrap_or_else(|| input_lock.clone()),
+        timestamp: tip.timestamp() / 1000,
+    };
+    let tx = build_time_index_cells_tx(
+        &plan.contract,
+        plan.input.clone(),
+        plan.time_index_lock
+            .clone()
+            .unwrap_or_else(|| input_lock.clone()),
+        &time_info,
+        plan.fee_reserve,
+        plan.mode,
+        block,
+    )?;
+
+    let input_capacity: u64 = input_output.capacity().unpack();
+    let output_capacity = tx
+        .outputs()
+        .into_iter()
+        .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+        .sum::<u64>();
+    let change_capacity = input_capacity
+        .checked_sub(output_capacity)
+        .and_then(|capacity| capacity.checked_sub(plan.fee))
+        .ok_or(Error::Capacity)?;
+    let change = CellOutput::new_builder()
+        .lock(input_lock)
+        .capacity(change_capacity.pack())
+        .build();
+    if change
+        .is_lack_of_capacity(Capacity::zero())
+        .map_err(|_| Error::Capacity)?
+    {
+        return Err(Error::Capacity);
+    }
+    let tx = tx
+        .as_advanced_builder()
+        .output(change)
+        .output_data(Bytes::new().pack())
+        .cell_deps(plan.cell_deps.clone())
+        .build();
+    let tx = signer.sign_tx(tx, &[0])?;
+    let type_script = tx
+        .outputs()
+        .get(0)
+        .and_then(|output| output.type_().to_opt())
+        .expect("type script");
+    Ok(Migration {
+        input: plan.input.clone(),
+        plan_hash: plan.hash(),
+        type_script,
+        time_index_cell: OutPoint::new(tx.hash(), 0),
+        time_info_cells: (1..=TIME_INDEX_CELL_DATA_N as u32)
+            .map(|index| OutPoint::new(tx.hash(), index))
+            .collect(),
+        tx,
+        committed: false,
+    })
+}
+
+/// Create the time index cell and the time info cells once, the migration at `path` records the transaction.
+/// A re-run with the same plan sends the recorded transaction again if the node lost it, waits if it is pending,
+/// and marks the migration committed once it is on chain, the input is never spent twice.
+/// A file recorded from another plan is an error, whichever field changed.
+pub fn migrate(
+    rpc: &mut impl Rpc,
+    signer: &Signer,
+    plan: &MigrationPlan,
+    path: &Path,
+) -> Result<(Migration, TxStatus), Error> {
+    let mut migration = if path.exists() {
+        let migration = Migration::load(path)?;
+        if migration.plan_hash != plan.hash() {
+            return Err(Error::Migration(format!(
+                "{} records another plan, with the input {}",
+                path.display(),
+                migration.input
+            )));
+        }
+        migration
+    } else {
+        // record the transaction before sending it, a crash in between is recovered by the next run
+        let migration = build_migration(rpc, signer, plan)?;
+        migration.save(path)?;
+        migration
+    };
+    if migration.committed {
+        return Ok((migration, TxStatus::Committed));
+    }
+
+    let status = match rpc.get_transaction_status(&migration.tx.hash())? {
+        TxStatus::Unknown => {
+            rpc.send_transaction(&migration.tx)?;
+            TxStatus::Pending
+        }
+        status => status,
+    };
+    if status == TxStatus::Committed {
+        migration.committed = true;
+        migration.save(path)?;
+    }
+    Ok((migration, status))
+}
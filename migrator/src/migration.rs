@@ -0,0 +1,98 @@
+use std::{fs, path::Path};
+
+use ckb_tool::{
+    ckb_jsonrpc_types as json,
+    ckb_types::{
+        core::TransactionView,
+        packed::{self, Byte32, OutPoint, Script},
+        prelude::*,
+        H256,
+    },
+};
+use serde::{Deserialize, Serialize};
+
+use crate::error::Error;
+
+/// Record of the create transaction of the time index cell and the time info cells
+#[derive(Debug, Clone, PartialEq)]
+pub struct Migration {
+    /// Input of the create transaction, the args of the type script
+    pub input: OutPoint,
+    /// Hash of the plan the transaction is built from
+    pub plan_hash: Byte32,
+    pub type_script: Script,
+    /// The signed create transaction, sent again if the node drops it
+    pub tx: TransactionView,
+    pub time_index_cell: OutPoint,
+    /// Time info cells ordered by index
+    pub time_info_cells: Vec<OutPoint>,
+    pub committed: bool,
+}
+
+#[derive(Serialize, Deserialize)]
+struct MigrationJson {
+    input: json::OutPoint,
+    plan_hash: H256,
+    type_script: json::Script,
+    tx_hash: H256,
+    transaction: json::Transaction,
+    time_index_cell: json::OutPoint,
+    time_info_cells: Vec<json::OutPoint>,
+    committed: bool,
+}
+
+impl Migration {
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let content = fs::read_to_string(path)?;
+        let migration: MigrationJson =
+            serde_json::from_str(&content).map_err(|err| Error::Json(err.to_string()))?;
+        let tx = packed::Transaction::from(migration.transaction).into_view();
+        if tx.hash() != migration.tx_hash.pack() {
+            return Err(Error::Migration(format!(
+                "transaction of {} does not match its hash",
+                path.display()
+            )));
+        }
+        Ok(Migration {
+            input: migration.input.into(),
+            plan_hash: migration.plan_hash.pack(),
+            type_script: migration.type_script.into(),
+            tx,
+            time_index_cell: migration.time_index_cell.into(),
+            time_info_cells: migration
+                .time_info_cells
+                .into_iter()
+                .map(Into::into)
+                .collect(),
+            committed: migration.committed,
+        })
+    }
+
+    /// Write the migration to a temporary file then move it to `path`, a crash never leaves half a file
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let migration = MigrationJson {
+            input: self.input.clone().into(),
+            plan_hash: self.plan_hash.unpack(),
+            type_script: self.type_script.clone().into(),
+            tx_hash: self.tx.hash().unpack(),
+            transaction: self.tx.data().into(),
+            time_index_cell: self.time_index_cell.clone().into(),
+            time_info_cells: self
+                .time_info_cells
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect(),
+            committed: self.committed,
+        };
+        let content =
+            serde_json::to_string_pretty(&migration).map_err(|err| Error::Json(err.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content)?;
+        fs::rename(&tmp_path, path)?;
+        Ok(())
+    }
+}
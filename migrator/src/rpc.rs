@@ -0,0 +1,98 @@
+use ckb_tool::{
+    ckb_jsonrpc_types::Status,
+    ckb_types::{
+        bytes::Bytes,
+        core::{HeaderView, TransactionView},
+        packed::{Byte32, CellOutput, OutPoint},
+        prelude::*,
+    },
+    rpc_client::RpcClient,
+};
+
+use crate::error::Error;
+
+const LIVE_CELL_STATUS: &str = "live";
+
+/// Status of a transaction known by the node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TxStatus {
+    Unknown,
+    Pending,
+    Committed,
+}
+
+/// Chain access used by the migrator, implemented by `HttpRpc` for a CKB node
+pub trait Rpc {
+    /// Return output and data of the cell, or none if the cell is not live
+    fn get_live_cell(&self, out_point: &OutPoint) -> Result<Option<(CellOutput, Bytes)>, Error>;
+
+    fn get_transaction_status(&self, tx_hash: &Byte32) -> Result<TxStatus, Error>;
+
+    /// Return header of the tip block
+    fn get_tip_header(&self) -> Result<HeaderView, Error>;
+
+    /// Submit the transaction to the pool, return the transaction hash
+    fn send_transaction(&mut self, tx: &TransactionView) -> Result<Byte32, Error>;
+}
+
+/// JSON-RPC client of a CKB node
+pub struct HttpRpc {
+    client: RpcClient,
+}
+
+impl HttpRpc {
+    pub fn new(uri: &str) -> Self {
+        HttpRpc {
+            client: RpcClient::new(uri),
+        }
+    }
+}
+
+impl Rpc for HttpRpc {
+    fn get_live_cell(&self, out_point: &OutPoint) -> Result<Option<(CellOutput, Bytes)>, Error> {
+        let cell_with_status = self
+            .client
+            .inner()
+            .get_live_cell(out_point.clone().into(), true)
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        if cell_with_status.status != LIVE_CELL_STATUS {
+            return Ok(None);
+        }
+        Ok(cell_with_status.cell.map(|cell| {
+            let data = cell
+                .data
+                .map(|data| data.content.into_bytes())
+                .unwrap_or_default();
+            (cell.output.into(), data)
+        }))
+    }
+
+    fn get_transaction_status(&self, tx_hash: &Byte32) -> Result<TxStatus, Error> {
+        let tx = self
+            .client
+            .inner()
+            .get_transaction(tx_hash.unpack())
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        Ok(match tx.map(|tx| tx.tx_status.status) {
+            Some(Status::Committed) => TxStatus::Committed,
+            Some(_) => TxStatus::Pending,
+            None => TxStatus::Unknown,
+        })
+    }
+
+    fn get_tip_header(&self) -> Result<HeaderView, Error> {
+        self.client
+            .inner()
+            .get_tip_header()
+            .map(Into::into)
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    fn send_transaction(&mut self, tx: &TransactionView) -> Result<Byte32, Error> {
+        self.client
+            .inner()
+            .send_transaction(tx.data().into())
+            .map(|tx_hash| tx_hash.pack())
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+}
@@ -0,0 +1,35 @@
+use std::fmt;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    Rpc(String),
+    Indexer(indexer::Error),
+    Io(String),
+    Config(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "rpc error: {}", err),
+            Self::Indexer(err) => write!(f, "indexer error: {}", err),
+            Self::Io(err) => write!(f, "io error: {}", err),
+            Self::Config(err) => write!(f, "config error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<indexer::Error> for Error {
+    fn from(err: indexer::Error) -> Self {
+        Self::Indexer(err)
+    }
+}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Self {
+        Self::Io(err.to_string())
+    }
+}
@@ -0,0 +1,6 @@
+mod error;
+mod monitor;
+pub mod source;
+
+pub use error::Error;
+pub use monitor::{Metrics, Monitor};
@@ -0,0 +1,170 @@
+use std::{
+    io::{BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+    path::Path,
+    process,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+
+use ckb_tool::ckb_types::{core::ScriptHashType, packed::Script, prelude::*, H256};
+use clap::{App, Arg, ArgMatches};
+use indexer::{source::RpcBlockSource, store::Store, Indexer};
+use monitor::{source::RpcSource, Error, Monitor};
+use tx_builder::decode_hex;
+
+// the indexer never rolls back, a fork shorter than the confirmations is never indexed
+const DEFAULT_CONFIRMATIONS: &str = "24";
+
+fn main() {
+    let matches = App::new("monitor")
+        .about("Export Prometheus metrics of the time index cell")
+        .arg(
+            Arg::with_name("rpc")
+                .long("rpc")
+                .takes_value(true)
+                .required(true)
+                .help("RPC url of the CKB node"),
+        )
+        .arg(
+            Arg::with_name("db")
+                .long("db")
+                .takes_value(true)
+                .required(true)
+                .help("Path of the indexer database"),
+        )
+        .arg(
+            Arg::with_name("code-hash")
+                .long("code-hash")
+                .takes_value(true)
+                .required(true)
+                .help("Code hash of the type script"),
+        )
+        .arg(
+            Arg::with_name("hash-type")
+                .long("hash-type")
+                .takes_value(true)
+                .possible_values(&["data", "type"])
+                .default_value("type"),
+        )
+        .arg(
+            Arg::with_name("args")
+                .long("args")
+                .takes_value(true)
+                .required(true)
+                .help("Args of the type script"),
+        )
+        .arg(
+            Arg::with_name("confirmations")
+                .long("confirmations")
+                .takes_value(true)
+                .default_value(DEFAULT_CONFIRMATIONS)
+                .help("Blocks on top of a block before it is indexed"),
+        )
+        .arg(
+            Arg::with_name("slot")
+                .long("slot")
+                .takes_value(true)
+                .default_value("60")
+                .help("Seconds between two updates"),
+        )
+        .arg(
+            Arg::with_name("poll")
+                .long("poll")
+                .takes_value(true)
+                .default_value("10")
+                .help("Seconds between two polls"),
+        )
+        .arg(
+            Arg::with_name("listen")
+                .long("listen")
+                .takes_value(true)
+                .default_value("127.0.0.1:9116")
+                .help("Address of the metrics endpoint"),
+        )
+        .get_matches();
+
+    if let Err(err) = run(&matches) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+}
+
+fn parse_number(m: &ArgMatches, name: &str) -> Result<u64, Error> {
+    let value = m.value_of(name).expect(name);
+    value
+        .parse()
+        .map_err(|_| Error::Config(format!("invalid {} {}", name, value)))
+}
+
+fn run(m: &ArgMatches) -> Result<(), Error> {
+    let code_hash: H256 = m
+        .value_of("code-hash")
+        .expect("code hash")
+        .trim_start_matches("0x")
+        .parse()
+        .map_err(|_| Error::Config("invalid code hash".to_string()))?;
+    let hash_type = match m.value_of("hash-type") {
+        Some("data") => ScriptHashType::Data,
+        _ => ScriptHashType::Type,
+    };
+    let type_script = Script::new_builder()
+        .code_hash(code_hash.pack())
+        .hash_type(hash_type.into())
+        .args(
+            decode_hex(m.value_of("args").expect("args"))
+                .map_err(|err| Error::Config(err.to_string()))?
+                .pack(),
+        )
+        .build();
+    let rpc_url = m.value_of("rpc").expect("rpc");
+    let indexer = Indexer::new(
+        RpcBlockSource::new(rpc_url),
+        Store::open(Path::new(m.value_of("db").expect("db")))?,
+        type_script.clone(),
+        parse_number(m, "confirmations")?,
+    )?;
+    let mut monitor = Monitor::new(
+        RpcSource::new(rpc_url, indexer),
+        type_script,
+        parse_number(m, "slot")?,
+    );
+    let poll_interval = Duration::from_secs(parse_number(m, "poll")?);
+    let listener = TcpListener::bind(m.value_of("listen").expect("listen"))?;
+
+    // the last rendered metrics, served to every scrape
+    let metrics = Arc::new(Mutex::new(String::new()));
+    let rendered = Arc::clone(&metrics);
+    thread::spawn(move || loop {
+        match monitor.poll() {
+            Ok(polled) => *rendered.lock().expect("lock") = polled.render(),
+            Err(err) => eprintln!("{}", err),
+        }
+        thread::sleep(poll_interval);
+    });
+
+    for stream in listener.incoming() {
+        let body = metrics.lock().expect("lock").clone();
+        if let Err(err) = stream.and_then(|stream| serve(stream, &body)) {
+            eprintln!("{}", err);
+        }
+    }
+    Ok(())
+}
+
+// answer `GET /metrics` with the metrics and anything else with 404
+fn serve(mut stream: TcpStream, body: &str) -> std::io::Result<()> {
+    let mut request_line = String::new();
+    BufReader::new(&stream).read_line(&mut request_line)?;
+    let response = if request_line.starts_with("GET /metrics ") {
+        format!(
+            "HTTP/1.1 200 OK\r\nContent-Type: text/plain; version=0.0.4\r\nContent-Length: {}\r\n\r\n{}",
+            body.len(),
+            body
+        )
+    } else {
+        "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n".to_string()
+    };
+    stream.write_all(response.as_bytes())
+}
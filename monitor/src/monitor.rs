@@ -0,0 +1,164 @@
+use std::collections::HashSet;
+
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionView,
+    packed::{Byte32, OutPoint, Script},
+};
+use time_index_state_validation::{check_update_cell_data, helper::get_clock_mode};
+
+use crate::{
+    error::Error,
+    source::{OracleSource, TimeIndexState},
+};
+
+/// Health of the oracle at one poll
+#[derive(Debug, Clone, PartialEq)]
+pub struct Metrics {
+    /// Index of the live time index cell, none before it is created
+    pub current_index: Option<u8>,
+    /// Seconds from the block of the last update to the tip
+    pub seconds_since_last_update: Option<u64>,
+    /// Slots passed without an update. The update of the current slot is not counted until the next slot is due,
+    /// so an update landing a few blocks late is not reported.
+    pub missed_slots: Option<u64>,
+    /// Transactions seen in the pool which try to move the time index cell to an invalid state
+    pub invalid_update_attempts: u64,
+}
+
+impl Metrics {
+    /// Metrics in the Prometheus text format
+    pub fn render(&self) -> String {
+        let mut text = String::new();
+        let mut metric = |name: &str, kind: &str, help: &str, value: Option<u64>| {
+            if let Some(value) = value {
+                text.push_str(&format!("# HELP {} {}\n", name, help));
+                text.push_str(&format!("# TYPE {} {}\n", name, kind));
+                text.push_str(&format!("{} {}\n", name, value));
+            }
+        };
+        metric(
+            "time_index_current_index",
+            "gauge",
+            "Index of the live time index cell.",
+            self.current_index.map(u64::from),
+        );
+        metric(
+            "time_index_seconds_since_last_update",
+            "gauge",
+            "Seconds from the block of the last update to the tip.",
+            self.seconds_since_last_update,
+        );
+        metric(
+            "time_index_missed_slots",
+            "gauge",
+            "Update slots passed without an update.",
+            self.missed_slots,
+        );
+        metric(
+            "time_index_invalid_update_attempts_total",
+            "counter",
+            "Invalid updates of the time index cell seen in the pool.",
+            Some(self.invalid_update_attempts),
+        );
+        text
+    }
+}
+
+/// Watches one time index cell, the pool transactions found invalid are counted once
+pub struct Monitor<S> {
+    source: S,
+    type_script: Script,
+    /// Seconds between two updates
+    slot_interval: u64,
+    invalid_txs: HashSet<Byte32>,
+}
+
+impl<S: OracleSource> Monitor<S> {
+    pub fn new(source: S, type_script: Script, slot_interval: u64) -> Self {
+        Monitor {
+            source,
+            type_script,
+            slot_interval,
+            invalid_txs: HashSet::new(),
+        }
+    }
+
+    pub fn source_mut(&mut self) -> &mut S {
+        &mut self.source
+    }
+
+    pub fn poll(&mut self) -> Result<Metrics, Error> {
+        let state = self.source.get_time_index_state()?;
+        let tip_timestamp = self.source.get_tip_timestamp()?;
+        let pool = self.source.get_pool_transactions()?;
+        if let Some(state) = state.as_ref() {
+            for tx_hash in self.find_invalid_updates(state, &pool) {
+                self.invalid_txs.insert(tx_hash);
+            }
+        }
+
+        let seconds_since_last_update = state
+            .as_ref()
+            .map(|state| tip_timestamp.saturating_sub(state.timestamp) / 1000);
+        Ok(Metrics {
+            current_index: state.as_ref().and_then(|state| state.data.first().cloned()),
+            seconds_since_last_update,
+            missed_slots: seconds_since_last_update
+                .map(|seconds| (seconds / self.slot_interval).saturating_sub(1)),
+            invalid_update_attempts: self.invalid_txs.len() as u64,
+        })
+    }
+
+    fn created_cell(&self, tx: &TransactionView) -> Option<(OutPoint, Bytes)> {
+        tx.outputs_with_data_iter()
+            .enumerate()
+            .find(|(_, (output, _))| output.type_().to_opt().as_ref() == Some(&self.type_script))
+            .map(|(index, (_, data))| (OutPoint::new(tx.hash(), index as u32), data))
+    }
+
+    // the valid updates chain from the live cell, each moving it to a next state by the update rules of the
+    // cell data, any other transaction consuming a cell of the chain or creating a cell of the type script is invalid
+    fn find_invalid_updates(
+        &self,
+        state: &TimeIndexState,
+        pool: &[TransactionView],
+    ) -> Vec<Byte32> {
+        let mut valid = HashSet::new();
+        let mut chain = vec![state.out_point.clone()];
+        let mut cell = (state.out_point.clone(), state.data.clone());
+        //the live cell was created with valid args, no update is valid otherwise
+        let mode = get_clock_mode(&self.type_script.args().raw_data());
+        loop {
+            let update = pool.iter().find_map(|tx| {
+                if !tx.input_pts_iter().any(|input| input == cell.0) {
+                    return None;
+                }
+                match (self.created_cell(tx), &mode) {
+                    (Some(created), Ok(mode))
+                        if check_update_cell_data(*mode, &cell.1, &created.1).is_ok() =>
+                    {
+                        Some((tx.hash(), created))
+                    }
+                    _ => None,
+                }
+            });
+            match update {
+                Some((tx_hash, created)) => {
+                    valid.insert(tx_hash);
+                    chain.push(created.0.clone());
+                    cell = created;
+                }
+                None => break,
+            }
+        }
+        pool.iter()
+            .filter(|tx| !valid.contains(&tx.hash()))
+            .filter(|tx| {
+                tx.input_pts_iter().any(|input| chain.contains(&input))
+                    || self.created_cell(tx).is_some()
+            })
+            .map(|tx| tx.hash())
+            .collect()
+    }
+}
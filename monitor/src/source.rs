@@ -0,0 +1,120 @@
+use ckb_tool::{
+    ckb_types::{
+        bytes::Bytes,
+        core::TransactionView,
+        packed::{self, OutPoint},
+        prelude::*,
+        H256,
+    },
+    rpc_client::RpcClient,
+};
+use indexer::{source::RpcBlockSource, Indexer};
+use serde::Deserialize;
+use simple_jsonrpc_client::*;
+
+use crate::error::Error;
+
+/// The live time index cell
+#[derive(Debug, Clone, PartialEq)]
+pub struct TimeIndexState {
+    pub out_point: OutPoint,
+    pub data: Bytes,
+    /// Timestamp of the block committing the cell in milliseconds
+    pub timestamp: u64,
+}
+
+/// Chain access used by the monitor, implemented by `RpcSource` with the indexer and a CKB node
+pub trait OracleSource {
+    /// Return the live time index cell, or none if it is not created yet
+    fn get_time_index_state(&mut self) -> Result<Option<TimeIndexState>, Error>;
+
+    /// Return timestamp of the tip block in milliseconds
+    fn get_tip_timestamp(&mut self) -> Result<u64, Error>;
+
+    /// Return the pending and proposed transactions of the pool
+    fn get_pool_transactions(&mut self) -> Result<Vec<TransactionView>, Error>;
+}
+
+#[derive(Deserialize)]
+pub struct RawTxPool {
+    pub pending: Vec<H256>,
+    pub proposed: Vec<H256>,
+}
+
+// `get_raw_tx_pool` is not wrapped by ckb-tool
+jsonrpc!(pub struct PoolRpc {
+    pub fn get_raw_tx_pool(&self, verbose: Option<bool>) -> RawTxPool;
+});
+
+/// The indexer finds the live time index cell, the node provides the tip and the pool
+pub struct RpcSource {
+    indexer: Indexer<RpcBlockSource>,
+    client: RpcClient,
+    pool: PoolRpc,
+}
+
+impl RpcSource {
+    pub fn new(uri: &str, indexer: Indexer<RpcBlockSource>) -> Self {
+        RpcSource {
+            indexer,
+            client: RpcClient::new(uri),
+            pool: PoolRpc::new(uri, reqwest::blocking::Client::new()),
+        }
+    }
+
+    fn get_transaction(&self, tx_hash: H256) -> Result<Option<TransactionView>, Error> {
+        Ok(self
+            .client
+            .inner()
+            .get_transaction(tx_hash)
+            .map_err(|err| Error::Rpc(err.to_string()))?
+            .map(|tx| packed::Transaction::from(tx.transaction.inner).into_view()))
+    }
+}
+
+impl OracleSource for RpcSource {
+    fn get_time_index_state(&mut self) -> Result<Option<TimeIndexState>, Error> {
+        self.indexer.sync()?;
+        let record = match self.indexer.store().current()? {
+            Some(record) => record,
+            None => return Ok(None),
+        };
+        // the data is read from the transaction, the cell may be spent by a block not indexed yet
+        let tx = self
+            .get_transaction(record.tx_hash().unpack())?
+            .ok_or_else(|| Error::Rpc(format!("transaction {} not found", record.tx_hash())))?;
+        let output_index: u32 = record.out_point.index().unpack();
+        let data = tx
+            .outputs_data()
+            .get(output_index as usize)
+            .ok_or_else(|| Error::Rpc(format!("output {} not found", record.out_point)))?;
+        Ok(Some(TimeIndexState {
+            out_point: record.out_point,
+            data: data.unpack(),
+            timestamp: record.timestamp,
+        }))
+    }
+
+    fn get_tip_timestamp(&mut self) -> Result<u64, Error> {
+        self.client
+            .inner()
+            .get_tip_header()
+            .map(|header| header.inner.timestamp.into())
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    fn get_pool_transactions(&mut self) -> Result<Vec<TransactionView>, Error> {
+        let pool = self
+            .pool
+            .get_raw_tx_pool(Some(false))
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        let mut txs = Vec::new();
+        // a transaction may leave the pool between the two calls
+        for tx_hash in pool.pending.into_iter().chain(pool.proposed) {
+            if let Some(tx) = self.get_transaction(tx_hash)? {
+                txs.push(tx);
+            }
+        }
+        Ok(txs)
+    }
+}
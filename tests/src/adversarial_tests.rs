@@ -0,0 +1,277 @@
+use ckb_testtool::context::random_out_point;
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::{
+        bytes::Bytes,
+        core::TransactionView,
+        packed::{self, *},
+        prelude::*,
+    },
+};
+
+use super::*;
+
+// error numbers
+const TIME_INDEX_INVALID_ARGS: i8 = 5;
+const TIME_INDEX_INVALID_OUTPUT: i8 = 7;
+
+// shapes of transactions around the time index cell, each test pins whether
+// create() or update() is dispatched and the error it returns
+
+fn plain_output(env: &TimeIndexTestEnv) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(env.lock_script.clone())
+        .build()
+}
+
+// a cell with a type script of another contract
+fn unrelated_typed_output(env: &TimeIndexTestEnv) -> CellOutput {
+    let type_script = env
+        .lock_script
+        .clone()
+        .as_builder()
+        .args(Bytes::from("unrelated").pack())
+        .build();
+    plain_output(env)
+        .as_builder()
+        .type_(Some(type_script).pack())
+        .build()
+}
+
+fn time_index_output(env: &TimeIndexTestEnv, type_script: Script) -> CellOutput {
+    plain_output(env)
+        .as_builder()
+        .type_(Some(type_script).pack())
+        .build()
+}
+
+fn insert_input(
+    env: &mut TimeIndexTestEnv,
+    tx: TransactionView,
+    position: usize,
+    output: CellOutput,
+    data: Bytes,
+) -> TransactionView {
+    let out_point = env.context.create_cell(output, data);
+    let mut inputs: Vec<CellInput> = tx.inputs().into_iter().collect();
+    inputs.insert(position, CellInput::new(out_point, 0));
+    tx.as_advanced_builder().set_inputs(inputs).build()
+}
+
+fn insert_output(
+    tx: TransactionView,
+    position: usize,
+    output: CellOutput,
+    data: Bytes,
+) -> TransactionView {
+    let mut outputs: Vec<CellOutput> = tx.outputs().into_iter().collect();
+    let mut outputs_data: Vec<packed::Bytes> = tx.outputs_data().into_iter().collect();
+    outputs.insert(position, output);
+    outputs_data.insert(position, data.pack());
+    tx.as_advanced_builder()
+        .set_outputs(outputs)
+        .set_outputs_data(outputs_data)
+        .build()
+}
+
+// deploy another copy of the contract guarded by a type id script,
+// return the script referencing it by type hash and its cell dep
+fn deploy_type_id_contract(env: &mut TimeIndexTestEnv, args: Bytes) -> (Script, CellDep) {
+    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(contract_bin);
+    (
+        build_type_hash_script(&type_id_script, args),
+        CellDep::new_builder().out_point(code_out_point).build(),
+    )
+}
+
+#[test]
+fn test_create_at_non_zero_output() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let tx = insert_output(tx, 0, plain_output(&env), Bytes::new());
+    let tx = insert_output(tx, 1, plain_output(&env), Bytes::new());
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_create_input_not_first() {
+    let mut env = TimeIndexTestEnv::new();
+    // the args are the out point of the input which is moved to position 1
+    let tx = env.create_tx().build();
+    let output = plain_output(&env);
+    let tx = insert_input(&mut env, tx, 0, output, Bytes::new());
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_ARGS).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_create_with_unrelated_type_scripts() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let output = unrelated_typed_output(&env);
+    let tx = insert_input(&mut env, tx, 1, output.clone(), Bytes::new());
+    let tx = insert_output(tx, 0, output, Bytes::new());
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_create_with_other_args_time_index_output() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    // a second time index cell whose args are not the out point of the first input
+    let other_type_script = env.build_type_script(random_out_point().as_bytes());
+    let output = time_index_output(&env, other_type_script);
+    let tx = insert_output(tx, 1, output, build_time_index_cell_data(0));
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_ARGS).output_type_script(1)
+    );
+}
+
+#[test]
+fn test_update_at_non_zero_position() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).build();
+    let output = plain_output(&env);
+    let tx = insert_input(&mut env, tx, 0, output.clone(), Bytes::new());
+    let tx = insert_input(&mut env, tx, 1, output.clone(), Bytes::new());
+    let tx = insert_output(tx, 0, output, Bytes::new());
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_update_with_unrelated_type_scripts() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(11, 0).build();
+    let output = unrelated_typed_output(&env);
+    let tx = insert_input(&mut env, tx, 0, output.clone(), Bytes::new());
+    let tx = insert_output(tx, 1, output, Bytes::new());
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_update_with_other_args_time_index_output() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).build();
+    // the other script group has no input, so it is checked as a create
+    let other_type_script = env.build_type_script(random_out_point().as_bytes());
+    let output = time_index_output(&env, other_type_script);
+    let tx = insert_output(tx, 1, output, build_time_index_cell_data(0));
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_ARGS).output_type_script(1)
+    );
+}
+
+#[test]
+fn test_update_with_other_args_time_index_input() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).build();
+    // the other time index cell is consumed without an output
+    let other_type_script = env.build_type_script(random_out_point().as_bytes());
+    let output = time_index_output(&env, other_type_script);
+    let tx = insert_input(&mut env, tx, 1, output, build_time_index_cell_data(0));
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_OUTPUT).input_type_script(1)
+    );
+}
+
+#[test]
+fn test_update_two_time_index_cells() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).build();
+    // each script group checks its own cells
+    let other_type_script = env.build_type_script(random_out_point().as_bytes());
+    let output = time_index_output(&env, other_type_script);
+    let tx = insert_input(
+        &mut env,
+        tx,
+        0,
+        output.clone(),
+        build_time_index_cell_data(11),
+    );
+    let tx = insert_output(tx, 1, output, build_time_index_cell_data(0));
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_create_with_type_hash_type() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let args = tx
+        .inputs()
+        .get(0)
+        .expect("input")
+        .previous_output()
+        .as_bytes();
+    let (type_script, type_script_dep) = deploy_type_id_contract(&mut env, args);
+    let output = time_index_output(&env, type_script);
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![output])
+        .cell_dep(type_script_dep)
+        .build();
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_update_with_type_hash_type() {
+    let mut env = TimeIndexTestEnv::new();
+    let (type_script, type_script_dep) =
+        deploy_type_id_contract(&mut env, random_out_point().as_bytes());
+    let output = time_index_output(&env, type_script);
+    let tx = env.update_tx(0, 1).build();
+    let tx = tx
+        .as_advanced_builder()
+        .set_inputs(vec![])
+        .set_outputs(vec![output.clone()])
+        .cell_dep(type_script_dep)
+        .build();
+    let tx = insert_input(&mut env, tx, 0, output, build_time_index_cell_data(0));
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_update_from_data_to_type_hash_type() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).build();
+    // the same binary referenced by type hash is another script, the input group finds no output.
+    // the output is a valid create of that script, so the error doesn't depend on the order of the groups
+    let input_out_point = tx.inputs().get(0).expect("input").previous_output();
+    let (type_script, type_script_dep) =
+        deploy_type_id_contract(&mut env, input_out_point.as_bytes());
+    let output = time_index_output(&env, type_script);
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![output])
+        .set_outputs_data(vec![build_time_index_cell_data(0).pack()])
+        .cell_dep(type_script_dep)
+        .build();
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_OUTPUT).input_type_script(0)
+    );
+}
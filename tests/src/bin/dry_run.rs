@@ -0,0 +1,72 @@
+use std::{fs, process};
+
+use ckb_tool::ckb_types::{bytes::Bytes, packed::Byte32, prelude::*};
+use clap::{App, Arg};
+use inspector::mock_tx::MockTransaction;
+use tests::{
+    dry_run::{dry_run, parse_script_failure, DryRunError},
+    Loader,
+};
+use tx_builder::decode_hex;
+
+const MAX_CYCLES: u64 = 70_000_000;
+
+fn main() {
+    let matches = App::new("dry_run")
+        .about("Run a ckb-cli mock transaction with the locally built time_index_state_type_script")
+        .arg(Arg::with_name("file").required(true))
+        .arg(
+            Arg::with_name("code-hash")
+                .long("code-hash")
+                .takes_value(true)
+                .required(true)
+                .help("Code hash of the type script"),
+        )
+        .arg(
+            Arg::with_name("binary")
+                .long("binary")
+                .takes_value(true)
+                .help("Path of the binary, default is the one built by capsule"),
+        )
+        .get_matches();
+
+    let mock_tx = match MockTransaction::load(matches.value_of("file").expect("file")) {
+        Ok(mock_tx) => mock_tx,
+        Err(err) => exit_with_error(err),
+    };
+    let code_hash = match decode_hex(matches.value_of("code-hash").expect("code hash")) {
+        Ok(code_hash) if code_hash.len() == 32 => {
+            Byte32::from_slice(&code_hash).expect("code hash")
+        }
+        _ => exit_with_error("invalid code hash"),
+    };
+    let binary: Bytes = match matches.value_of("binary") {
+        Some(path) => match fs::read(path) {
+            Ok(binary) => binary.into(),
+            Err(err) => exit_with_error(err),
+        },
+        None => Loader::default().load_binary("time_index_state_type_script"),
+    };
+
+    match dry_run(&mock_tx, &code_hash, binary, MAX_CYCLES) {
+        Ok(cycles) => println!("pass, cycles: {}", cycles),
+        Err(DryRunError::Verify(err)) => {
+            match parse_script_failure(&mock_tx, &code_hash, &err) {
+                Some(failure) => match failure.error_code {
+                    Some(error_code) => {
+                        println!("fail, {} exit code {}", failure.source, error_code)
+                    }
+                    None => println!("fail, {} exit code {}", failure.source, failure.exit_code),
+                },
+                None => println!("fail, {}", err),
+            }
+            process::exit(1);
+        }
+        Err(err) => exit_with_error(err),
+    }
+}
+
+fn exit_with_error<E: std::fmt::Display>(err: E) -> ! {
+    eprintln!("{}", err);
+    process::exit(2);
+}
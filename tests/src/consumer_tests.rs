@@ -0,0 +1,430 @@
+use time_index_consumer::{
+    find_time_index_cell_dep, find_time_info_cell_dep, load_time_index_block_number,
+    resolve_time_cell_deps, verify_time_index_cell_dep, CellDepLoader, ClockMode, Epoch, Error,
+    HashType, Script, TimeCellDeps, TimeIndexIdentity, TimeIndexState,
+};
+
+const CODE_HASH: [u8; 32] = [1; 32];
+const OTHER_CODE_HASH: [u8; 32] = [2; 32];
+const ARGS: [u8; 36] = [3; 36];
+const OTHER_ARGS: [u8; 36] = [4; 36];
+const TIME_INFO_TYPE_HASH: [u8; 32] = [5; 32];
+const OTHER_TYPE_HASH: [u8; 32] = [6; 32];
+
+struct CellDep {
+    type_script: Option<Script>,
+    data: Vec<u8>,
+    // hash of the type script, set on its own for the time info cells
+    type_hash: Option<[u8; 32]>,
+    // none if the header of its block is not in header deps
+    block_number: Option<u64>,
+}
+
+// cell deps of a transaction in memory
+struct MockLoader {
+    cell_deps: Vec<CellDep>,
+}
+
+impl CellDepLoader for MockLoader {
+    fn load_cell_dep_type(&self, index: usize) -> Result<Option<Script>, Error> {
+        let cell_dep = self.cell_deps.get(index).ok_or(Error::IndexOutOfBound)?;
+        Ok(cell_dep.type_script.clone())
+    }
+
+    fn load_cell_dep_data(&self, index: usize) -> Result<Vec<u8>, Error> {
+        let cell_dep = self.cell_deps.get(index).ok_or(Error::IndexOutOfBound)?;
+        Ok(cell_dep.data.clone())
+    }
+
+    fn load_small_cell_dep_data(
+        &self,
+        index: usize,
+        max_len: usize,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let data = self.load_cell_dep_data(index)?;
+        Ok(if data.len() <= max_len {
+            Some(data)
+        } else {
+            None
+        })
+    }
+
+    fn load_cell_dep_type_hash(&self, index: usize) -> Result<Option<[u8; 32]>, Error> {
+        let cell_dep = self.cell_deps.get(index).ok_or(Error::IndexOutOfBound)?;
+        Ok(cell_dep.type_hash)
+    }
+
+    fn load_cell_dep_block_number(&self, index: usize) -> Result<u64, Error> {
+        let cell_dep = self.cell_deps.get(index).ok_or(Error::IndexOutOfBound)?;
+        cell_dep.block_number.ok_or(Error::ItemMissing)
+    }
+}
+
+fn identity() -> TimeIndexIdentity {
+    TimeIndexIdentity::new(CODE_HASH, HashType::Type as u8, &ARGS).expect("identity")
+}
+
+fn typed_cell_dep(code_hash: [u8; 32], hash_type: HashType, args: &[u8], data: Vec<u8>) -> CellDep {
+    CellDep {
+        type_script: Some(Script {
+            code_hash,
+            hash_type: hash_type as u8,
+            args: args.to_vec(),
+        }),
+        data,
+        type_hash: Some(OTHER_TYPE_HASH),
+        block_number: None,
+    }
+}
+
+fn plain_cell_dep(data: Vec<u8>) -> CellDep {
+    CellDep {
+        type_script: None,
+        data,
+        type_hash: None,
+        block_number: None,
+    }
+}
+
+fn time_info_cell_dep(index: u8, timestamp: u64, type_hash: Option<[u8; 32]>) -> CellDep {
+    let mut data = vec![index];
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    CellDep {
+        type_hash,
+        ..plain_cell_dep(data)
+    }
+}
+
+fn time_index_cell_dep(index: u8) -> CellDep {
+    typed_cell_dep(CODE_HASH, HashType::Type, &ARGS, vec![index, 12])
+}
+
+fn verify_single(cell_dep: CellDep) -> Result<TimeIndexState, Error> {
+    let loader = MockLoader {
+        cell_deps: vec![cell_dep],
+    };
+    verify_time_index_cell_dep(&loader, 0, &identity())
+}
+
+#[test]
+fn test_verify_time_index_cell_dep() {
+    assert_eq!(
+        verify_single(time_index_cell_dep(3)),
+        Ok(TimeIndexState {
+            index: 3,
+            n: 12,
+            lap: None,
+            epoch: None,
+        })
+    );
+    assert_eq!(
+        verify_single(typed_cell_dep(
+            CODE_HASH,
+            HashType::Type,
+            &ARGS,
+            vec![3, 12, 1]
+        )),
+        Ok(TimeIndexState {
+            index: 3,
+            n: 12,
+            lap: None,
+            epoch: None,
+        })
+    );
+    let mut data = vec![3, 12, 2];
+    data.extend_from_slice(&7u64.to_le_bytes());
+    assert_eq!(
+        verify_single(typed_cell_dep(CODE_HASH, HashType::Type, &ARGS, data)),
+        Ok(TimeIndexState {
+            index: 3,
+            n: 12,
+            lap: Some(7),
+            epoch: None,
+        })
+    );
+}
+
+#[test]
+fn test_verify_time_index_cell_dep_with_epoch() {
+    let mut args = ARGS.to_vec();
+    args.extend_from_slice(&1u64.to_le_bytes());
+    args.push(2);
+    let identity =
+        TimeIndexIdentity::new(CODE_HASH, HashType::Type as u8, &args).expect("identity");
+    assert_eq!(identity.mode(), Some(ClockMode::Epoch(1)));
+
+    // number 5, index 10 of 1800
+    let epoch: u64 = 5 | (10 << 24) | (1800 << 40);
+    let mut data = vec![3, 12, 1];
+    data.extend_from_slice(&epoch.to_le_bytes());
+    let loader = MockLoader {
+        cell_deps: vec![typed_cell_dep(CODE_HASH, HashType::Type, &args, data)],
+    };
+    assert_eq!(
+        verify_time_index_cell_dep(&loader, 0, &identity),
+        Ok(TimeIndexState {
+            index: 3,
+            n: 12,
+            lap: None,
+            epoch: Some(Epoch {
+                number: 5,
+                index: 10,
+                length: 1800,
+            }),
+        })
+    );
+}
+
+#[test]
+fn test_verify_impostor_cell_deps() {
+    let data = vec![0, 12];
+    assert_eq!(
+        verify_single(plain_cell_dep(data.clone())),
+        Err(Error::NoTypeScript)
+    );
+    assert_eq!(
+        verify_single(typed_cell_dep(
+            OTHER_CODE_HASH,
+            HashType::Type,
+            &ARGS,
+            data.clone()
+        )),
+        Err(Error::InvalidCodeHash)
+    );
+    assert_eq!(
+        verify_single(typed_cell_dep(
+            CODE_HASH,
+            HashType::Data,
+            &ARGS,
+            data.clone()
+        )),
+        Err(Error::InvalidHashType)
+    );
+    assert_eq!(
+        verify_single(typed_cell_dep(
+            CODE_HASH,
+            HashType::Type,
+            &OTHER_ARGS,
+            data.clone()
+        )),
+        Err(Error::InvalidArgs)
+    );
+    assert_eq!(
+        verify_single(typed_cell_dep(CODE_HASH, HashType::Type, &ARGS[..35], data)),
+        Err(Error::InvalidArgs)
+    );
+}
+
+#[test]
+fn test_verify_invalid_cell_data() {
+    for data in [vec![], vec![0], vec![12, 12], vec![0, 11], vec![0, 12, 0]] {
+        assert_eq!(
+            verify_single(typed_cell_dep(CODE_HASH, HashType::Type, &ARGS, data)),
+            Err(Error::InvalidCellData)
+        );
+    }
+}
+
+#[test]
+fn test_verify_out_of_bound() {
+    let loader = MockLoader {
+        cell_deps: vec![time_index_cell_dep(0)],
+    };
+    assert_eq!(
+        verify_time_index_cell_dep(&loader, 1, &identity()),
+        Err(Error::TimeIndexNotFound)
+    );
+}
+
+#[test]
+fn test_invalid_identity() {
+    assert_eq!(
+        TimeIndexIdentity::new(CODE_HASH, 2, &ARGS),
+        Err(Error::InvalidIdentity)
+    );
+    assert_eq!(
+        TimeIndexIdentity::new(CODE_HASH, HashType::Type as u8, &ARGS[..32]),
+        Err(Error::InvalidIdentity)
+    );
+    // an update interval of 0 seconds
+    assert_eq!(
+        TimeIndexIdentity::new(CODE_HASH, HashType::Type as u8, &[0; 44]),
+        Err(Error::InvalidIdentity)
+    );
+}
+
+#[test]
+fn test_identity_with_interval() {
+    assert_eq!(identity().mode(), None);
+
+    // the hourly clock is another identity than the one without interval
+    let mut args = ARGS.to_vec();
+    args.extend_from_slice(&3600u64.to_le_bytes());
+    let hourly = TimeIndexIdentity::new(CODE_HASH, HashType::Type as u8, &args).expect("identity");
+    assert_eq!(hourly.mode(), Some(ClockMode::Timestamp(3600)));
+    assert_ne!(hourly, identity());
+
+    // so is the clock advancing every 3600 blocks
+    args.push(1);
+    let height = TimeIndexIdentity::new(CODE_HASH, HashType::Type as u8, &args).expect("identity");
+    assert_eq!(height.mode(), Some(ClockMode::BlockNumber(3600)));
+    assert_ne!(height, hourly);
+}
+
+#[test]
+fn test_find_skips_impostor_cell_deps() {
+    let loader = MockLoader {
+        cell_deps: vec![
+            plain_cell_dep(vec![5, 12]),
+            typed_cell_dep(OTHER_CODE_HASH, HashType::Type, &ARGS, vec![6, 12]),
+            typed_cell_dep(CODE_HASH, HashType::Type, &OTHER_ARGS, vec![7, 12]),
+            time_index_cell_dep(8),
+        ],
+    };
+    assert_eq!(
+        find_time_index_cell_dep(&loader, &identity()),
+        Ok((
+            3,
+            TimeIndexState {
+                index: 8,
+                n: 12,
+                lap: None,
+                epoch: None,
+            }
+        ))
+    );
+}
+
+#[test]
+fn test_find_without_time_index() {
+    let loader = MockLoader {
+        cell_deps: vec![typed_cell_dep(
+            CODE_HASH,
+            HashType::Data,
+            &ARGS,
+            vec![0, 12],
+        )],
+    };
+    assert_eq!(
+        find_time_index_cell_dep(&loader, &identity()),
+        Err(Error::TimeIndexNotFound)
+    );
+}
+
+#[test]
+fn test_find_time_info_cell_dep() {
+    let loader = MockLoader {
+        cell_deps: vec![
+            // the data of a time info cell without its type script, e.g. under the lock of the oracle
+            time_info_cell_dep(3, 400, None),
+            time_info_cell_dep(2, 100, Some(TIME_INFO_TYPE_HASH)),
+            time_info_cell_dep(3, 200, Some(OTHER_TYPE_HASH)),
+            time_info_cell_dep(3, 300, Some(TIME_INFO_TYPE_HASH)),
+        ],
+    };
+    assert_eq!(
+        find_time_info_cell_dep(&loader, 3, &TIME_INFO_TYPE_HASH),
+        Ok((3, 300))
+    );
+    assert_eq!(
+        find_time_info_cell_dep(&loader, 2, &TIME_INFO_TYPE_HASH),
+        Ok((1, 100))
+    );
+    assert_eq!(
+        find_time_info_cell_dep(&loader, 4, &TIME_INFO_TYPE_HASH),
+        Err(Error::TimeInfoNotFound)
+    );
+}
+
+#[test]
+fn test_find_time_info_cell_dep_error_ambiguous() {
+    let loader = MockLoader {
+        cell_deps: vec![
+            time_info_cell_dep(3, 300, Some(TIME_INFO_TYPE_HASH)),
+            time_info_cell_dep(3, 400, Some(TIME_INFO_TYPE_HASH)),
+        ],
+    };
+    assert_eq!(
+        find_time_info_cell_dep(&loader, 3, &TIME_INFO_TYPE_HASH),
+        Err(Error::AmbiguousTimeInfo)
+    );
+
+    // a cell typed as a time info cell holds its data
+    let mut cell_dep = plain_cell_dep(vec![3; 100]);
+    cell_dep.type_hash = Some(TIME_INFO_TYPE_HASH);
+    let loader = MockLoader {
+        cell_deps: vec![cell_dep],
+    };
+    assert_eq!(
+        find_time_info_cell_dep(&loader, 3, &TIME_INFO_TYPE_HASH),
+        Err(Error::InvalidTimeInfo)
+    );
+}
+
+#[test]
+fn test_resolve_members_of_dep_group() {
+    // the cell deps expanded from a dep group of code cells followed by the time index and time info cells
+    // as code deps, the time info cell of the last index is attached too
+    let loader = MockLoader {
+        cell_deps: vec![
+            plain_cell_dep(vec![0; 1000]),
+            time_index_cell_dep(7),
+            time_info_cell_dep(6, 1_600_000_000, Some(TIME_INFO_TYPE_HASH)),
+            time_info_cell_dep(7, 1_600_000_060, Some(TIME_INFO_TYPE_HASH)),
+        ],
+    };
+    assert_eq!(
+        resolve_time_cell_deps(&loader, &identity(), &TIME_INFO_TYPE_HASH),
+        Ok(TimeCellDeps {
+            time_index_position: 1,
+            state: TimeIndexState {
+                index: 7,
+                n: 12,
+                lap: None,
+                epoch: None,
+            },
+            time_info_position: 3,
+            timestamp: 1_600_000_060,
+        })
+    );
+
+    // anyone can publish a dep group, a forged member without the type script of the time info cells is skipped
+    let loader = MockLoader {
+        cell_deps: vec![
+            time_index_cell_dep(7),
+            time_info_cell_dep(7, u64::MAX, None),
+            time_info_cell_dep(7, 1_600_000_060, Some(TIME_INFO_TYPE_HASH)),
+        ],
+    };
+    assert_eq!(
+        resolve_time_cell_deps(&loader, &identity(), &TIME_INFO_TYPE_HASH)
+            .map(|deps| deps.timestamp),
+        Ok(1_600_000_060)
+    );
+
+    // a time info cell dep left behind by an update has another index
+    let loader = MockLoader {
+        cell_deps: vec![
+            time_index_cell_dep(8),
+            time_info_cell_dep(7, 1_600_000_060, Some(TIME_INFO_TYPE_HASH)),
+        ],
+    };
+    assert_eq!(
+        resolve_time_cell_deps(&loader, &identity(), &TIME_INFO_TYPE_HASH),
+        Err(Error::TimeInfoNotFound)
+    );
+}
+
+#[test]
+fn test_load_time_index_block_number() {
+    let mut cell_dep = time_index_cell_dep(3);
+    cell_dep.block_number = Some(1000);
+    let loader = MockLoader {
+        cell_deps: vec![time_index_cell_dep(2), cell_dep],
+    };
+    assert_eq!(load_time_index_block_number(&loader, 1), Ok(1000));
+    assert_eq!(
+        load_time_index_block_number(&loader, 0),
+        Err(Error::HeaderNotFound)
+    );
+}
@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+
+use ckb_tool::ckb_types::{bytes::Bytes, core::TransactionView, packed::*, prelude::*};
+use tx_builder::{build_cell_data, build_create_tx, build_update_tx, Contract, TimeIndexCell};
+
+use super::*;
+
+// unrelated cells on each side of the worst case transaction
+const UNRELATED_CELLS: usize = 64;
+
+const BASELINE_FILE: &str = "cycles_baseline.toml";
+// allowed growth over the baseline, in percent
+const MARGIN_ENV_VAR: &str = "CYCLES_MARGIN";
+const DEFAULT_MARGIN: u64 = 10;
+// set to rewrite the baseline with the measured cycles
+const UPDATE_ENV_VAR: &str = "UPDATE_CYCLES_BASELINE";
+
+type Baseline = BTreeMap<String, BTreeMap<String, u64>>;
+
+struct Bench {
+    env: TimeIndexTestEnv,
+    contract: Contract,
+    other_type_script: Script,
+}
+
+impl Bench {
+    fn new() -> Self {
+        let mut time_index_env = TimeIndexTestEnv::new();
+        let contract = time_index_env.contract();
+        let other_type_script = time_index_env.build_always_success_script(Bytes::from("other"));
+        Bench {
+            env: time_index_env,
+            contract,
+            other_type_script,
+        }
+    }
+
+    fn create_time_index_cell(&mut self, index: u8) -> TimeIndexCell {
+        let output = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(self.env.lock_script.clone())
+            .type_(Some(self.contract.type_script(Bytes::from(vec![1; 36]))).pack())
+            .build();
+        let data = build_cell_data(index, TIME_INDEX_CELL_DATA_N);
+        let out_point = self.env.context.create_cell(output.clone(), data.clone());
+        TimeIndexCell {
+            out_point,
+            output,
+            data,
+        }
+    }
+
+    fn unrelated_cell(&self, i: usize) -> CellOutput {
+        let builder = CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(self.env.lock_script.clone());
+        // half of them have a type script of another contract
+        if i < UNRELATED_CELLS / 2 {
+            builder
+                .type_(Some(self.other_type_script.clone()).pack())
+                .build()
+        } else {
+            builder.build()
+        }
+    }
+
+    fn create_tx(&mut self) -> TransactionView {
+        let input_out_point = self.env.create_plain_cell();
+        build_create_tx(
+            &self.contract,
+            input_out_point,
+            self.env.lock_script.clone(),
+            TIME_INDEX_CELL_DATA_N,
+        )
+        .expect("create tx")
+    }
+
+    fn update_tx(&mut self, index: u8) -> TransactionView {
+        let current_cell = self.create_time_index_cell(index);
+        build_update_tx(&self.contract, &current_cell).expect("update tx")
+    }
+
+    // the time index cell comes after many unrelated cells on both sides,
+    // so the script walks through all of them to find it
+    fn update_tx_with_unrelated_cells(&mut self) -> TransactionView {
+        let tx = self.update_tx(0);
+        let mut inputs = Vec::new();
+        let mut outputs = Vec::new();
+        let mut outputs_data = Vec::new();
+        for i in 0..UNRELATED_CELLS {
+            let output = self.unrelated_cell(i);
+            let out_point = self.env.context.create_cell(output.clone(), Bytes::new());
+            inputs.push(CellInput::new(out_point, 0));
+            outputs.push(output);
+            outputs_data.push(Bytes::new().pack());
+        }
+        inputs.extend(tx.inputs());
+        outputs.extend(tx.outputs());
+        outputs_data.extend(tx.outputs_data());
+        tx.as_advanced_builder()
+            .set_inputs(inputs)
+            .set_outputs(outputs)
+            .set_outputs_data(outputs_data)
+            .build()
+    }
+
+    fn measure(&mut self, tx: TransactionView) -> u64 {
+        let tx = self.env.context.complete_tx(tx);
+        self.env.verify_tx(&tx).expect("pass verification")
+    }
+}
+
+fn test_env_name() -> String {
+    env::var(TEST_ENV_VAR)
+        .unwrap_or_else(|_| "debug".to_string())
+        .to_lowercase()
+}
+
+fn load_baseline() -> Baseline {
+    match fs::read_to_string(BASELINE_FILE) {
+        Ok(content) => toml::from_str(&content).expect("baseline"),
+        Err(_) => Baseline::new(),
+    }
+}
+
+fn save_baseline(baseline: &Baseline) {
+    let content = format!(
+        "# cycles of the transactions in tests/src/cycles_tests.rs, by test env\n\
+         # regenerate with `{}=1 capsule test`\n\n{}",
+        UPDATE_ENV_VAR,
+        toml::to_string(baseline).expect("baseline")
+    );
+    fs::write(BASELINE_FILE, content).expect("write baseline");
+}
+
+#[test]
+fn test_cycles_budget() {
+    let mut bench = Bench::new();
+    let mut measured = BTreeMap::new();
+    let tx = bench.create_tx();
+    measured.insert("create".to_string(), bench.measure(tx));
+    let tx = bench.update_tx(0);
+    measured.insert("update".to_string(), bench.measure(tx));
+    let tx = bench.update_tx(TIME_INDEX_CELL_DATA_N - 1);
+    measured.insert("update_wraparound".to_string(), bench.measure(tx));
+    let tx = bench.update_tx_with_unrelated_cells();
+    measured.insert("update_with_unrelated_cells".to_string(), bench.measure(tx));
+    for (name, cycles) in measured.iter() {
+        println!("{}: {} cycles", name, cycles);
+    }
+
+    let mut baseline = load_baseline();
+    if env::var(UPDATE_ENV_VAR).is_ok() {
+        baseline.insert(test_env_name(), measured);
+        save_baseline(&baseline);
+        return;
+    }
+
+    let margin: u64 = env::var(MARGIN_ENV_VAR)
+        .map(|margin| margin.parse().expect("margin"))
+        .unwrap_or(DEFAULT_MARGIN);
+    let expected = match baseline.get(&test_env_name()) {
+        Some(expected) => expected,
+        None => {
+            println!("no cycles baseline for {}", test_env_name());
+            return;
+        }
+    };
+    for (name, cycles) in measured.iter() {
+        let budget =
+            expected.get(name).expect("baseline of the transaction") * (100 + margin) / 100;
+        assert!(
+            *cycles <= budget,
+            "{} consumes {} cycles, over the budget {}",
+            name,
+            cycles,
+            budget
+        );
+    }
+}
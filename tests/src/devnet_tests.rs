@@ -0,0 +1,23 @@
+use std::{env, path::PathBuf, process};
+
+use devnet::scenario::{run, ScenarioConfig};
+
+// path of the ckb binary, required when the ignored test is run
+const CKB_BIN_ENV_VAR: &str = "CKB_BIN";
+
+#[test]
+#[ignore]
+fn test_devnet_scenario() {
+    let ckb_bin = match env::var(CKB_BIN_ENV_VAR) {
+        Ok(ckb_bin) => PathBuf::from(ckb_bin),
+        Err(_) => panic!("{} should be the path of the ckb binary", CKB_BIN_ENV_VAR),
+    };
+    let root = env::current_dir().unwrap().parent().unwrap().to_path_buf();
+    let dir = env::temp_dir().join(format!("time-index-devnet-test-{}", process::id()));
+    let config = ScenarioConfig::new(ckb_bin, root, dir);
+
+    let report = run(&config).expect("devnet scenario");
+    assert_eq!(report.updates.len(), config.updates);
+    let (_, last_timestamp) = report.updates[report.updates.len() - 1];
+    assert_eq!(report.consumer_timestamp, last_timestamp);
+}
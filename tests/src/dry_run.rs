@@ -0,0 +1,224 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use ckb_testtool::context::Context;
+use ckb_tool::{
+    ckb_error::Error as CKBError,
+    ckb_script::TransactionScriptsVerifier,
+    ckb_types::{
+        bytes::Bytes,
+        core::{
+            cell::{CellMeta, CellMetaBuilder, ResolvedTransaction},
+            Cycle, DepType, HeaderView, TransactionInfo,
+        },
+        packed::{Byte32, CellOutput, OutPoint, OutPointVec, Script},
+        prelude::*,
+    },
+};
+use inspector::{mock_tx::MockTransaction, ErrorCode};
+
+/// Error of the dry run
+#[derive(Debug)]
+pub enum DryRunError {
+    /// The code cell is referenced by data hash, so the transaction can't run with another binary
+    BinaryMismatch,
+    /// A cell or header referenced by the transaction is missing in the mock info
+    Missing(String),
+    /// The transaction fails in CKB-VM
+    Verify(CKBError),
+}
+
+impl fmt::Display for DryRunError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::BinaryMismatch => write!(
+                f,
+                "the script references the code by data hash, which doesn't match the binary"
+            ),
+            Self::Missing(item) => write!(f, "{} is missing in the mock transaction", item),
+            Self::Verify(err) => write!(f, "{}", err),
+        }
+    }
+}
+
+/// Failure of a script, parsed from the verify error
+#[derive(Debug, PartialEq)]
+pub struct ScriptFailure {
+    /// Where the script runs, e.g. `Inputs[0].Type`
+    pub source: String,
+    pub exit_code: i8,
+    /// Name of the exit code if the script is time_index_state_type_script
+    pub error_code: Option<ErrorCode>,
+}
+
+/// Run all scripts of the mock transaction, the code cell of `code_hash` is replaced by `binary`.
+/// If `code_hash` is a type hash, which is how the code cell is upgraded under type id,
+/// the data of the code cell is replaced; if it's a data hash, the binary must be the same.
+pub fn dry_run(
+    mock_tx: &MockTransaction,
+    code_hash: &Byte32,
+    binary: Bytes,
+    max_cycles: Cycle,
+) -> Result<Cycle, DryRunError> {
+    let mut context = Context::default();
+    for header in mock_tx.header_deps.iter() {
+        context.insert_header(header.clone());
+    }
+
+    let mut dep_cells = HashMap::new();
+    for cell_dep in mock_tx.cell_deps.iter() {
+        let mut data = cell_dep.data.clone();
+        if &CellOutput::calc_data_hash(&data) == code_hash
+            && &CellOutput::calc_data_hash(&binary) != code_hash
+        {
+            return Err(DryRunError::BinaryMismatch);
+        }
+        if let Some(type_script) = cell_dep.output.type_().to_opt() {
+            if &type_script.calc_script_hash() == code_hash {
+                data = binary.clone();
+            }
+        }
+        let cell_meta = build_cell_meta(
+            &mock_tx.header_deps,
+            cell_dep.cell_dep.out_point(),
+            cell_dep.output.clone(),
+            data,
+            cell_dep.header.clone(),
+        )?;
+        dep_cells.insert(cell_dep.cell_dep.out_point(), cell_meta);
+    }
+
+    let mut resolved_cell_deps = Vec::new();
+    let mut resolved_dep_groups = Vec::new();
+    for cell_dep in mock_tx.tx.cell_deps_iter() {
+        let cell_meta = get_dep_cell(&dep_cells, &cell_dep.out_point())?;
+        if cell_dep.dep_type() == DepType::DepGroup.into() {
+            let data = cell_meta.mem_cell_data.as_ref().expect("data").0.clone();
+            let out_points = OutPointVec::from_slice(&data)
+                .map_err(|_| DryRunError::Missing(format!("dep group {}", cell_dep.out_point())))?;
+            for out_point in out_points.into_iter() {
+                resolved_cell_deps.push(get_dep_cell(&dep_cells, &out_point)?);
+            }
+            resolved_dep_groups.push(cell_meta);
+        } else {
+            resolved_cell_deps.push(cell_meta);
+        }
+    }
+
+    let resolved_inputs = mock_tx
+        .inputs
+        .iter()
+        .map(|input| {
+            build_cell_meta(
+                &mock_tx.header_deps,
+                input.input.previous_output(),
+                input.output.clone(),
+                input.data.clone(),
+                input.header.clone(),
+            )
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let resolved_tx = ResolvedTransaction {
+        transaction: mock_tx.tx.clone(),
+        resolved_cell_deps,
+        resolved_inputs,
+        resolved_dep_groups,
+    };
+    let mut verifier = TransactionScriptsVerifier::new(&resolved_tx, &context);
+    verifier.set_debug_printer(|_id, msg| {
+        println!("[contract debug] {}", msg);
+    });
+    verifier.verify(max_cycles).map_err(DryRunError::Verify)
+}
+
+fn build_cell_meta(
+    header_deps: &[HeaderView],
+    out_point: OutPoint,
+    output: CellOutput,
+    data: Bytes,
+    block_hash: Option<Byte32>,
+) -> Result<CellMeta, DryRunError> {
+    let mut builder = CellMetaBuilder::from_cell_output(output, data).out_point(out_point);
+    if let Some(block_hash) = block_hash {
+        let header = header_deps
+            .iter()
+            .find(|header| header.hash() == block_hash)
+            .ok_or_else(|| DryRunError::Missing(format!("header {}", block_hash)))?;
+        builder = builder.transaction_info(TransactionInfo::new(
+            header.number(),
+            header.epoch(),
+            block_hash,
+            0,
+        ));
+    }
+    Ok(builder.build())
+}
+
+fn get_dep_cell(
+    dep_cells: &HashMap<OutPoint, CellMeta>,
+    out_point: &OutPoint,
+) -> Result<CellMeta, DryRunError> {
+    dep_cells
+        .get(out_point)
+        .cloned()
+        .ok_or_else(|| DryRunError::Missing(format!("cell dep {}", out_point)))
+}
+
+/// Parse the script failure from the verify error, e.g.
+/// `TransactionScriptError { source: Inputs[0].Type, cause: ValidationFailure(8): ... }`
+pub fn parse_script_failure(
+    mock_tx: &MockTransaction,
+    code_hash: &Byte32,
+    err: &CKBError,
+) -> Option<ScriptFailure> {
+    let message = err.to_string();
+    let source = message
+        .split("source: ")
+        .nth(1)?
+        .split(',')
+        .next()?
+        .to_string();
+    let exit_code = message
+        .split("ValidationFailure(")
+        .nth(1)?
+        .split(')')
+        .next()?
+        .parse::<i8>()
+        .ok()?;
+
+    //the code hash is a data hash or a type hash, either way the script carries it
+    let error_code = match find_source_script(mock_tx, &source) {
+        Some(script) if &script.code_hash() == code_hash => ErrorCode::from_code(exit_code),
+        _ => None,
+    };
+    Some(ScriptFailure {
+        source,
+        exit_code,
+        error_code,
+    })
+}
+
+fn find_source_script(mock_tx: &MockTransaction, source: &str) -> Option<Script> {
+    let (cells, rest) = if let Some(rest) = source.strip_prefix("Inputs[") {
+        (
+            mock_tx
+                .inputs
+                .iter()
+                .map(|input| input.output.clone())
+                .collect::<Vec<_>>(),
+            rest,
+        )
+    } else {
+        let rest = source.strip_prefix("Outputs[")?;
+        (mock_tx.tx.outputs().into_iter().collect(), rest)
+    };
+    let mut parts = rest.split("].");
+    let index = parts.next()?.parse::<usize>().ok()?;
+    let output = cells.get(index)?;
+    match parts.next()? {
+        "Lock" => Some(output.lock()),
+        "Type" => output.type_().to_opt(),
+        _ => None,
+    }
+}
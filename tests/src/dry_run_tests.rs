@@ -0,0 +1,126 @@
+use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::random_out_point};
+use ckb_tool::ckb_types::{
+    core::{DepType, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use inspector::ErrorCode;
+
+use super::dry_run::{dry_run, parse_script_failure, DryRunError, ScriptFailure};
+use super::inspector_tests::build_mock_tx;
+use super::*;
+
+// updates a time index cell from `input_index` to `output_index`,
+// the code cell is referenced by `type_script` and the cell deps are packed in a dep group
+fn build_update_tx(
+    env: &mut TimeIndexTestEnv,
+    code_out_point: OutPoint,
+    type_script: Script,
+    input_index: u8,
+    output_index: u8,
+) -> TransactionView {
+    let dep_group_data = vec![code_out_point, env.always_success_out_point.clone()]
+        .pack()
+        .as_bytes();
+    let dep_group_out_point = env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(env.lock_script.clone())
+            .build(),
+        dep_group_data,
+    );
+
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(env.lock_script.clone())
+        .type_(Some(type_script).pack())
+        .build();
+    let input_out_point = env
+        .context
+        .create_cell(output.clone(), build_time_index_cell_data(input_index));
+
+    TransactionBuilder::default()
+        .input(CellInput::new(input_out_point, 0))
+        .output(output)
+        .output_data(build_time_index_cell_data(output_index).pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(dep_group_out_point)
+                .dep_type(DepType::DepGroup.into())
+                .build(),
+        )
+        .build()
+}
+
+#[test]
+fn test_dry_run_success() {
+    let mut env = TimeIndexTestEnv::new();
+    let code_hash = env.contract().code_hash;
+    let type_script = env.build_type_script(random_out_point().as_bytes());
+    let out_point = env.contract_out_point.clone();
+    let tx = build_update_tx(&mut env, out_point, type_script, 11, 0);
+
+    let mock_tx = build_mock_tx(&env.context, &tx);
+    let binary = Loader::default().load_binary("time_index_state_type_script");
+    let cycles = dry_run(&mock_tx, &code_hash, binary, MAX_CYCLES).expect("pass dry run");
+    assert!(cycles > 0);
+}
+
+#[test]
+fn test_dry_run_invalid_cell_data() {
+    let mut env = TimeIndexTestEnv::new();
+    let code_hash = env.contract().code_hash;
+    let type_script = env.build_type_script(random_out_point().as_bytes());
+    let out_point = env.contract_out_point.clone();
+    let tx = build_update_tx(&mut env, out_point, type_script, 3, 5);
+
+    let mock_tx = build_mock_tx(&env.context, &tx);
+    let binary = Loader::default().load_binary("time_index_state_type_script");
+    let err = match dry_run(&mock_tx, &code_hash, binary, MAX_CYCLES) {
+        Err(DryRunError::Verify(err)) => err,
+        _ => panic!("expect verify error"),
+    };
+    assert_eq!(
+        parse_script_failure(&mock_tx, &code_hash, &err),
+        Some(ScriptFailure {
+            source: "Inputs[0].Type".to_string(),
+            exit_code: 8,
+            error_code: Some(ErrorCode::InvalidCellData),
+        })
+    );
+}
+
+#[test]
+fn test_dry_run_replace_code_under_type_id() {
+    let mut env = TimeIndexTestEnv::new();
+    // the deployed code accepts everything, the local binary replaces it
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(ALWAYS_SUCCESS.clone());
+    let code_hash = type_id_script.calc_script_hash();
+    let type_script = build_type_hash_script(&type_id_script, random_out_point().as_bytes());
+    let tx = build_update_tx(&mut env, code_out_point, type_script, 3, 5);
+
+    let mock_tx = build_mock_tx(&env.context, &tx);
+    dry_run(&mock_tx, &code_hash, ALWAYS_SUCCESS.clone(), MAX_CYCLES).expect("pass dry run");
+    let binary = Loader::default().load_binary("time_index_state_type_script");
+    let err = match dry_run(&mock_tx, &code_hash, binary, MAX_CYCLES) {
+        Err(DryRunError::Verify(err)) => err,
+        _ => panic!("expect verify error"),
+    };
+    let failure = parse_script_failure(&mock_tx, &code_hash, &err).expect("script failure");
+    assert_eq!(failure.error_code, Some(ErrorCode::InvalidCellData));
+}
+
+#[test]
+fn test_dry_run_error_binary_mismatch() {
+    let mut env = TimeIndexTestEnv::new();
+    let code_hash = env.contract().code_hash;
+    let type_script = env.build_type_script(random_out_point().as_bytes());
+    let out_point = env.contract_out_point.clone();
+    let tx = build_update_tx(&mut env, out_point, type_script, 0, 1);
+
+    let mock_tx = build_mock_tx(&env.context, &tx);
+    match dry_run(&mock_tx, &code_hash, ALWAYS_SUCCESS.clone(), MAX_CYCLES) {
+        Err(DryRunError::BinaryMismatch) => {}
+        _ => panic!("expect binary mismatch"),
+    }
+}
@@ -0,0 +1,349 @@
+use std::{env, fs, path::PathBuf, process};
+
+use ckb_testtool::context::random_out_point;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{BlockBuilder, BlockView, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use indexer::{
+    source::BlockSource,
+    store::{StateRecord, Store},
+    Error, Indexer,
+};
+use tx_builder::{build_create_tx, build_update_tx, Contract, TimeIndexCell};
+
+use super::TIME_INDEX_CELL_DATA_N;
+
+const BLOCK_INTERVAL: u64 = 8_000;
+// default of the indexer and the monitor
+const DEFAULT_CONFIRMATIONS: u64 = 24;
+
+// blocks of the main chain in memory
+struct MockBlockSource {
+    blocks: Vec<BlockView>,
+}
+
+impl BlockSource for &MockBlockSource {
+    fn get_tip_block_number(&self) -> Result<u64, Error> {
+        Ok(self.blocks.len() as u64 - 1)
+    }
+
+    fn get_block(&self, number: u64) -> Result<Option<BlockView>, Error> {
+        Ok(self.blocks.get(number as usize).cloned())
+    }
+}
+
+impl MockBlockSource {
+    fn new() -> Self {
+        let mut source = MockBlockSource { blocks: Vec::new() };
+        source.push_block(0, vec![]);
+        source
+    }
+
+    fn push_block(&mut self, timestamp: u64, transactions: Vec<TransactionView>) {
+        let block = BlockBuilder::default()
+            .number((self.blocks.len() as u64).pack())
+            .timestamp(timestamp.pack())
+            .transactions(transactions)
+            .build();
+        self.blocks.push(block);
+    }
+}
+
+// a database directory removed on drop
+struct TempDb {
+    path: PathBuf,
+}
+
+impl TempDb {
+    fn new(name: &str) -> Self {
+        let path = env::temp_dir().join(format!("time-index-indexer-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&path);
+        TempDb { path }
+    }
+
+    fn open(&self) -> Store {
+        Store::open(&self.path).expect("open store")
+    }
+}
+
+impl Drop for TempDb {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.path);
+    }
+}
+
+fn contract() -> Contract {
+    Contract {
+        code_hash: [1u8; 32].pack(),
+        hash_type: ScriptHashType::Type,
+        cell_dep: CellDep::new_builder().out_point(random_out_point()).build(),
+    }
+}
+
+fn lock_script() -> Script {
+    Script::new_builder()
+        .args(Bytes::from("owner").pack())
+        .build()
+}
+
+// the time index cell moved by update transactions
+struct Oracle {
+    contract: Contract,
+    cell: TimeIndexCell,
+}
+
+impl Oracle {
+    fn create() -> (Self, TransactionView) {
+        let contract = contract();
+        let tx = build_create_tx(
+            &contract,
+            random_out_point(),
+            lock_script(),
+            TIME_INDEX_CELL_DATA_N,
+        )
+        .expect("create tx");
+        let cell = cell_of(&tx);
+        (Oracle { contract, cell }, tx)
+    }
+
+    fn update(&mut self) -> TransactionView {
+        let tx = build_update_tx(&self.contract, &self.cell).expect("update tx");
+        self.cell = cell_of(&tx);
+        tx
+    }
+
+    fn type_script(&self) -> Script {
+        self.cell.output.type_().to_opt().expect("type script")
+    }
+}
+
+fn cell_of(tx: &TransactionView) -> TimeIndexCell {
+    TimeIndexCell {
+        out_point: OutPoint::new(tx.hash(), 0),
+        output: tx.outputs().get(0).expect("output"),
+        data: tx.outputs_data().get(0).expect("data").unpack(),
+    }
+}
+
+fn plain_tx() -> TransactionView {
+    TransactionBuilder::default()
+        .input(CellInput::new(random_out_point(), 0))
+        .output(CellOutput::new_builder().lock(lock_script()).build())
+        .output_data(Bytes::new().pack())
+        .build()
+}
+
+// a cell with the type script of the time index cell which does not consume the live one,
+// rejected by the contract on chain but fed to the indexer here
+fn impostor_tx(oracle: &Oracle) -> TransactionView {
+    TransactionBuilder::default()
+        .input(CellInput::new(random_out_point(), 0))
+        .output(oracle.cell.output.clone())
+        .output_data(oracle.cell.data.pack())
+        .build()
+}
+
+fn indices(records: &[StateRecord]) -> Vec<u8> {
+    records.iter().map(|record| record.index).collect()
+}
+
+#[test]
+fn test_sync_follows_the_time_index_cell() {
+    let (mut oracle, create_tx) = Oracle::create();
+    let mut source = MockBlockSource::new();
+    source.push_block(BLOCK_INTERVAL, vec![plain_tx(), create_tx]);
+    for step in 2..=TIME_INDEX_CELL_DATA_N as u64 + 2 {
+        let impostor = impostor_tx(&oracle);
+        let update = oracle.update();
+        source.push_block(step * BLOCK_INTERVAL, vec![impostor, plain_tx(), update]);
+    }
+    source.push_block((TIME_INDEX_CELL_DATA_N as u64 + 3) * BLOCK_INTERVAL, vec![]);
+
+    let db = TempDb::new("follow");
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 0).expect("indexer");
+    assert_eq!(indexer.sync().expect("sync"), source.blocks.len() as u64);
+    let store = indexer.store();
+
+    let records = store.records().expect("records");
+    let mut expected: Vec<u8> = (0..TIME_INDEX_CELL_DATA_N).collect();
+    expected.extend_from_slice(&[0, 1]);
+    assert_eq!(indices(&records), expected);
+    assert!(records
+        .iter()
+        .all(|record| record.tx_index == if record.block_number == 1 { 1 } else { 2 }));
+
+    let current = store.current().expect("current").expect("live cell");
+    assert_eq!(current.out_point, oracle.cell.out_point);
+    assert_eq!(current.index, 1);
+    assert_eq!(current.n, TIME_INDEX_CELL_DATA_N);
+
+    // the index repeats every N updates, the lap tells the states apart
+    assert_eq!(current.lap, Some(1));
+    let first_lap = store.state_of(0, 1).expect("state").expect("first lap");
+    assert_eq!((first_lap.block_number, first_lap.lap), (2, Some(0)));
+    assert_eq!(store.state_of(1, 1).expect("state"), Some(current.clone()));
+    assert_eq!(store.state_of(2, 0).expect("state"), None);
+
+    assert_eq!(store.state_at(0).expect("state"), None);
+    let created = store.state_at(1).expect("state").expect("created");
+    assert_eq!((created.block_number, created.index), (1, 0));
+    assert_eq!(created.timestamp, BLOCK_INTERVAL);
+    let state = store.state_at(5).expect("state").expect("updated");
+    assert_eq!((state.block_number, state.index), (5, 4));
+    // the empty last block keeps the state of the block before
+    assert_eq!(
+        store
+            .state_at(source.blocks.len() as u64 - 1)
+            .expect("state"),
+        Some(current)
+    );
+}
+
+#[test]
+fn test_sync_waits_for_confirmations() {
+    let (mut oracle, create_tx) = Oracle::create();
+    let mut source = MockBlockSource::new();
+    source.push_block(BLOCK_INTERVAL, vec![create_tx]);
+    source.push_block(2 * BLOCK_INTERVAL, vec![oracle.update()]);
+
+    let db = TempDb::new("confirmations");
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 3).expect("indexer");
+    assert_eq!(indexer.sync().expect("sync"), 0);
+    assert_eq!(indexer.store().tip().expect("tip"), None);
+    drop(indexer);
+
+    for step in 3..6 {
+        source.push_block(step * BLOCK_INTERVAL, vec![]);
+    }
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 3).expect("indexer");
+    assert_eq!(indexer.sync().expect("sync"), 3);
+    assert_eq!(indexer.store().tip().expect("tip"), Some(2));
+    assert_eq!(indexer.sync().expect("sync"), 0);
+    drop(indexer);
+
+    // the next update is indexed once confirmed, following the cell indexed by the last sync
+    let update = oracle.update();
+    source.push_block(6 * BLOCK_INTERVAL, vec![update]);
+    for step in 7..10 {
+        source.push_block(step * BLOCK_INTERVAL, vec![]);
+    }
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 3).expect("indexer");
+    assert_eq!(indexer.sync().expect("sync"), 4);
+    let records = indexer.store().records().expect("records");
+    assert_eq!(indices(&records), vec![0, 1, 2]);
+    assert_eq!(records[2].block_number, 6);
+}
+
+#[test]
+fn test_sync_from_block() {
+    let (mut oracle, create_tx) = Oracle::create();
+    let mut source = MockBlockSource::new();
+    for step in 1..5 {
+        source.push_block(step * BLOCK_INTERVAL, vec![plain_tx()]);
+    }
+    source.push_block(5 * BLOCK_INTERVAL, vec![create_tx]);
+    source.push_block(6 * BLOCK_INTERVAL, vec![oracle.update()]);
+
+    let db = TempDb::new("from-block");
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 0)
+        .expect("indexer")
+        .from_block(5);
+    assert_eq!(indexer.sync().expect("sync"), 2);
+    let records = indexer.store().records().expect("records");
+    assert_eq!(indices(&records), vec![0, 1]);
+    assert_eq!(records[0].block_number, 5);
+    drop(indexer);
+
+    // the start block only applies to an empty store
+    source.push_block(7 * BLOCK_INTERVAL, vec![oracle.update()]);
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 0)
+        .expect("indexer")
+        .from_block(0);
+    assert_eq!(indexer.sync().expect("sync"), 1);
+    assert_eq!(indexer.store().tip().expect("tip"), Some(7));
+}
+
+#[test]
+fn test_sync_skips_reorganized_blocks() {
+    let (mut oracle, create_tx) = Oracle::create();
+    let mut source = MockBlockSource::new();
+    source.push_block(BLOCK_INTERVAL, vec![create_tx]);
+    for step in 2..31 {
+        source.push_block(step * BLOCK_INTERVAL, vec![]);
+    }
+    let update = oracle.update();
+    source.push_block(31 * BLOCK_INTERVAL, vec![update.clone()]);
+
+    let db = TempDb::new("reorg");
+    let type_script = oracle.type_script();
+    let mut indexer = Indexer::new(
+        &source,
+        db.open(),
+        type_script.clone(),
+        DEFAULT_CONFIRMATIONS,
+    )
+    .expect("indexer");
+    assert_eq!(indexer.sync().expect("sync"), 8);
+    drop(indexer);
+
+    // a reorg replaces the last 4 blocks, the update is committed again in another block
+    source.blocks.truncate(28);
+    for step in 28..30 {
+        source.push_block(step * BLOCK_INTERVAL + 1, vec![]);
+    }
+    source.push_block(30 * BLOCK_INTERVAL + 1, vec![update]);
+    for step in 31..(31 + DEFAULT_CONFIRMATIONS) {
+        source.push_block(step * BLOCK_INTERVAL + 1, vec![]);
+    }
+    let mut indexer =
+        Indexer::new(&source, db.open(), type_script, DEFAULT_CONFIRMATIONS).expect("indexer");
+    assert_eq!(indexer.sync().expect("sync"), 23);
+    let records = indexer.store().records().expect("records");
+    assert_eq!(indices(&records), vec![0, 1]);
+    assert_eq!(records[1].block_number, 30);
+    assert_eq!(records[1].timestamp, 30 * BLOCK_INTERVAL + 1);
+}
+
+#[test]
+fn test_gaps() {
+    let (mut oracle, create_tx) = Oracle::create();
+    let mut source = MockBlockSource::new();
+    source.push_block(60_000, vec![create_tx]);
+    source.push_block(120_000, vec![oracle.update()]);
+    source.push_block(300_000, vec![oracle.update()]);
+    source.push_block(330_000, vec![]);
+    source.push_block(360_000, vec![oracle.update()]);
+    source.push_block(1_000_000, vec![oracle.update()]);
+
+    let db = TempDb::new("gaps");
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 0).expect("indexer");
+    indexer.sync().expect("sync");
+    let gaps = indexer.store().gaps(60_000).expect("gaps");
+    let gaps: Vec<(u64, u64, u64)> = gaps
+        .iter()
+        .map(|gap| (gap.from.block_number, gap.to.block_number, gap.duration))
+        .collect();
+    assert_eq!(gaps, vec![(2, 3, 180_000), (5, 6, 640_000)]);
+    assert!(indexer.store().gaps(1_000_000).expect("gaps").is_empty());
+}
+
+#[test]
+fn test_store_bound_to_type_script() {
+    let (oracle, create_tx) = Oracle::create();
+    let mut source = MockBlockSource::new();
+    source.push_block(BLOCK_INTERVAL, vec![create_tx]);
+
+    let db = TempDb::new("type-script");
+    let mut indexer = Indexer::new(&source, db.open(), oracle.type_script(), 0).expect("indexer");
+    indexer.sync().expect("sync");
+    drop(indexer);
+
+    let (other, _) = Oracle::create();
+    match Indexer::new(&source, db.open(), other.type_script(), 0) {
+        Err(Error::TypeScriptMismatch) => {}
+        _ => panic!("the store indexes another type script"),
+    }
+}
@@ -0,0 +1,352 @@
+use ckb_testtool::context::Context;
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::{
+        bytes::Bytes,
+        core::{DepType, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+};
+use inspector::{
+    decode_args, decode_cell_data,
+    explain::{explain_tx, Action},
+    mock_tx::{MockCellDep, MockInput, MockTransaction},
+    predict_next_cell_data, ClockMode, Epoch, ErrorCode, TimeIndexArgs, TimeIndexState,
+};
+use tx_builder::{
+    build_cell_data, build_cell_data_with_lap, build_create_tx, build_update_tx, decode_hex,
+    TimeIndexCell,
+};
+
+use super::*;
+
+fn build_create_tx_with_data(env: &mut TimeIndexTestEnv, data: Bytes) -> TransactionView {
+    let input_out_point = env.create_plain_cell();
+    let tx = build_create_tx(
+        &env.contract(),
+        input_out_point,
+        env.lock_script.clone(),
+        TIME_INDEX_CELL_DATA_N,
+    )
+    .expect("create tx");
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs_data(vec![data.pack()])
+        .build();
+    env.context.complete_tx(tx)
+}
+
+fn build_update_tx_with_data(
+    env: &mut TimeIndexTestEnv,
+    input_data: Bytes,
+    output_data: Bytes,
+) -> TransactionView {
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(env.lock_script.clone())
+        .type_(Some(env.contract().type_script(Bytes::from(vec![1; 36]))).pack())
+        .build();
+    let out_point = env.context.create_cell(output.clone(), input_data.clone());
+    let current_cell = TimeIndexCell {
+        out_point,
+        output,
+        data: input_data,
+    };
+    let tx = build_update_tx(&env.contract(), &current_cell).expect("update tx");
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs_data(vec![output_data.pack()])
+        .build();
+    env.context.complete_tx(tx)
+}
+
+// snapshot of the transaction and the cells it references, passed through json like a ckb-cli mock tx,
+// the cells in dep groups are included as well
+pub(crate) fn build_mock_tx(context: &Context, tx: &TransactionView) -> MockTransaction {
+    let inputs = tx
+        .inputs()
+        .into_iter()
+        .map(|input| {
+            let (output, data) = context.get_cell(&input.previous_output()).expect("cell");
+            MockInput {
+                input,
+                output,
+                data,
+                header: None,
+            }
+        })
+        .collect();
+    let mut cell_deps = Vec::new();
+    for cell_dep in tx.cell_deps().into_iter() {
+        let (output, data) = context.get_cell(&cell_dep.out_point()).expect("cell");
+        if cell_dep.dep_type() == DepType::DepGroup.into() {
+            for out_point in OutPointVec::from_slice(&data)
+                .expect("dep group")
+                .into_iter()
+            {
+                let (output, data) = context.get_cell(&out_point).expect("cell");
+                cell_deps.push(MockCellDep {
+                    cell_dep: CellDep::new_builder().out_point(out_point).build(),
+                    output,
+                    data,
+                    header: None,
+                });
+            }
+        }
+        cell_deps.push(MockCellDep {
+            cell_dep,
+            output,
+            data,
+            header: None,
+        });
+    }
+    let mock_tx = MockTransaction {
+        inputs,
+        cell_deps,
+        header_deps: Vec::new(),
+        tx: tx.clone(),
+    };
+    MockTransaction::from_json(&mock_tx.to_json().expect("json")).expect("mock tx")
+}
+
+// the explanation must agree with the result of running the contract
+fn assert_explained(
+    env: &TimeIndexTestEnv,
+    tx: TransactionView,
+    action: Action,
+    expected: Result<(), ErrorCode>,
+) {
+    let mock_tx = build_mock_tx(&env.context, &tx);
+    let reports = explain_tx(&mock_tx, &env.contract().code_hash);
+    assert_eq!(reports.len(), 1);
+    assert_eq!(reports[0].action, action);
+    assert_eq!(reports[0].result, expected);
+
+    let result = env.verify_tx(&tx);
+    match expected {
+        Ok(()) => {
+            result.expect("pass verification");
+        }
+        Err(code) => {
+            let script_error = ScriptError::ValidationFailure(code.code());
+            let script_error = match action {
+                Action::Create => script_error.output_type_script(0),
+                Action::Update => script_error.input_type_script(0),
+            };
+            assert_error_eq!(result.unwrap_err(), script_error);
+        }
+    }
+}
+
+#[test]
+fn test_decode_cell_data() {
+    assert_eq!(
+        decode_cell_data(&[11, TIME_INDEX_CELL_DATA_N]),
+        Ok(TimeIndexState {
+            index: 11,
+            n: TIME_INDEX_CELL_DATA_N,
+            version: 0,
+            lap: None,
+            epoch: None,
+        })
+    );
+    // number 5, index 10 of 1800
+    let epoch: u64 = 5 | (10 << 24) | (1800 << 40);
+    let mut data = vec![3, TIME_INDEX_CELL_DATA_N, 1];
+    data.extend_from_slice(&epoch.to_le_bytes());
+    assert_eq!(
+        decode_cell_data(&data),
+        Ok(TimeIndexState {
+            index: 3,
+            n: TIME_INDEX_CELL_DATA_N,
+            version: 1,
+            lap: None,
+            epoch: Some(Epoch {
+                number: 5,
+                index: 10,
+                length: 1800,
+            }),
+        })
+    );
+    assert_eq!(
+        predict_next_cell_data(&[11, TIME_INDEX_CELL_DATA_N, 1]),
+        Ok(Bytes::from(vec![0, TIME_INDEX_CELL_DATA_N, 1]))
+    );
+    // the lap moves on with the wraparound
+    assert_eq!(
+        decode_cell_data(&build_cell_data_with_lap(11, TIME_INDEX_CELL_DATA_N, 4)),
+        Ok(TimeIndexState {
+            index: 11,
+            n: TIME_INDEX_CELL_DATA_N,
+            version: 2,
+            lap: Some(4),
+            epoch: None,
+        })
+    );
+    assert_eq!(
+        predict_next_cell_data(&build_cell_data_with_lap(11, TIME_INDEX_CELL_DATA_N, 4)),
+        Ok(build_cell_data_with_lap(0, TIME_INDEX_CELL_DATA_N, 5))
+    );
+    // the last index of the last lap has no next state
+    let data = decode_hex("0b0c02ffffffffffffffff").expect("hex");
+    assert_eq!(
+        decode_cell_data(&data),
+        Ok(TimeIndexState {
+            index: 11,
+            n: TIME_INDEX_CELL_DATA_N,
+            version: 2,
+            lap: Some(u64::MAX),
+            epoch: None,
+        })
+    );
+    assert_eq!(predict_next_cell_data(&data), Err(ErrorCode::InvalidLap));
+    assert_eq!(
+        decode_cell_data(&[12, TIME_INDEX_CELL_DATA_N]),
+        Err(ErrorCode::InvalidCellData)
+    );
+    assert_eq!(
+        decode_cell_data(&[0, TIME_INDEX_CELL_DATA_N, 3]),
+        Err(ErrorCode::InvalidDataVersion)
+    );
+}
+
+#[test]
+fn test_decode_args() {
+    let out_point = OutPoint::new(Byte32::new([3; 32]), 7);
+    assert_eq!(
+        decode_args(out_point.as_slice()),
+        Ok(TimeIndexArgs {
+            out_point: out_point.clone(),
+            mode: None,
+        })
+    );
+    let mut args = out_point.as_slice().to_vec();
+    args.extend_from_slice(&3600u64.to_le_bytes());
+    assert_eq!(
+        decode_args(&args),
+        Ok(TimeIndexArgs {
+            out_point: out_point.clone(),
+            mode: Some(ClockMode::Timestamp(3600)),
+        })
+    );
+    args.push(1);
+    assert_eq!(
+        decode_args(&args),
+        Ok(TimeIndexArgs {
+            out_point: out_point.clone(),
+            mode: Some(ClockMode::BlockNumber(3600)),
+        })
+    );
+    args[44] = 2;
+    assert_eq!(
+        decode_args(&args),
+        Ok(TimeIndexArgs {
+            out_point,
+            mode: Some(ClockMode::Epoch(3600)),
+        })
+    );
+    args[44] = 3;
+    assert_eq!(decode_args(&args), Err(ErrorCode::InvalidArgument));
+    assert_eq!(decode_args(&[0; 32]), Err(ErrorCode::InvalidArgument));
+    assert_eq!(decode_args(&[0; 44]), Err(ErrorCode::InvalidArgument));
+}
+
+#[test]
+fn test_explain_create() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_create_tx_with_data(&mut env, build_cell_data(0, TIME_INDEX_CELL_DATA_N));
+    assert_explained(&env, tx, Action::Create, Ok(()));
+}
+
+#[test]
+fn test_explain_create_invalid_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_create_tx_with_data(&mut env, build_cell_data(1, TIME_INDEX_CELL_DATA_N));
+    assert_explained(&env, tx, Action::Create, Err(ErrorCode::InvalidCellData));
+}
+
+#[test]
+fn test_explain_create_invalid_args() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_create_tx_with_data(&mut env, build_cell_data(0, TIME_INDEX_CELL_DATA_N));
+    let output = tx.output(0).expect("output");
+    let type_script = env.contract().type_script(Bytes::from(vec![0; 36]));
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![output
+            .as_builder()
+            .type_(Some(type_script).pack())
+            .build()])
+        .build();
+    assert_explained(&env, tx, Action::Create, Err(ErrorCode::InvalidArgument));
+}
+
+#[test]
+fn test_explain_update() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx_with_data(
+        &mut env,
+        build_cell_data(11, TIME_INDEX_CELL_DATA_N),
+        build_cell_data_with_lap(0, TIME_INDEX_CELL_DATA_N, 1),
+    );
+    assert_explained(&env, tx, Action::Update, Ok(()));
+}
+
+#[test]
+fn test_explain_update_replay_lap() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx_with_data(
+        &mut env,
+        build_cell_data(11, TIME_INDEX_CELL_DATA_N),
+        build_cell_data(0, TIME_INDEX_CELL_DATA_N),
+    );
+    assert_explained(&env, tx, Action::Update, Err(ErrorCode::InvalidLap));
+}
+
+#[test]
+fn test_explain_update_skip_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx_with_data(
+        &mut env,
+        build_cell_data(3, TIME_INDEX_CELL_DATA_N),
+        build_cell_data(5, TIME_INDEX_CELL_DATA_N),
+    );
+    assert_explained(&env, tx, Action::Update, Err(ErrorCode::InvalidCellData));
+}
+
+#[test]
+fn test_explain_update_downgrade_version() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx_with_data(
+        &mut env,
+        build_cell_data(3, TIME_INDEX_CELL_DATA_N),
+        Bytes::from(vec![4, TIME_INDEX_CELL_DATA_N]),
+    );
+    assert_explained(&env, tx, Action::Update, Err(ErrorCode::InvalidDataVersion));
+}
+
+#[test]
+fn test_explain_update_drop_output() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx_with_data(
+        &mut env,
+        build_cell_data(3, TIME_INDEX_CELL_DATA_N),
+        build_cell_data(4, TIME_INDEX_CELL_DATA_N),
+    );
+    let output = tx.output(0).expect("output");
+    let tx = tx
+        .as_advanced_builder()
+        .set_outputs(vec![output
+            .as_builder()
+            .type_(ScriptOpt::default())
+            .build()])
+        .build();
+    assert_explained(
+        &env,
+        tx,
+        Action::Update,
+        Err(ErrorCode::InvalidTimeIndexOutput),
+    );
+}
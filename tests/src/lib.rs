@@ -3,11 +3,69 @@ use std::fs;
 use std::path::PathBuf;
 use std::str::FromStr;
 
-use ckb_tool::ckb_types::bytes::Bytes;
+use ckb_testtool::{
+    builtin::ALWAYS_SUCCESS,
+    context::{random_hash, Context},
+};
+use ckb_tool::{
+    ckb_chain_spec::consensus::TYPE_ID_CODE_HASH,
+    ckb_error::Error as CKBError,
+    ckb_script::TransactionScriptsVerifier,
+    ckb_types::{
+        bytes::Bytes,
+        core::{
+            cell::{CellMetaBuilder, ResolvedTransaction},
+            Capacity, Cycle, DepType, HeaderView, ScriptHashType, TransactionBuilder,
+            TransactionView,
+        },
+        packed::*,
+        prelude::*,
+    },
+};
 
+#[cfg(test)]
+mod adversarial_tests;
+#[cfg(test)]
+mod consumer_tests;
 #[cfg(test)]
 mod create_tests;
+#[cfg(test)]
+mod cycles_tests;
+#[cfg(test)]
+mod devnet_tests;
+pub mod dry_run;
+#[cfg(test)]
+mod dry_run_tests;
+#[cfg(test)]
+mod indexer_tests;
+#[cfg(test)]
+mod inspector_tests;
+#[cfg(test)]
+mod lifecycle_tests;
+#[cfg(test)]
+mod lock_tests;
+#[cfg(test)]
+mod migrator_tests;
+#[cfg(test)]
+mod monitor_tests;
+#[cfg(test)]
+mod proptest_tests;
+#[cfg(test)]
+mod time_info_tests;
+#[cfg(test)]
+mod timestamp_reader_tests;
+#[cfg(test)]
+mod tx_builder_tests;
+#[cfg(test)]
 mod update_test;
+#[cfg(test)]
+mod updater_tests;
+#[cfg(test)]
+mod upgrade_tests;
+#[cfg(test)]
+mod validation_tests;
+#[cfg(test)]
+mod vault_tests;
 
 const TEST_ENV_VAR: &str = "CAPSULE_TEST_ENV";
 
@@ -61,3 +119,358 @@ impl Loader {
         fs::read(path).expect("binary").into()
     }
 }
+
+pub const MAX_CYCLES: u64 = 10_000_000;
+pub const TIME_INDEX_CELL_DATA_N: u8 = 12;
+
+/// Cell data of the first release, `index | N`
+pub fn build_time_index_cell_data(index: u8) -> Bytes {
+    Bytes::from(vec![index, TIME_INDEX_CELL_DATA_N])
+}
+
+/// Script referencing the code cell guarded by `type_id_script` by type hash
+pub fn build_type_hash_script(type_id_script: &Script, args: Bytes) -> Script {
+    Script::new_builder()
+        .code_hash(type_id_script.calc_script_hash())
+        .hash_type(ScriptHashType::Type.into())
+        .args(args.pack())
+        .build()
+}
+
+/// Context with time_index_state_type_script and an always success lock deployed
+pub struct TimeIndexTestEnv {
+    pub context: Context,
+    pub contract_out_point: OutPoint,
+    pub always_success_out_point: OutPoint,
+    pub lock_script: Script,
+}
+
+impl Default for TimeIndexTestEnv {
+    fn default() -> Self {
+        let mut context = Context::default();
+        let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+        let contract_out_point = context.deploy_cell(contract_bin);
+        let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
+        let lock_script = context
+            .build_script(&always_success_out_point, Default::default())
+            .expect("script");
+        TimeIndexTestEnv {
+            context,
+            contract_out_point,
+            always_success_out_point,
+            lock_script,
+        }
+    }
+}
+
+impl TimeIndexTestEnv {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn build_type_script(&mut self, args: Bytes) -> Script {
+        self.context
+            .build_script(&self.contract_out_point, args)
+            .expect("script")
+    }
+
+    /// Always success lock told apart from the others by `args`
+    pub fn build_always_success_script(&mut self, args: Bytes) -> Script {
+        self.context
+            .build_script(&self.always_success_out_point, args)
+            .expect("script")
+    }
+
+    /// Deploy a binary of the build directory
+    pub fn deploy_binary(&mut self, name: &str) -> OutPoint {
+        let binary: Bytes = Loader::default().load_binary(name);
+        self.context.deploy_cell(binary)
+    }
+
+    /// Deploy `binary` as `deployment.toml` does, in a code cell guarded by a type id script.
+    /// Returns the type id script and the out point of the code cell
+    pub fn deploy_type_id_cell(&mut self, binary: Bytes) -> (Script, OutPoint) {
+        let type_id_script = Script::new_builder()
+            .code_hash(TYPE_ID_CODE_HASH.pack())
+            .hash_type(ScriptHashType::Type.into())
+            .args(random_hash().as_bytes().pack())
+            .build();
+        let out_point = self.context.create_cell(
+            CellOutput::new_builder()
+                .capacity(Capacity::bytes(binary.len()).expect("capacity").pack())
+                .lock(self.lock_script.clone())
+                .type_(Some(type_id_script.clone()).pack())
+                .build(),
+            binary,
+        );
+        (type_id_script, out_point)
+    }
+
+    /// Live cell without type script, locked by the always success lock
+    pub fn create_plain_cell(&mut self) -> OutPoint {
+        self.context.create_cell(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.lock_script.clone())
+                .build(),
+            Bytes::new(),
+        )
+    }
+
+    /// Transaction creating a time index cell at index 0, the args are the out point of the first input
+    pub fn create_tx(&mut self) -> TimeIndexTxBuilder<'_> {
+        TimeIndexTxBuilder {
+            env: self,
+            args: None,
+            type_id_contract: None,
+            lock_script: None,
+            input_data: None,
+            output_data: build_time_index_cell_data(0),
+            input_count: 1,
+            output_count: 1,
+            output_capacity: 500,
+            since: 0,
+            input_block: None,
+            header_deps: Vec::new(),
+        }
+    }
+
+    /// Transaction updating a time index cell from index `from` to index `to`
+    pub fn update_tx(&mut self, from: u8, to: u8) -> TimeIndexTxBuilder<'_> {
+        TimeIndexTxBuilder {
+            env: self,
+            args: None,
+            type_id_contract: None,
+            lock_script: None,
+            input_data: Some(build_time_index_cell_data(from)),
+            output_data: build_time_index_cell_data(to),
+            input_count: 1,
+            output_count: 1,
+            output_capacity: 500,
+            since: 0,
+            input_block: None,
+            header_deps: Vec::new(),
+        }
+    }
+
+    /// Type script of the time info cells following the time index cell typed by `time_index_type_script`
+    pub fn build_time_info_type_script(&mut self, time_index_type_script: &Script) -> Script {
+        self.time_info_contract()
+            .type_script(tx_builder::build_time_info_args(time_index_type_script))
+    }
+
+    /// Deploy time_info_type_script, referenced by data hash, for `tx_builder`
+    pub fn time_info_contract(&mut self) -> tx_builder::Contract {
+        let time_info_out_point = self.deploy_binary("time_info_type_script");
+        self.data_hash_contract(time_info_out_point)
+    }
+
+    /// The deployed contract referenced by data hash, for `tx_builder`
+    pub fn contract(&self) -> tx_builder::Contract {
+        self.data_hash_contract(self.contract_out_point.clone())
+    }
+
+    fn data_hash_contract(&self, out_point: OutPoint) -> tx_builder::Contract {
+        let (_, binary) = self.context.get_cell(&out_point).expect("code cell");
+        tx_builder::Contract {
+            code_hash: CellOutput::calc_data_hash(&binary),
+            hash_type: ScriptHashType::Data,
+            cell_dep: CellDep::new_builder().out_point(out_point).build(),
+        }
+    }
+
+    pub fn verify_tx(&self, tx: &TransactionView) -> Result<Cycle, CKBError> {
+        self.context.verify_tx(tx, MAX_CYCLES)
+    }
+
+    /// Run the scripts with dep groups expanded as the node does, which `Context::verify_tx` doesn't
+    pub fn verify_tx_with_dep_groups(&self, tx: &TransactionView) -> Result<Cycle, CKBError> {
+        let cell_meta = |out_point: OutPoint| {
+            let (output, data) = self.context.get_cell(&out_point).expect("live cell");
+            CellMetaBuilder::from_cell_output(output, data)
+                .out_point(out_point)
+                .build()
+        };
+        let mut resolved_cell_deps = Vec::new();
+        let mut resolved_dep_groups = Vec::new();
+        for cell_dep in tx.cell_deps_iter() {
+            let dep = cell_meta(cell_dep.out_point());
+            if cell_dep.dep_type() == DepType::DepGroup.into() {
+                let data = dep.mem_cell_data.as_ref().expect("data").0.clone();
+                let out_points = OutPointVec::from_slice(&data).expect("dep group data");
+                resolved_cell_deps.extend(out_points.into_iter().map(cell_meta));
+                resolved_dep_groups.push(dep);
+            } else {
+                resolved_cell_deps.push(dep);
+            }
+        }
+        let resolved_tx = ResolvedTransaction {
+            transaction: tx.clone(),
+            resolved_cell_deps,
+            resolved_inputs: tx.input_pts_iter().map(cell_meta).collect(),
+            resolved_dep_groups,
+        };
+        TransactionScriptsVerifier::new(&resolved_tx, &self.context).verify(MAX_CYCLES)
+    }
+}
+
+/// Shape of a create or update transaction, change it with the mutators before `build`
+pub struct TimeIndexTxBuilder<'a> {
+    env: &'a mut TimeIndexTestEnv,
+    args: Option<Bytes>,
+    // type id script and code cell of a contract referenced by type hash, instead of the deployed one
+    type_id_contract: Option<(Script, OutPoint)>,
+    // lock of the time index cells, the always success lock by default
+    lock_script: Option<Script>,
+    // none for the create transaction
+    input_data: Option<Bytes>,
+    output_data: Bytes,
+    // time index cells in inputs of the update transaction, or plain cells of the create transaction
+    input_count: usize,
+    output_count: usize,
+    output_capacity: u64,
+    // since of the inputs
+    since: u64,
+    // block committing the inputs, its header is added to header deps
+    input_block: Option<HeaderView>,
+    header_deps: Vec<HeaderView>,
+}
+
+impl<'a> TimeIndexTxBuilder<'a> {
+    pub fn args(mut self, args: Bytes) -> Self {
+        self.args = Some(args);
+        self
+    }
+
+    pub fn type_id_contract(mut self, type_id_script: Script, code_out_point: OutPoint) -> Self {
+        self.type_id_contract = Some((type_id_script, code_out_point));
+        self
+    }
+
+    pub fn lock(mut self, lock_script: Script) -> Self {
+        self.lock_script = Some(lock_script);
+        self
+    }
+
+    pub fn input_data(mut self, data: Bytes) -> Self {
+        self.input_data = Some(data);
+        self
+    }
+
+    pub fn output_data(mut self, data: Bytes) -> Self {
+        self.output_data = data;
+        self
+    }
+
+    pub fn input_count(mut self, count: usize) -> Self {
+        self.input_count = count;
+        self
+    }
+
+    pub fn output_count(mut self, count: usize) -> Self {
+        self.output_count = count;
+        self
+    }
+
+    pub fn output_capacity(mut self, capacity: u64) -> Self {
+        self.output_capacity = capacity;
+        self
+    }
+
+    pub fn since(mut self, since: u64) -> Self {
+        self.since = since;
+        self
+    }
+
+    pub fn input_block(mut self, header: HeaderView) -> Self {
+        self.input_block = Some(header);
+        self
+    }
+
+    pub fn header_dep(mut self, header: HeaderView) -> Self {
+        self.header_deps.push(header);
+        self
+    }
+
+    pub fn build(self) -> TransactionView {
+        let env = self.env;
+        let since = self.since;
+        let lock_script = self.lock_script.unwrap_or_else(|| env.lock_script.clone());
+        let type_id_contract = self.type_id_contract;
+        let build_type_script = |env: &mut TimeIndexTestEnv, args| match &type_id_contract {
+            Some((type_id_script, _)) => build_type_hash_script(type_id_script, args),
+            None => env.build_type_script(args),
+        };
+        let (type_script, inputs) = match self.input_data {
+            None => {
+                let input_out_points: Vec<OutPoint> = (0..self.input_count)
+                    .map(|_| env.create_plain_cell())
+                    .collect();
+                let args = self.args.unwrap_or_else(|| input_out_points[0].as_bytes());
+                (build_type_script(env, args), input_out_points)
+            }
+            Some(input_data) => {
+                let args = self
+                    .args
+                    .unwrap_or_else(|| env.contract_out_point.as_bytes());
+                let type_script = build_type_script(env, args);
+                let input_out_points = (0..self.input_count)
+                    .map(|_| {
+                        env.context.create_cell(
+                            CellOutput::new_builder()
+                                .capacity(1000u64.pack())
+                                .lock(lock_script.clone())
+                                .type_(Some(type_script.clone()).pack())
+                                .build(),
+                            input_data.clone(),
+                        )
+                    })
+                    .collect();
+                (type_script, input_out_points)
+            }
+        };
+
+        let mut header_deps = self.header_deps;
+        if let Some(block) = self.input_block {
+            env.context.insert_header(block.clone());
+            for out_point in inputs.iter() {
+                env.context
+                    .link_cell_with_block(out_point.clone(), block.hash(), 0);
+            }
+            header_deps.insert(0, block);
+        }
+        for header in header_deps.iter() {
+            env.context.insert_header(header.clone());
+        }
+
+        let output = CellOutput::new_builder()
+            .capacity(self.output_capacity.pack())
+            .lock(lock_script.clone())
+            .type_(Some(type_script).pack())
+            .build();
+        let tx = TransactionBuilder::default()
+            .inputs(
+                inputs
+                    .into_iter()
+                    .map(|out_point| CellInput::new(out_point, since)),
+            )
+            .outputs(vec![output; self.output_count])
+            .outputs_data(vec![self.output_data.pack(); self.output_count])
+            .header_deps(header_deps.iter().map(|header| header.hash()))
+            .build();
+        match type_id_contract {
+            // complete_tx only finds code cells by data hash
+            Some((_, code_out_point)) => {
+                let lock_out_point = env
+                    .context
+                    .get_cell_by_data_hash(&lock_script.code_hash())
+                    .expect("lock code cell");
+                tx.as_advanced_builder()
+                    .cell_dep(CellDep::new_builder().out_point(lock_out_point).build())
+                    .cell_dep(CellDep::new_builder().out_point(code_out_point).build())
+                    .build()
+            }
+            None => env.context.complete_tx(tx),
+        }
+    }
+}
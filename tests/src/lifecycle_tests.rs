@@ -0,0 +1,175 @@
+use std::collections::HashSet;
+
+use ckb_tool::{
+    ckb_error::{assert_error_eq, Error as CKBError},
+    ckb_script::ScriptError,
+    ckb_types::{bytes::Bytes, core::TransactionView, packed::*, prelude::*},
+};
+use tx_builder::{build_create_tx, build_update_tx, Contract, TimeIndexCell};
+
+use super::*;
+
+// error numbers
+const TIME_INDEX_INVALID_CELL_DATA: i8 = 8;
+const TIME_INDEX_INVALID_LAP: i8 = 13;
+
+// complete laps of the ring
+const LAPS: usize = 3;
+
+#[derive(Debug)]
+enum CommitError {
+    DeadCell(OutPoint),
+    Verify(CKBError),
+}
+
+// commits transactions on top of the test context, an input must be live
+// and is dead after the transaction consuming it is committed
+struct Chain {
+    env: TimeIndexTestEnv,
+    contract: Contract,
+    live_cells: HashSet<OutPoint>,
+}
+
+impl Chain {
+    fn new() -> Self {
+        let env = TimeIndexTestEnv::new();
+        let contract = env.contract();
+        Chain {
+            env,
+            contract,
+            live_cells: HashSet::new(),
+        }
+    }
+
+    fn create_input(&mut self) -> OutPoint {
+        let out_point = self.env.context.create_cell(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.env.lock_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        self.live_cells.insert(out_point.clone());
+        out_point
+    }
+
+    fn commit(&mut self, tx: TransactionView) -> Result<TimeIndexCell, CommitError> {
+        let tx = self.env.context.complete_tx(tx);
+        for input in tx.inputs() {
+            if !self.live_cells.contains(&input.previous_output()) {
+                return Err(CommitError::DeadCell(input.previous_output()));
+            }
+        }
+        self.env.verify_tx(&tx).map_err(CommitError::Verify)?;
+
+        for input in tx.inputs() {
+            self.live_cells.remove(&input.previous_output());
+        }
+        let out_point = OutPoint::new(tx.hash(), 0);
+        let output = tx.outputs().get(0).expect("output");
+        let data: Bytes = tx.outputs_data().get(0).expect("output data").unpack();
+        self.env.context.create_cell_with_out_point(
+            out_point.clone(),
+            output.clone(),
+            data.clone(),
+        );
+        self.live_cells.insert(out_point.clone());
+        Ok(TimeIndexCell {
+            out_point,
+            output,
+            data,
+        })
+    }
+}
+
+fn assert_state(cell: &TimeIndexCell, index: u8) {
+    assert_eq!(cell.data[0], index);
+    assert_eq!(cell.data[1], TIME_INDEX_CELL_DATA_N);
+    assert_eq!(cell.data[2], tx_builder::TIME_INDEX_CELL_DATA_VERSION);
+}
+
+#[test]
+fn test_lifecycle() {
+    let mut chain = Chain::new();
+    let input_out_point = chain.create_input();
+    let tx = build_create_tx(
+        &chain.contract,
+        input_out_point,
+        chain.env.lock_script.clone(),
+        TIME_INDEX_CELL_DATA_N,
+    )
+    .expect("create tx");
+    let mut cell = chain.commit(tx).expect("create");
+    assert_state(&cell, 0);
+    let type_script = cell.output.type_().to_opt().expect("type script");
+
+    let mut history = vec![cell.clone()];
+    for step in 1..=LAPS * TIME_INDEX_CELL_DATA_N as usize {
+        let tx = build_update_tx(&chain.contract, &cell).expect("update tx");
+        cell = chain.commit(tx).expect("update");
+        assert_state(&cell, (step % TIME_INDEX_CELL_DATA_N as usize) as u8);
+        assert_eq!(cell.output.type_().to_opt(), Some(type_script.clone()));
+        history.push(cell.clone());
+    }
+    // back to index 0 after each lap
+    assert_state(&cell, 0);
+
+    // the previous states are consumed
+    for old_cell in &history[..history.len() - 1] {
+        assert!(!chain.live_cells.contains(&old_cell.out_point));
+    }
+}
+
+#[test]
+fn test_replay_old_state() {
+    let mut chain = Chain::new();
+    let input_out_point = chain.create_input();
+    let tx = build_create_tx(
+        &chain.contract,
+        input_out_point,
+        chain.env.lock_script.clone(),
+        TIME_INDEX_CELL_DATA_N,
+    )
+    .expect("create tx");
+    let mut cell = chain.commit(tx).expect("create");
+
+    let mut history = vec![cell.clone()];
+    for _ in 0..TIME_INDEX_CELL_DATA_N + 1 {
+        let tx = build_update_tx(&chain.contract, &cell).expect("update tx");
+        cell = chain.commit(tx).expect("update");
+        history.push(cell.clone());
+    }
+
+    // updating a consumed state again fails
+    for old_cell in &history[..history.len() - 1] {
+        let tx = build_update_tx(&chain.contract, old_cell).expect("update tx");
+        match chain.commit(tx) {
+            Err(CommitError::DeadCell(out_point)) => assert_eq!(out_point, old_cell.out_point),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+
+    // moving the live cell back to an old state fails in the script,
+    // the state of the last lap at the next index is told apart by its lap
+    let next_data = tx_builder::next_cell_data(&cell.data).expect("next cell data");
+    for old_cell in &history {
+        let expected_error = if old_cell.data[0] == next_data[0] {
+            TIME_INDEX_INVALID_LAP
+        } else {
+            TIME_INDEX_INVALID_CELL_DATA
+        };
+        let tx = build_update_tx(&chain.contract, &cell).expect("update tx");
+        let tx = tx
+            .as_advanced_builder()
+            .set_outputs_data(vec![old_cell.data.pack()])
+            .build();
+        match chain.commit(tx) {
+            Err(CommitError::Verify(err)) => assert_error_eq!(
+                err,
+                ScriptError::ValidationFailure(expected_error).input_type_script(0)
+            ),
+            other => panic!("unexpected result: {:?}", other.map(|_| ())),
+        }
+    }
+    assert!(chain.live_cells.contains(&cell.out_point));
+}
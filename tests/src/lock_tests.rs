@@ -0,0 +1,432 @@
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::bytes::BufMut,
+    ckb_types::{
+        bytes::{Bytes, BytesMut},
+        core::TransactionView,
+        packed::{self, *},
+        prelude::*,
+    },
+};
+
+use super::*;
+
+// error numbers
+const TIME_INDEX_LOCK_INVALID_ARGS: i8 = 5;
+const TIME_INDEX_LOCK_INVALID_OUTPUT: i8 = 7;
+const TIME_INDEX_LOCK_OWNER_NOT_FOUND: i8 = 8;
+const TIME_INDEX_LOCK_INVALID_SINCE: i8 = 9;
+const TIME_INDEX_LOCK_INVALID_CAPACITY: i8 = 10;
+const TIME_INDEX_LOCK_INVALID_TIME_INFO_OUTPUT: i8 = 11;
+
+const UPDATE_INTERVAL: u64 = 60;
+const UPDATE_REWARD: u64 = 100;
+// relative since measured by the median timestamp, in seconds
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = 0xc000_0000_0000_0000;
+// absolute since measured by the median timestamp, in seconds
+const SINCE_TIMESTAMP_FLAG: u64 = 0x4000_0000_0000_0000;
+
+fn build_permissionless_args(interval: u64, reward: u64, time_info_type_hash: Byte32) -> Bytes {
+    let mut args_buf = BytesMut::with_capacity(48);
+    args_buf.put_u64_le(interval);
+    args_buf.put_u64_le(reward);
+    args_buf.put(time_info_type_hash.as_slice());
+    Bytes::from(args_buf.to_vec())
+}
+
+fn build_time_info_cell_data(index: u8, timestamp: u64) -> Bytes {
+    let mut time_buf = BytesMut::with_capacity(9);
+    time_buf.put_u8(index);
+    time_buf.put_u64_le(timestamp);
+    Bytes::from(time_buf.to_vec())
+}
+
+// type script of the time info cells of the time index cell updated by `update_tx`
+fn build_time_info_type_script(env: &mut TimeIndexTestEnv) -> Script {
+    let time_index_type_script = env.build_type_script(env.contract_out_point.as_bytes());
+    env.build_time_info_type_script(&time_index_type_script)
+}
+
+fn build_time_index_lock_script(env: &mut TimeIndexTestEnv, args: Bytes) -> Script {
+    let lock_out_point = env.deploy_binary("time_index_state_lock_script");
+    env.context
+        .build_script(&lock_out_point, args)
+        .expect("script")
+}
+
+// updates a time index cell guarded by `lock_script` from index 0 to 1,
+// paid by a cell of `payer_lock_script` which takes the change
+fn build_update_tx(
+    env: &mut TimeIndexTestEnv,
+    lock_script: Script,
+    payer_lock_script: Script,
+    since: u64,
+    output_capacity: u64,
+) -> TransactionView {
+    let tx = env
+        .update_tx(0, 1)
+        .lock(lock_script)
+        .since(since)
+        .output_capacity(output_capacity)
+        .build();
+    let payer_output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(payer_lock_script)
+        .build();
+    let payer_out_point = env.context.create_cell(payer_output.clone(), Bytes::new());
+    let change_output = payer_output
+        .as_builder()
+        .capacity((2000u64 - output_capacity).pack())
+        .build();
+    let tx = tx
+        .as_advanced_builder()
+        .input(CellInput::new(payer_out_point, 0))
+        .output(change_output)
+        .output_data(Bytes::new().pack())
+        .build();
+    env.context.complete_tx(tx)
+}
+
+// updates a time index cell guarded by the owner lock, paid by a cell of `payer_lock_script`
+fn build_owner_update_tx(
+    env: &mut TimeIndexTestEnv,
+    owner_lock_script: &Script,
+    payer_lock_script: Script,
+) -> TransactionView {
+    let lock_script =
+        build_time_index_lock_script(env, owner_lock_script.calc_script_hash().as_bytes());
+    build_update_tx(env, lock_script, payer_lock_script, 0, 1000)
+}
+
+// updates a permissionless time index cell from index 0 to 1 without its time info cell, paid by an updater cell
+fn build_permissionless_index_update_tx(
+    env: &mut TimeIndexTestEnv,
+    interval: u64,
+    since: u64,
+    output_capacity: u64,
+) -> TransactionView {
+    let time_info_type_hash = build_time_info_type_script(env).calc_script_hash();
+    let args = build_permissionless_args(interval, UPDATE_REWARD, time_info_type_hash);
+    let lock_script = build_time_index_lock_script(env, args);
+    let updater_lock_script = env.build_always_success_script(Bytes::from("updater"));
+    build_update_tx(
+        env,
+        lock_script,
+        updater_lock_script,
+        since,
+        output_capacity,
+    )
+}
+
+// appends the update of the time info cell of `index` from `timestamp` to `timestamp + 1`
+fn add_time_info_update(
+    env: &mut TimeIndexTestEnv,
+    tx: TransactionView,
+    index: u8,
+    timestamp: u64,
+) -> TransactionView {
+    let time_info_output = CellOutput::new_builder()
+        .capacity(500u64.pack())
+        .lock(env.build_always_success_script(Bytes::from("updater")))
+        .type_(Some(build_time_info_type_script(env)).pack())
+        .build();
+    let time_info_out_point = env.context.create_cell(
+        time_info_output.clone(),
+        build_time_info_cell_data(index, timestamp),
+    );
+    let tx = tx
+        .as_advanced_builder()
+        .input(CellInput::new(
+            time_info_out_point,
+            SINCE_TIMESTAMP_FLAG | (timestamp + 1),
+        ))
+        .output(time_info_output)
+        .output_data(build_time_info_cell_data(index, timestamp + 1).pack())
+        .build();
+    env.context.complete_tx(tx)
+}
+
+// updates a permissionless time index cell from index 0 to 1 with its time info cell, paid by an updater cell
+fn build_permissionless_update_tx(
+    env: &mut TimeIndexTestEnv,
+    interval: u64,
+    since: u64,
+    output_capacity: u64,
+) -> TransactionView {
+    let tx = build_permissionless_index_update_tx(env, interval, since, output_capacity);
+    add_time_info_update(env, tx, 1, 0)
+}
+
+fn replace_time_index_output(
+    tx: TransactionView,
+    output: CellOutput,
+    data: Bytes,
+) -> TransactionView {
+    let mut outputs: Vec<CellOutput> = tx.outputs().into_iter().collect();
+    let mut outputs_data: Vec<packed::Bytes> = tx.outputs_data().into_iter().collect();
+    outputs[0] = output;
+    outputs_data[0] = data.pack();
+    tx.as_advanced_builder()
+        .set_outputs(outputs)
+        .set_outputs_data(outputs_data)
+        .build()
+}
+
+#[test]
+fn test_success() {
+    let mut env = TimeIndexTestEnv::new();
+    let owner_lock_script = env.build_always_success_script(Bytes::from("owner"));
+    let tx = build_owner_update_tx(&mut env, &owner_lock_script, owner_lock_script.clone());
+
+    // run
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_error_owner_not_found() {
+    let mut env = TimeIndexTestEnv::new();
+    let owner_lock_script = env.build_always_success_script(Bytes::from("owner"));
+    // the fee cell is not owned by the owner
+    let other_lock_script = env.build_always_success_script(Bytes::from("other"));
+    let tx = build_owner_update_tx(&mut env, &owner_lock_script, other_lock_script);
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_OWNER_NOT_FOUND).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_error_consume_into_other_lock() {
+    let mut env = TimeIndexTestEnv::new();
+    let owner_lock_script = env.build_always_success_script(Bytes::from("owner"));
+    let tx = build_owner_update_tx(&mut env, &owner_lock_script, owner_lock_script.clone());
+    // the next state is valid for the type script, but it is moved to the owner lock
+    let output = tx
+        .output(0)
+        .expect("output")
+        .as_builder()
+        .lock(owner_lock_script)
+        .build();
+    let tx = replace_time_index_output(tx, output, build_time_index_cell_data(1));
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_OUTPUT).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_error_drop_type_script() {
+    let mut env = TimeIndexTestEnv::new();
+    let owner_lock_script = env.build_always_success_script(Bytes::from("owner"));
+    let tx = build_owner_update_tx(&mut env, &owner_lock_script, owner_lock_script.clone());
+    // the cell keeps the lock but is no longer a time index cell
+    let output = tx
+        .output(0)
+        .expect("output")
+        .as_builder()
+        .type_(ScriptOpt::default())
+        .build();
+    let tx = replace_time_index_output(tx, output, Bytes::new());
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_OUTPUT).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_error_invalid_args() {
+    let mut env = TimeIndexTestEnv::new();
+    let owner_lock_script = env.build_always_success_script(Bytes::from("owner"));
+    let lock_script = build_time_index_lock_script(&mut env, Bytes::from("test args"));
+    let tx = build_update_tx(&mut env, lock_script, owner_lock_script, 0, 1000);
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_ARGS).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_success() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL,
+        1000u64 - UPDATE_REWARD,
+    );
+
+    // run
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_permissionless_refill_success() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL,
+        1500u64,
+    );
+
+    // run
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_permissionless_error_interval_not_passed() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | (UPDATE_INTERVAL - 1),
+        1000u64 - UPDATE_REWARD,
+    );
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_SINCE).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_max_since_success() {
+    let mut env = TimeIndexTestEnv::new();
+    // the largest since value in seconds, more than u64::MAX in milliseconds
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | 0x00ff_ffff_ffff_ffff,
+        1000u64 - UPDATE_REWARD,
+    );
+
+    // run
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_permissionless_error_zero_interval() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        0,
+        SINCE_RELATIVE_TIMESTAMP_FLAG,
+        1000u64 - UPDATE_REWARD,
+    );
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_ARGS).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_error_absolute_since() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(&mut env, UPDATE_INTERVAL, 0, 1000u64 - UPDATE_REWARD);
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_SINCE).input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_error_time_info_not_updated() {
+    let mut env = TimeIndexTestEnv::new();
+    // the index moves on, but the time info cell of the new index is left behind
+    let tx = build_permissionless_index_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL,
+        1000u64 - UPDATE_REWARD,
+    );
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_TIME_INFO_OUTPUT)
+            .input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_error_time_info_of_other_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_index_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL,
+        1000u64 - UPDATE_REWARD,
+    );
+    let tx = add_time_info_update(&mut env, tx, 2, 0);
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_TIME_INFO_OUTPUT)
+            .input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_error_two_time_info_cells() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL,
+        1000u64 - UPDATE_REWARD,
+    );
+    let tx = add_time_info_update(&mut env, tx, 1, 0);
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_TIME_INFO_OUTPUT)
+            .input_lock_script(0)
+    );
+}
+
+#[test]
+fn test_permissionless_error_reward_exceeded() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_permissionless_update_tx(
+        &mut env,
+        UPDATE_INTERVAL,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL,
+        1000u64 - UPDATE_REWARD - 1,
+    );
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_LOCK_INVALID_CAPACITY).input_lock_script(0)
+    );
+}
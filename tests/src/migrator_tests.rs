@@ -0,0 +1,343 @@
+use std::{env, fs, path::PathBuf, process};
+
+use ckb_testtool::context::Context;
+use ckb_tool::{
+    ckb_crypto::secp::Generator,
+    ckb_types::{
+        bytes::Bytes,
+        core::{Capacity, EpochNumberWithFraction, HeaderBuilder, HeaderView, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+};
+use migrator::{
+    build_time_info_cell_data, migrate,
+    rpc::{Rpc, TxStatus},
+    Error, Migration, MigrationPlan,
+};
+use tx_builder::{
+    build_args, build_args_with_mode, build_cell_data, build_cell_data_with_epoch,
+    build_time_info_args, ClockMode,
+};
+use updater::signer::Signer;
+
+use super::*;
+
+const INPUT_CAPACITY: u64 = 10_000 * 100_000_000;
+const FEE: u64 = 100_000;
+const FEE_RESERVE: u64 = 1_000 * 100_000_000;
+
+// chain in memory, sent transactions stay in the pool until `commit`
+struct MockChain {
+    context: Context,
+    dead_cells: Vec<OutPoint>,
+    pool: Vec<TransactionView>,
+    committed: Vec<Byte32>,
+    sent_txs: Vec<Byte32>,
+    tip: HeaderView,
+}
+
+impl MockChain {
+    fn new(context: Context) -> Self {
+        MockChain {
+            context,
+            dead_cells: Vec::new(),
+            pool: Vec::new(),
+            committed: Vec::new(),
+            sent_txs: Vec::new(),
+            tip: HeaderBuilder::default().build(),
+        }
+    }
+
+    fn commit(&mut self) {
+        for tx in self.pool.drain(..) {
+            for input in tx.inputs().into_iter() {
+                self.dead_cells.push(input.previous_output());
+            }
+            for (i, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+                self.context.create_cell_with_out_point(
+                    OutPoint::new(tx.hash(), i as u32),
+                    output,
+                    data,
+                );
+            }
+            self.committed.push(tx.hash());
+        }
+    }
+}
+
+impl Rpc for MockChain {
+    fn get_live_cell(&self, out_point: &OutPoint) -> Result<Option<(CellOutput, Bytes)>, Error> {
+        if self.dead_cells.contains(out_point) {
+            return Ok(None);
+        }
+        Ok(self.context.get_cell(out_point))
+    }
+
+    fn get_transaction_status(&self, tx_hash: &Byte32) -> Result<TxStatus, Error> {
+        if self.committed.contains(tx_hash) {
+            Ok(TxStatus::Committed)
+        } else if self.pool.iter().any(|tx| &tx.hash() == tx_hash) {
+            Ok(TxStatus::Pending)
+        } else {
+            Ok(TxStatus::Unknown)
+        }
+    }
+
+    fn get_tip_header(&self) -> Result<HeaderView, Error> {
+        Ok(self.tip.clone())
+    }
+
+    fn send_transaction(&mut self, tx: &TransactionView) -> Result<Byte32, Error> {
+        for input in tx.inputs().into_iter() {
+            if self.get_live_cell(&input.previous_output())?.is_none() {
+                return Err(Error::Rpc("dead input".to_string()));
+            }
+        }
+        self.context.insert_header(self.tip.clone());
+        self.context
+            .verify_tx(tx, MAX_CYCLES)
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        self.pool.push(tx.clone());
+        self.sent_txs.push(tx.hash());
+        Ok(tx.hash())
+    }
+}
+
+// a migration file in a directory removed on drop
+struct TempFile {
+    dir: PathBuf,
+    path: PathBuf,
+}
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("time-index-migrator-{}-{}", name, process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("migration.json");
+        TempFile { dir, path }
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+fn setup(input_capacity: u64) -> (MockChain, MigrationPlan, Signer) {
+    let mut env = TimeIndexTestEnv::new();
+    let input = env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(input_capacity.pack())
+            .lock(env.lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    // the cell dep of the lock of the input
+    let always_success_out_point = env.always_success_out_point.clone();
+    let plan = MigrationPlan {
+        contract: env.contract(),
+        time_info_contract: env.time_info_contract(),
+        input,
+        time_index_lock: None,
+        time_info_lock: None,
+        fee_reserve: FEE_RESERVE,
+        mode: None,
+        fee: FEE,
+        cell_deps: vec![CellDep::new_builder()
+            .out_point(always_success_out_point)
+            .build()],
+    };
+    (
+        MockChain::new(env.context),
+        plan,
+        Signer::new(Generator::random_privkey()),
+    )
+}
+
+#[test]
+fn test_migrate() {
+    let (mut chain, plan, signer) = setup(INPUT_CAPACITY);
+    chain.tip = HeaderBuilder::default()
+        .timestamp(1_600_000_000_123u64.pack())
+        .build();
+    let file = TempFile::new("migrate");
+
+    let (migration, status) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    assert_eq!(status, TxStatus::Pending);
+    assert!(!migration.committed);
+    assert_eq!(Migration::load(&file.path).expect("load"), migration);
+    chain.commit();
+
+    let (migration, status) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    assert_eq!(status, TxStatus::Committed);
+    assert!(migration.committed);
+    assert!(Migration::load(&file.path).expect("load").committed);
+    assert_eq!(chain.sent_txs, vec![migration.tx.hash()]);
+
+    // the time index cell at index 0, the args are the out point of the input
+    let (index_output, index_data) = chain
+        .get_live_cell(&migration.time_index_cell)
+        .expect("rpc")
+        .expect("time index cell");
+    let type_script = index_output.type_().to_opt().expect("type script");
+    assert_eq!(type_script, migration.type_script);
+    assert_eq!(type_script.args().raw_data(), build_args(&plan.input));
+    assert_eq!(index_data, build_cell_data(0, TIME_INDEX_CELL_DATA_N));
+    let occupied = index_output
+        .occupied_capacity(Capacity::bytes(index_data.len()).expect("capacity"))
+        .expect("capacity")
+        .as_u64();
+    let index_capacity: u64 = index_output.capacity().unpack();
+    assert_eq!(index_capacity, occupied + FEE_RESERVE);
+
+    assert_eq!(
+        migration.time_info_cells.len(),
+        TIME_INDEX_CELL_DATA_N as usize
+    );
+    // the time info cells are typed by the time index cell and record the timestamp of the tip
+    let time_info_type_script = plan
+        .time_info_contract
+        .type_script(build_time_info_args(&type_script));
+    for (index, out_point) in migration.time_info_cells.iter().enumerate() {
+        let (output, data) = chain
+            .get_live_cell(out_point)
+            .expect("rpc")
+            .expect("time info cell");
+        assert_eq!(output.type_().to_opt(), Some(time_info_type_script.clone()));
+        assert_eq!(data, build_time_info_cell_data(index as u8, 1_600_000_000));
+    }
+
+    // the change goes back to the input lock, less the fee
+    let total: u64 = migration
+        .tx
+        .outputs()
+        .into_iter()
+        .map(|output| Unpack::<u64>::unpack(&output.capacity()))
+        .sum();
+    assert_eq!(total, INPUT_CAPACITY - FEE);
+}
+
+#[test]
+fn test_migrate_is_idempotent() {
+    let (mut chain, plan, signer) = setup(INPUT_CAPACITY);
+    let file = TempFile::new("idempotent");
+
+    let (first, _) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    // pending, nothing sent again
+    let (migration, status) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    assert_eq!(
+        (migration.tx.hash(), status),
+        (first.tx.hash(), TxStatus::Pending)
+    );
+    assert_eq!(chain.sent_txs.len(), 1);
+
+    // dropped by the node, the same transaction is sent again
+    chain.pool.clear();
+    let (migration, status) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    assert_eq!(
+        (migration.tx.hash(), status),
+        (first.tx.hash(), TxStatus::Pending)
+    );
+    assert_eq!(chain.sent_txs, vec![first.tx.hash(), first.tx.hash()]);
+
+    chain.commit();
+    for _ in 0..2 {
+        let (migration, status) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+        assert_eq!(
+            (migration.tx.hash(), status),
+            (first.tx.hash(), TxStatus::Committed)
+        );
+    }
+    assert_eq!(chain.sent_txs.len(), 2);
+    assert_eq!(chain.committed, vec![first.tx.hash()]);
+}
+
+#[test]
+fn test_migrate_file_of_another_input() {
+    let (mut chain, plan, signer) = setup(INPUT_CAPACITY);
+    let file = TempFile::new("another-input");
+    migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+
+    let (mut other_chain, other_plan, _) = setup(INPUT_CAPACITY);
+    match migrate(&mut other_chain, &signer, &other_plan, &file.path) {
+        Err(Error::Migration(_)) => {}
+        _ => panic!("the migration file records another input"),
+    }
+    assert!(other_chain.sent_txs.is_empty());
+}
+
+#[test]
+fn test_migrate_file_of_another_plan() {
+    let (mut chain, mut plan, signer) = setup(INPUT_CAPACITY);
+    let file = TempFile::new("another-plan");
+    migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    chain.commit();
+
+    // same input, another interval
+    plan.mode = Some(ClockMode::BlockNumber(10));
+    match migrate(&mut chain, &signer, &plan, &file.path) {
+        Err(Error::Migration(_)) => {}
+        _ => panic!("the migration file records another plan"),
+    }
+    assert_eq!(chain.sent_txs.len(), 1);
+}
+
+#[test]
+fn test_migrate_with_spent_input() {
+    let (mut chain, plan, signer) = setup(INPUT_CAPACITY);
+    chain.dead_cells.push(plan.input.clone());
+    let file = TempFile::new("spent-input");
+
+    match migrate(&mut chain, &signer, &plan, &file.path) {
+        Err(Error::Migration(_)) => {}
+        _ => panic!("the input is spent"),
+    }
+    assert!(!file.path.exists());
+}
+
+#[test]
+fn test_migrate_without_enough_capacity() {
+    let (mut chain, plan, signer) = setup(FEE_RESERVE);
+    let file = TempFile::new("capacity");
+
+    match migrate(&mut chain, &signer, &plan, &file.path) {
+        Err(Error::Capacity) => {}
+        _ => panic!("the input can not pay the cells"),
+    }
+    assert!(!file.path.exists());
+}
+
+#[test]
+fn test_migrate_epoch_clock() {
+    let (mut chain, mut plan, signer) = setup(INPUT_CAPACITY);
+    plan.mode = Some(ClockMode::Epoch(1));
+    let epoch = EpochNumberWithFraction::new(7, 100, 1800);
+    chain.tip = HeaderBuilder::default()
+        .number(12_000u64.pack())
+        .epoch(epoch.full_value().pack())
+        .build();
+    let file = TempFile::new("migrate-epoch");
+
+    migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    chain.commit();
+    let (migration, status) = migrate(&mut chain, &signer, &plan, &file.path).expect("migrate");
+    assert_eq!(status, TxStatus::Committed);
+
+    // the clock starts at the epoch of the tip, proven by its header
+    assert_eq!(migration.tx.header_deps().get(0), Some(chain.tip.hash()));
+    let (index_output, index_data) = chain
+        .get_live_cell(&migration.time_index_cell)
+        .expect("rpc")
+        .expect("time index cell");
+    let type_script = index_output.type_().to_opt().expect("type script");
+    assert_eq!(
+        type_script.args().raw_data(),
+        build_args_with_mode(&plan.input, ClockMode::Epoch(1))
+    );
+    assert_eq!(
+        index_data,
+        build_cell_data_with_epoch(0, TIME_INDEX_CELL_DATA_N, epoch.full_value())
+    );
+}
@@ -0,0 +1,268 @@
+use ckb_testtool::context::random_out_point;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use monitor::{
+    source::{OracleSource, TimeIndexState},
+    Error, Metrics, Monitor,
+};
+use tx_builder::{
+    build_args, build_args_with_mode, build_cell_data, build_cell_data_with_epoch, next_cell_data,
+    ClockMode,
+};
+
+use super::TIME_INDEX_CELL_DATA_N;
+
+const SLOT_INTERVAL: u64 = 60;
+const LAST_UPDATE_TIMESTAMP: u64 = 1_600_000_000_000;
+
+// the live time index cell, the tip and the pool in memory
+struct MockSource {
+    state: Option<TimeIndexState>,
+    tip_timestamp: u64,
+    pool: Vec<TransactionView>,
+}
+
+impl OracleSource for MockSource {
+    fn get_time_index_state(&mut self) -> Result<Option<TimeIndexState>, Error> {
+        Ok(self.state.clone())
+    }
+
+    fn get_tip_timestamp(&mut self) -> Result<u64, Error> {
+        Ok(self.tip_timestamp)
+    }
+
+    fn get_pool_transactions(&mut self) -> Result<Vec<TransactionView>, Error> {
+        Ok(self.pool.clone())
+    }
+}
+
+fn type_script() -> Script {
+    Script::new_builder()
+        .code_hash([1u8; 32].pack())
+        .hash_type(ScriptHashType::Type.into())
+        .args(build_args(&random_out_point()).pack())
+        .build()
+}
+
+fn time_index_output(type_script: &Script) -> CellOutput {
+    CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .type_(Some(type_script.clone()).pack())
+        .build()
+}
+
+// a transaction spending `input` into a cell of `type_script` with `data`
+fn update_tx(input: OutPoint, type_script: &Script, data: Bytes) -> TransactionView {
+    TransactionBuilder::default()
+        .input(CellInput::new(input, 0))
+        .output(time_index_output(type_script))
+        .output_data(data.pack())
+        .build()
+}
+
+fn created(tx: &TransactionView) -> (OutPoint, Bytes) {
+    (
+        OutPoint::new(tx.hash(), 0),
+        tx.outputs_data().get(0).expect("data").unpack(),
+    )
+}
+
+fn setup(index: u8) -> (Monitor<MockSource>, Script, TimeIndexState) {
+    let type_script = type_script();
+    let state = TimeIndexState {
+        out_point: random_out_point(),
+        data: build_cell_data(index, TIME_INDEX_CELL_DATA_N),
+        timestamp: LAST_UPDATE_TIMESTAMP,
+    };
+    let source = MockSource {
+        state: Some(state.clone()),
+        tip_timestamp: LAST_UPDATE_TIMESTAMP,
+        pool: Vec::new(),
+    };
+    (
+        Monitor::new(source, type_script.clone(), SLOT_INTERVAL),
+        type_script,
+        state,
+    )
+}
+
+#[test]
+fn test_metrics_before_create() {
+    let mut monitor = Monitor::new(
+        MockSource {
+            state: None,
+            tip_timestamp: LAST_UPDATE_TIMESTAMP,
+            pool: Vec::new(),
+        },
+        type_script(),
+        SLOT_INTERVAL,
+    );
+    let metrics = monitor.poll().expect("poll");
+    assert_eq!(
+        metrics,
+        Metrics {
+            current_index: None,
+            seconds_since_last_update: None,
+            missed_slots: None,
+            invalid_update_attempts: 0,
+        }
+    );
+    assert_eq!(
+        metrics.render(),
+        "# HELP time_index_invalid_update_attempts_total Invalid updates of the time index cell seen in the pool.\n\
+         # TYPE time_index_invalid_update_attempts_total counter\n\
+         time_index_invalid_update_attempts_total 0\n"
+    );
+}
+
+#[test]
+fn test_seconds_since_last_update_and_missed_slots() {
+    let (mut monitor, _, _) = setup(3);
+    let cases = vec![
+        (0, 0, 0),
+        (59_999, 59, 0),
+        (60_000, 60, 0),
+        (119_999, 119, 0),
+        (120_000, 120, 1),
+        (600_500, 600, 9),
+    ];
+    for (elapsed, seconds, missed_slots) in cases {
+        monitor.source_mut().tip_timestamp = LAST_UPDATE_TIMESTAMP + elapsed;
+        let metrics = monitor.poll().expect("poll");
+        assert_eq!(metrics.current_index, Some(3));
+        assert_eq!(metrics.seconds_since_last_update, Some(seconds));
+        assert_eq!(metrics.missed_slots, Some(missed_slots));
+    }
+}
+
+#[test]
+fn test_render() {
+    let metrics = Metrics {
+        current_index: Some(11),
+        seconds_since_last_update: Some(130),
+        missed_slots: Some(1),
+        invalid_update_attempts: 2,
+    };
+    let text = metrics.render();
+    for line in &[
+        "# TYPE time_index_current_index gauge",
+        "time_index_current_index 11",
+        "# TYPE time_index_seconds_since_last_update gauge",
+        "time_index_seconds_since_last_update 130",
+        "# TYPE time_index_missed_slots gauge",
+        "time_index_missed_slots 1",
+        "# TYPE time_index_invalid_update_attempts_total counter",
+        "time_index_invalid_update_attempts_total 2",
+    ] {
+        assert!(text.lines().any(|l| l == *line), "missing {}", line);
+    }
+}
+
+#[test]
+fn test_valid_updates_in_pool() {
+    let (mut monitor, type_script, state) = setup(TIME_INDEX_CELL_DATA_N - 1);
+    // two updates chained in the pool, the second wraps around to index 0
+    let first = update_tx(
+        state.out_point.clone(),
+        &type_script,
+        next_cell_data(&state.data).expect("next"),
+    );
+    let (out_point, data) = created(&first);
+    let second = update_tx(
+        out_point,
+        &type_script,
+        next_cell_data(&data).expect("next"),
+    );
+    monitor.source_mut().pool = vec![
+        second,
+        first,
+        update_tx(random_out_point(), &Script::default(), Bytes::new()),
+    ];
+
+    assert_eq!(monitor.poll().expect("poll").invalid_update_attempts, 0);
+}
+
+#[test]
+fn test_invalid_updates_in_pool() {
+    let (mut monitor, type_script, state) = setup(5);
+    let valid = update_tx(
+        state.out_point.clone(),
+        &type_script,
+        next_cell_data(&state.data).expect("next"),
+    );
+    // skipping an index
+    let skip = update_tx(
+        state.out_point.clone(),
+        &type_script,
+        build_cell_data(7, TIME_INDEX_CELL_DATA_N),
+    );
+    // creating a second cell of the type script
+    let impostor = update_tx(
+        random_out_point(),
+        &type_script,
+        next_cell_data(&state.data).expect("next"),
+    );
+    // destroying the cell
+    let destroy = TransactionBuilder::default()
+        .input(CellInput::new(state.out_point.clone(), 0))
+        .output(CellOutput::new_builder().capacity(1000u64.pack()).build())
+        .output_data(Bytes::new().pack())
+        .build();
+    monitor.source_mut().pool = vec![skip.clone(), valid, impostor.clone()];
+    assert_eq!(monitor.poll().expect("poll").invalid_update_attempts, 2);
+
+    // a transaction seen again is counted once, the counter never goes down
+    monitor.source_mut().pool = vec![skip, destroy];
+    assert_eq!(monitor.poll().expect("poll").invalid_update_attempts, 3);
+    monitor.source_mut().pool = Vec::new();
+    assert_eq!(monitor.poll().expect("poll").invalid_update_attempts, 3);
+}
+
+#[test]
+fn test_epoch_clock_updates_in_pool() {
+    let type_script = type_script()
+        .as_builder()
+        .args(build_args_with_mode(&random_out_point(), ClockMode::Epoch(2)).pack())
+        .build();
+    let epoch = |number| EpochNumberWithFraction::new(number, 0, 1800).full_value();
+    let state = TimeIndexState {
+        out_point: random_out_point(),
+        data: build_cell_data_with_epoch(3, TIME_INDEX_CELL_DATA_N, epoch(7)),
+        timestamp: LAST_UPDATE_TIMESTAMP,
+    };
+    let mut monitor = Monitor::new(
+        MockSource {
+            state: Some(state.clone()),
+            tip_timestamp: LAST_UPDATE_TIMESTAMP,
+            pool: Vec::new(),
+        },
+        type_script.clone(),
+        SLOT_INTERVAL,
+    );
+    // the update records a later epoch, by the interval at least
+    let valid = update_tx(
+        state.out_point.clone(),
+        &type_script,
+        build_cell_data_with_epoch(4, TIME_INDEX_CELL_DATA_N, epoch(9)),
+    );
+    monitor.source_mut().pool = vec![valid];
+    assert_eq!(monitor.poll().expect("poll").invalid_update_attempts, 0);
+
+    // the epoch kept or moved by less than the interval
+    let kept = update_tx(
+        state.out_point.clone(),
+        &type_script,
+        next_cell_data(&state.data).expect("next"),
+    );
+    let early = update_tx(
+        state.out_point.clone(),
+        &type_script,
+        build_cell_data_with_epoch(4, TIME_INDEX_CELL_DATA_N, epoch(8)),
+    );
+    monitor.source_mut().pool = vec![kept, early];
+    assert_eq!(monitor.poll().expect("poll").invalid_update_attempts, 2);
+}
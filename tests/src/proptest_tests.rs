@@ -0,0 +1,320 @@
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{HeaderBuilder, TransactionBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use proptest::prelude::*;
+
+use super::*;
+
+const TIME_INDEX_CELL_DATA_VERSION: u8 = 2;
+const TIME_INDEX_CELL_DATA_LAP_VERSION: u8 = 2;
+// the out point of the create transaction, args of a clock without interval
+const TIME_INDEX_ARGS_LEN: usize = 36;
+const TIME_INDEX_ARGS_WITH_INTERVAL_LEN: usize = 44;
+const CLOCK_METRIC_BLOCK_NUMBER: u8 = 1;
+const CLOCK_METRIC_EPOCH: u8 = 2;
+
+// every case runs the binary in ckb-vm, so keep the number of cases small
+const CONTRACT_CASES: u32 = 32;
+
+// reference model of the index ring, independent of the contract,
+// cell data is `(index, version, lap)`
+fn model_data(data: &[u8]) -> Option<(u8, u8, Option<u64>)> {
+    let (version, lap) = match data.len() {
+        2 => (0, None),
+        3 if data[2] >= 1 && data[2] < TIME_INDEX_CELL_DATA_LAP_VERSION => (data[2], None),
+        11 if data[2] == TIME_INDEX_CELL_DATA_LAP_VERSION => {
+            let mut lap = [0u8; 8];
+            lap.copy_from_slice(&data[3..11]);
+            (data[2], Some(u64::from_le_bytes(lap)))
+        }
+        _ => return None,
+    };
+    if data[1] != TIME_INDEX_CELL_DATA_N || data[0] >= TIME_INDEX_CELL_DATA_N {
+        return None;
+    }
+    Some((data[0], version, lap))
+}
+
+// the clock of the args after the out point, `(interval, metric)` with the metric byte if any,
+// none for args no clock accepts
+fn model_clock(args: &[u8]) -> Option<Option<(u64, Option<u8>)>> {
+    if args.len() == TIME_INDEX_ARGS_LEN {
+        return Some(None);
+    }
+    if args.len() != TIME_INDEX_ARGS_WITH_INTERVAL_LEN
+        && args.len() != TIME_INDEX_ARGS_WITH_INTERVAL_LEN + 1
+    {
+        return None;
+    }
+    let mut interval = [0u8; 8];
+    interval.copy_from_slice(&args[TIME_INDEX_ARGS_LEN..TIME_INDEX_ARGS_WITH_INTERVAL_LEN]);
+    let interval = u64::from_le_bytes(interval);
+    let metric = args.get(TIME_INDEX_ARGS_WITH_INTERVAL_LEN).copied();
+    match metric {
+        _ if interval == 0 => None,
+        None | Some(CLOCK_METRIC_BLOCK_NUMBER) | Some(CLOCK_METRIC_EPOCH) => {
+            Some(Some((interval, metric)))
+        }
+        Some(_) => None,
+    }
+}
+
+fn model_accepts_create(args: &[u8], first_input: &OutPoint, data: &[u8]) -> bool {
+    // an epoch clock needs the epoch of a header dep, the create transaction has none
+    let clock_accepted = match model_clock(args) {
+        Some(Some((_, metric))) => metric != Some(CLOCK_METRIC_EPOCH),
+        Some(None) => true,
+        None => false,
+    };
+    clock_accepted
+        && args[..TIME_INDEX_ARGS_LEN] == *first_input.as_slice()
+        && matches!(model_data(data), Some((0, _, lap)) if lap.unwrap_or(0) == 0)
+}
+
+fn model_accepts_update(args: &[u8], input_data: &[u8], output_data: &[u8]) -> bool {
+    match (model_data(input_data), model_data(output_data)) {
+        (
+            Some((input_index, input_version, input_lap)),
+            Some((output_index, output_version, output_lap)),
+        ) => {
+            let (next_index, next_lap) = if input_index == TIME_INDEX_CELL_DATA_N - 1 {
+                (0, input_lap.unwrap_or(0).checked_add(1))
+            } else {
+                (input_index + 1, Some(input_lap.unwrap_or(0)))
+            };
+            // the update transaction has no since nor header deps, so only a clock without interval moves
+            model_clock(args) == Some(None)
+                && output_index == next_index
+                && output_version >= input_version
+                && (output_lap.is_none() || output_lap == next_lap)
+        }
+        _ => false,
+    }
+}
+
+fn lap_data(index: u8, version: Option<u8>, lap: u64) -> Vec<u8> {
+    let mut data = vec![index, TIME_INDEX_CELL_DATA_N];
+    data.extend(version);
+    if version == Some(TIME_INDEX_CELL_DATA_LAP_VERSION) {
+        data.extend_from_slice(&lap.to_le_bytes());
+    }
+    data
+}
+
+// mostly well formed cell data around the ring, sometimes arbitrary bytes
+fn cell_data() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        3 => (
+            0u8..TIME_INDEX_CELL_DATA_N,
+            prop::option::of(0u8..TIME_INDEX_CELL_DATA_VERSION + 2),
+            prop_oneof![0u64..3, Just(u64::MAX)],
+        )
+            .prop_map(|(index, version, lap)| lap_data(index, version, lap)),
+        1 => (any::<u8>(), prop_oneof![Just(TIME_INDEX_CELL_DATA_N), any::<u8>()])
+            .prop_map(|(index, n)| vec![index, n]),
+        1 => prop::collection::vec(any::<u8>(), 0..5),
+    ]
+}
+
+// the update of the ring from `index`, sometimes to a wrong index
+fn ring_transition() -> impl Strategy<Value = (Vec<u8>, Vec<u8>)> {
+    (
+        0u8..TIME_INDEX_CELL_DATA_N,
+        prop::bool::weighted(0.7),
+        0u8..TIME_INDEX_CELL_DATA_N,
+        0u64..3,
+    )
+        .prop_map(|(index, follow, other, lap)| {
+            let next = if follow {
+                (index + 1) % TIME_INDEX_CELL_DATA_N
+            } else {
+                other
+            };
+            let next_lap = if next == 0 { lap + 1 } else { lap };
+            let version = Some(TIME_INDEX_CELL_DATA_LAP_VERSION);
+            (
+                lap_data(index, version, lap),
+                lap_data(next, version, next_lap),
+            )
+        })
+}
+
+// args after the out point, none, an interval with or without a metric, sometimes malformed
+fn clock_args() -> impl Strategy<Value = Vec<u8>> {
+    prop_oneof![
+        3 => Just(Vec::new()),
+        2 => (
+            prop_oneof![Just(0u64), 1u64..100, Just(u64::MAX)],
+            prop::option::of(0u8..4),
+        )
+            .prop_map(|(interval, metric)| {
+                let mut args = interval.to_le_bytes().to_vec();
+                args.extend(metric);
+                args
+            }),
+        1 => prop::collection::vec(any::<u8>(), 1..10),
+    ]
+}
+
+fn update_args() -> impl Strategy<Value = Vec<u8>> {
+    let out_point = prop_oneof![
+        4 => prop::collection::vec(any::<u8>(), TIME_INDEX_ARGS_LEN),
+        2 => prop::collection::vec(any::<u8>(), 1..40),
+        1 => Just(Vec::new()),
+    ];
+    (out_point, clock_args()).prop_map(|(mut args, clock_args)| {
+        args.extend(clock_args);
+        args
+    })
+}
+
+fn build_output(env: &mut TimeIndexTestEnv, args: &[u8]) -> CellOutput {
+    let type_script = env.build_type_script(Bytes::from(args.to_vec()));
+    CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(env.lock_script.clone())
+        .type_(Some(type_script).pack())
+        .build()
+}
+
+fn verify(env: &mut TimeIndexTestEnv, tx: TransactionView) -> bool {
+    let tx = env.context.complete_tx(tx);
+    env.verify_tx(&tx).is_ok()
+}
+
+// runs the create transaction, the args are the out point of the first input if `args` is none,
+// followed by `clock_args`
+fn run_create(args: Option<Vec<u8>>, clock_args: &[u8], data: &[u8]) -> (bool, bool) {
+    let mut env = TimeIndexTestEnv::new();
+    let input_out_point = env.create_plain_cell();
+    let mut args = args.unwrap_or_else(|| input_out_point.as_slice().to_vec());
+    args.extend_from_slice(clock_args);
+    let output = build_output(&mut env, &args);
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(input_out_point.clone(), 0))
+        .output(output)
+        .output_data(Bytes::from(data.to_vec()).pack())
+        .build();
+    (
+        verify(&mut env, tx),
+        model_accepts_create(&args, &input_out_point, data),
+    )
+}
+
+fn run_update(args: &[u8], input_data: &[u8], output_data: &[u8]) -> (bool, bool) {
+    let mut env = TimeIndexTestEnv::new();
+    let output = build_output(&mut env, args);
+    let input_out_point = env
+        .context
+        .create_cell(output.clone(), Bytes::from(input_data.to_vec()));
+    // the block committing the input is not in header deps, a block number clock can't prove its interval
+    let block = HeaderBuilder::default().build();
+    env.context.insert_header(block.clone());
+    env.context
+        .link_cell_with_block(input_out_point.clone(), block.hash(), 0);
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(input_out_point, 0))
+        .output(output)
+        .output_data(Bytes::from(output_data.to_vec()).pack())
+        .build();
+    (
+        verify(&mut env, tx),
+        model_accepts_update(args, input_data, output_data),
+    )
+}
+
+#[test]
+fn test_model_wraparound() {
+    let args = [1u8; 36];
+    assert!(model_accepts_update(&args, &[11, 12], &[0, 12]));
+    assert!(!model_accepts_update(&args, &[11, 12], &[12, 12]));
+    assert!(model_accepts_update(&args, &[5, 12], &[6, 12, 1]));
+    assert!(!model_accepts_update(&args, &[5, 12, 1], &[6, 12]));
+    let lap = Some(TIME_INDEX_CELL_DATA_LAP_VERSION);
+    assert!(model_accepts_update(
+        &args,
+        &lap_data(11, lap, 3),
+        &lap_data(0, lap, 4)
+    ));
+    assert!(!model_accepts_update(
+        &args,
+        &lap_data(11, lap, 3),
+        &lap_data(0, lap, 3)
+    ));
+    assert!(!model_accepts_update(
+        &args,
+        &lap_data(5, lap, 3),
+        &lap_data(6, lap, 4)
+    ));
+    assert!(model_accepts_update(
+        &args,
+        &[11, 12, 1],
+        &lap_data(0, lap, 1)
+    ));
+    assert!(!model_accepts_update(
+        &args,
+        &lap_data(11, lap, u64::MAX),
+        &lap_data(0, lap, 0)
+    ));
+}
+
+#[test]
+fn test_model_clock() {
+    let out_point = [1u8; 36];
+    let clock = |tail: &[u8]| {
+        let mut args = out_point.to_vec();
+        args.extend_from_slice(tail);
+        model_clock(&args)
+    };
+    let interval = 60u64.to_le_bytes();
+    assert_eq!(clock(&[]), Some(None));
+    assert_eq!(clock(&interval), Some(Some((60, None))));
+    assert_eq!(
+        clock(&[&interval[..], &[CLOCK_METRIC_BLOCK_NUMBER]].concat()),
+        Some(Some((60, Some(CLOCK_METRIC_BLOCK_NUMBER))))
+    );
+    assert_eq!(
+        clock(&[&interval[..], &[CLOCK_METRIC_EPOCH]].concat()),
+        Some(Some((60, Some(CLOCK_METRIC_EPOCH))))
+    );
+    assert_eq!(clock(&[&interval[..], &[0]].concat()), None);
+    assert_eq!(clock(&[&interval[..], &[3]].concat()), None);
+    assert_eq!(clock(&0u64.to_le_bytes()), None);
+    assert_eq!(clock(&interval[..4]), None);
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(CONTRACT_CASES))]
+
+    #[test]
+    fn test_create_matches_model(
+        args in prop::option::of(prop::collection::vec(any::<u8>(), 0..40)),
+        clock_args in clock_args(),
+        data in cell_data(),
+    ) {
+        let (accepted, expected) = run_create(args, &clock_args, &data);
+        prop_assert_eq!(accepted, expected);
+    }
+
+    #[test]
+    fn test_update_matches_model(
+        args in update_args(),
+        input_data in cell_data(),
+        output_data in cell_data(),
+    ) {
+        let (accepted, expected) = run_update(&args, &input_data, &output_data);
+        prop_assert_eq!(accepted, expected);
+    }
+
+    #[test]
+    fn test_ring_transition_matches_model(
+        args in update_args(),
+        (input_data, output_data) in ring_transition(),
+    ) {
+        let (accepted, expected) = run_update(&args, &input_data, &output_data);
+        prop_assert_eq!(accepted, expected);
+    }
+}
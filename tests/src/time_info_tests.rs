@@ -0,0 +1,311 @@
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::{
+        bytes::Bytes,
+        core::{TransactionBuilder, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+};
+use time_index_state_validation::helper::TIME_INFO_TIMESTAMP_TOLERANCE;
+
+use super::*;
+
+// error numbers
+const TIME_INFO_INVALID_ARGS: i8 = 5;
+const TIME_INFO_TIME_INDEX_NOT_FOUND: i8 = 6;
+const TIME_INFO_INVALID_CELL_DATA: i8 = 7;
+const TIME_INFO_INVALID_INDEX: i8 = 8;
+const TIME_INFO_INVALID_TIMESTAMP: i8 = 9;
+const TIME_INFO_INVALID_SINCE: i8 = 10;
+
+// absolute since measured by the median timestamp, in seconds
+const SINCE_TIMESTAMP_FLAG: u64 = 0x4000_0000_0000_0000;
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = 0xc000_0000_0000_0000;
+// median time the update is committed at
+const MEDIAN_TIMESTAMP: u64 = 150;
+
+fn build_time_info_cell_data(index: u8, timestamp: u64) -> Bytes {
+    let mut data = vec![index];
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    Bytes::from(data)
+}
+
+fn time_index_type_script(tx: &TransactionView) -> Script {
+    tx.outputs()
+        .get(0)
+        .and_then(|output| output.type_().to_opt())
+        .expect("type script")
+}
+
+// add time info cells typed by `type_script` to `tx`, the inputs are created in the context with `since`
+fn with_time_info_cells(
+    env: &mut TimeIndexTestEnv,
+    tx: TransactionView,
+    type_script: Script,
+    since: u64,
+    inputs: Vec<Bytes>,
+    outputs: Vec<Bytes>,
+) -> TransactionView {
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(env.lock_script.clone())
+        .type_(Some(type_script).pack())
+        .build();
+    let mut builder = tx.as_advanced_builder();
+    for data in inputs {
+        let out_point = env.context.create_cell(output.clone(), data);
+        builder = builder.input(CellInput::new(out_point, since));
+    }
+    for data in outputs {
+        builder = builder.output(output.clone()).output_data(data.pack());
+    }
+    env.context.complete_tx(builder.build())
+}
+
+// the update of the time index cell from index 0 to 1, with time info cells following it
+fn build_update_tx(
+    env: &mut TimeIndexTestEnv,
+    inputs: Vec<Bytes>,
+    outputs: Vec<Bytes>,
+) -> TransactionView {
+    build_update_tx_with_since(
+        env,
+        SINCE_TIMESTAMP_FLAG | MEDIAN_TIMESTAMP,
+        inputs,
+        outputs,
+    )
+}
+
+fn build_update_tx_with_since(
+    env: &mut TimeIndexTestEnv,
+    since: u64,
+    inputs: Vec<Bytes>,
+    outputs: Vec<Bytes>,
+) -> TransactionView {
+    let tx = env.update_tx(0, 1).build();
+    let type_script = env.build_time_info_type_script(&time_index_type_script(&tx));
+    with_time_info_cells(env, tx, type_script, since, inputs, outputs)
+}
+
+#[test]
+fn test_create() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let type_script = env.build_time_info_type_script(&time_index_type_script(&tx));
+    let outputs = (0..TIME_INDEX_CELL_DATA_N)
+        .map(|index| build_time_info_cell_data(index, 0))
+        .collect();
+    let tx = with_time_info_cells(&mut env, tx, type_script, 0, vec![], outputs);
+
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_error_create_duplicate_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let type_script = env.build_time_info_type_script(&time_index_type_script(&tx));
+    let outputs = vec![
+        build_time_info_cell_data(0, 0),
+        build_time_info_cell_data(0, 0),
+    ];
+    let tx = with_time_info_cells(&mut env, tx, type_script, 0, vec![], outputs);
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_INDEX).output_type_script(1)
+    );
+}
+
+#[test]
+fn test_error_create_without_time_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let time_index_tx = env.create_tx().build();
+    let type_script = env.build_time_info_type_script(&time_index_type_script(&time_index_tx));
+
+    // anyone can lock a cell with the data of a time info cell, but not type it without the time index cell
+    let input_out_point = env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(env.lock_script.clone())
+            .build(),
+        Bytes::new(),
+    );
+    let tx = TransactionBuilder::default()
+        .input(CellInput::new(input_out_point, 0))
+        .build();
+    let tx = with_time_info_cells(
+        &mut env,
+        tx,
+        type_script,
+        0,
+        vec![],
+        vec![build_time_info_cell_data(0, u64::MAX)],
+    );
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_TIME_INDEX_NOT_FOUND).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_invalid_args() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let type_script = env
+        .build_time_info_type_script(&time_index_type_script(&tx))
+        .as_builder()
+        .args(Bytes::from(vec![0u8; 31]).pack())
+        .build();
+    let outputs = vec![build_time_info_cell_data(0, 0)];
+    let tx = with_time_info_cells(&mut env, tx, type_script, 0, vec![], outputs);
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_ARGS).output_type_script(1)
+    );
+}
+
+#[test]
+fn test_error_invalid_cell_data() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.create_tx().build();
+    let type_script = env.build_time_info_type_script(&time_index_type_script(&tx));
+    let outputs = vec![build_time_info_cell_data(TIME_INDEX_CELL_DATA_N, 0)];
+    let tx = with_time_info_cells(&mut env, tx, type_script, 0, vec![], outputs);
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_CELL_DATA).output_type_script(1)
+    );
+}
+
+#[test]
+fn test_update() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx(
+        &mut env,
+        vec![build_time_info_cell_data(1, 100)],
+        vec![build_time_info_cell_data(1, 160)],
+    );
+
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_error_update_other_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx(
+        &mut env,
+        vec![build_time_info_cell_data(2, 100)],
+        vec![build_time_info_cell_data(2, 160)],
+    );
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_INDEX).input_type_script(1)
+    );
+}
+
+#[test]
+fn test_error_update_extra_time_info() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx(
+        &mut env,
+        vec![build_time_info_cell_data(1, 100)],
+        vec![
+            build_time_info_cell_data(1, 160),
+            build_time_info_cell_data(1, 160),
+        ],
+    );
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_INDEX).input_type_script(1)
+    );
+}
+
+#[test]
+fn test_error_update_timestamp_back() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx(
+        &mut env,
+        vec![build_time_info_cell_data(1, 160)],
+        vec![build_time_info_cell_data(1, 100)],
+    );
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_TIMESTAMP).input_type_script(1)
+    );
+}
+
+#[test]
+fn test_update_timestamp_within_tolerance() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = build_update_tx(
+        &mut env,
+        vec![build_time_info_cell_data(1, 100)],
+        vec![build_time_info_cell_data(
+            1,
+            MEDIAN_TIMESTAMP + TIME_INFO_TIMESTAMP_TOLERANCE,
+        )],
+    );
+
+    env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_error_update_timestamp_ahead_of_chain() {
+    let mut env = TimeIndexTestEnv::new();
+    // the timestamp is further ahead of the median time than the tolerance
+    let tx = build_update_tx(
+        &mut env,
+        vec![build_time_info_cell_data(1, 100)],
+        vec![build_time_info_cell_data(
+            1,
+            MEDIAN_TIMESTAMP + TIME_INFO_TIMESTAMP_TOLERANCE + 1,
+        )],
+    );
+
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INFO_INVALID_TIMESTAMP).input_type_script(1)
+    );
+}
+
+#[test]
+fn test_error_update_without_since() {
+    for since in [
+        0,
+        SINCE_RELATIVE_TIMESTAMP_FLAG | MEDIAN_TIMESTAMP,
+        MEDIAN_TIMESTAMP,
+    ] {
+        let mut env = TimeIndexTestEnv::new();
+        let tx = build_update_tx_with_since(
+            &mut env,
+            since,
+            vec![build_time_info_cell_data(1, 100)],
+            vec![build_time_info_cell_data(1, 160)],
+        );
+
+        let err = env.verify_tx(&tx).unwrap_err();
+        assert_error_eq!(
+            err,
+            ScriptError::ValidationFailure(TIME_INFO_INVALID_SINCE).input_type_script(1)
+        );
+    }
+}
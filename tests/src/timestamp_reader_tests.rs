@@ -0,0 +1,270 @@
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::{
+        bytes::Bytes,
+        core::{TransactionBuilder, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+};
+
+use super::*;
+
+// error numbers
+const READER_INVALID_ARGS: i8 = 5;
+const READER_TIME_INDEX_NOT_FOUND: i8 = 6;
+const READER_TIME_INFO_NOT_FOUND: i8 = 7;
+const READER_INVALID_TIMESTAMP: i8 = 8;
+const READER_INVALID_TIME_INDEX: i8 = 9;
+const READER_INVALID_TIME_INFO: i8 = 10;
+
+const TIMESTAMP: u64 = 1_600_000_000;
+
+struct ReaderEnv {
+    env: TimeIndexTestEnv,
+    reader_out_point: OutPoint,
+    time_index_type_hash: Byte32,
+    time_info_type_script: Script,
+    time_index_dep: CellDep,
+    time_info_deps: Vec<CellDep>,
+}
+
+fn build_time_info_cell_data(index: u8, timestamp: u64) -> Bytes {
+    let mut data = vec![index];
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    Bytes::from(data)
+}
+
+// the time index cell at `index` and N time info cells, the one at `index` holds `TIMESTAMP`
+fn setup(index: u8) -> ReaderEnv {
+    let mut env = TimeIndexTestEnv::new();
+    let reader_out_point = env.deploy_binary("timestamp_reader");
+
+    let type_script = env.build_type_script(Bytes::from(vec![1; 36]));
+    let time_index_type_hash = type_script.calc_script_hash();
+    let time_info_type_script = env.build_time_info_type_script(&type_script);
+    let time_index_out_point = env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(env.lock_script.clone())
+            .type_(Some(type_script).pack())
+            .build(),
+        build_time_index_cell_data(index),
+    );
+    let time_info_deps = (0..TIME_INDEX_CELL_DATA_N)
+        .map(|i| {
+            let timestamp = if i == index { TIMESTAMP } else { i as u64 };
+            let out_point = env.context.create_cell(
+                CellOutput::new_builder()
+                    .capacity(1000u64.pack())
+                    .lock(env.lock_script.clone())
+                    .type_(Some(time_info_type_script.clone()).pack())
+                    .build(),
+                build_time_info_cell_data(i, timestamp),
+            );
+            CellDep::new_builder().out_point(out_point).build()
+        })
+        .collect();
+    ReaderEnv {
+        env,
+        reader_out_point,
+        time_index_type_hash,
+        time_info_type_script,
+        time_index_dep: CellDep::new_builder()
+            .out_point(time_index_out_point)
+            .build(),
+        time_info_deps,
+    }
+}
+
+impl ReaderEnv {
+    // reader_args = time_index_type_hash | time_info_type_hash
+    fn args(&self) -> Bytes {
+        let mut args = self.time_index_type_hash.as_bytes().to_vec();
+        args.extend_from_slice(&self.time_info_type_script.calc_script_hash().as_bytes());
+        Bytes::from(args)
+    }
+
+    // a cell dep with `data`, typed by `type_script`
+    fn cell_dep(&mut self, type_script: Option<Script>, data: Bytes) -> CellDep {
+        let out_point = self.env.context.create_cell(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.env.lock_script.clone())
+                .type_(type_script.pack())
+                .build(),
+            data,
+        );
+        CellDep::new_builder().out_point(out_point).build()
+    }
+
+    fn build_tx(
+        &mut self,
+        args: Bytes,
+        timestamp: u64,
+        cell_deps: Vec<CellDep>,
+    ) -> TransactionView {
+        let reader_script = self
+            .env
+            .context
+            .build_script(&self.reader_out_point, args)
+            .expect("script");
+        let input_out_point = self.env.context.create_cell(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.env.lock_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(input_out_point, 0))
+            .output(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(self.env.lock_script.clone())
+                    .type_(Some(reader_script).pack())
+                    .build(),
+            )
+            .output_data(Bytes::from(timestamp.to_le_bytes().to_vec()).pack())
+            .cell_deps(cell_deps)
+            .build();
+        self.env.context.complete_tx(tx)
+    }
+
+    fn all_deps(&self) -> Vec<CellDep> {
+        let mut cell_deps = vec![self.time_index_dep.clone()];
+        cell_deps.extend(self.time_info_deps.iter().cloned());
+        cell_deps
+    }
+}
+
+#[test]
+fn test_success() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    let cell_deps = reader.all_deps();
+    let tx = reader.build_tx(args, TIMESTAMP, cell_deps);
+
+    let cycles = reader.env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_error_invalid_timestamp() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    let cell_deps = reader.all_deps();
+    // the timestamp of another time info cell
+    let tx = reader.build_tx(args, 4, cell_deps);
+
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_INVALID_TIMESTAMP).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_time_index_not_found() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    let cell_deps = reader.time_info_deps.clone();
+    let tx = reader.build_tx(args, TIMESTAMP, cell_deps);
+
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_TIME_INDEX_NOT_FOUND).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_time_info_not_found() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    let mut cell_deps = reader.all_deps();
+    // drop the time info cell at index 3
+    cell_deps.remove(4);
+    let tx = reader.build_tx(args, TIMESTAMP, cell_deps);
+
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_TIME_INFO_NOT_FOUND).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_invalid_args() {
+    let mut reader = setup(3);
+    let cell_deps = reader.all_deps();
+    let tx = reader.build_tx(Bytes::from("test args"), TIMESTAMP, cell_deps);
+
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_INVALID_ARGS).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_skip_forged_time_info() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    // the data of the current time info cell without its type script, attached first
+    let forged_dep = reader.cell_dep(None, build_time_info_cell_data(3, TIMESTAMP + 1));
+    let mut cell_deps = vec![forged_dep];
+    cell_deps.extend(reader.all_deps());
+
+    let tx = reader.build_tx(args.clone(), TIMESTAMP + 1, cell_deps.clone());
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_INVALID_TIMESTAMP).output_type_script(0)
+    );
+    let tx = reader.build_tx(args, TIMESTAMP, cell_deps);
+    reader.env.verify_tx(&tx).expect("pass verification");
+}
+
+#[test]
+fn test_error_ambiguous_time_info() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    let type_script = reader.time_info_type_script.clone();
+    let other_dep = reader.cell_dep(Some(type_script), build_time_info_cell_data(3, TIMESTAMP));
+    let mut cell_deps = reader.all_deps();
+    cell_deps.push(other_dep);
+    let tx = reader.build_tx(args, TIMESTAMP, cell_deps);
+
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_INVALID_TIME_INFO).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_invalid_time_index() {
+    let mut reader = setup(3);
+    let args = reader.args();
+    // a time index cell beyond N
+    let type_script = reader
+        .env
+        .context
+        .get_cell(&reader.time_index_dep.out_point())
+        .and_then(|(output, _)| output.type_().to_opt());
+    let time_index_dep = reader.cell_dep(
+        type_script,
+        Bytes::from(vec![TIME_INDEX_CELL_DATA_N, TIME_INDEX_CELL_DATA_N]),
+    );
+    let mut cell_deps = vec![time_index_dep];
+    cell_deps.extend(reader.time_info_deps.iter().cloned());
+    let tx = reader.build_tx(args, TIMESTAMP, cell_deps);
+
+    let err = reader.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(READER_INVALID_TIME_INDEX).output_type_script(0)
+    );
+}
@@ -0,0 +1,388 @@
+use ckb_testtool::context::random_out_point;
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{EpochNumberWithFraction, HeaderBuilder, TransactionView},
+    packed::*,
+    prelude::*,
+};
+use tx_builder::{
+    build_args, build_args_with_interval, build_args_with_mode, build_cell_data,
+    build_cell_data_with_epoch, build_cell_data_with_lap, build_create_tx,
+    build_create_tx_in_block, build_create_tx_with_mode, build_update_tx, build_update_tx_in_block,
+    cell_data_epoch, clock_mode, ClockMode, Error, TimeIndexCell,
+};
+
+use super::*;
+
+const UPDATE_INTERVAL: u64 = 3600;
+const BLOCK_INTERVAL: u64 = 100;
+// relative since measured by the median timestamp, in seconds
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = 0xc000_0000_0000_0000;
+
+// creates a live time index cell with `data` in the context
+fn create_time_index_cell(env: &mut TimeIndexTestEnv, data: Bytes) -> TimeIndexCell {
+    let output = CellOutput::new_builder()
+        .capacity(1000u64.pack())
+        .lock(env.lock_script.clone())
+        .type_(Some(env.contract().type_script(build_args(&random_out_point()))).pack())
+        .build();
+    let out_point = env.context.create_cell(output.clone(), data.clone());
+    TimeIndexCell {
+        out_point,
+        output,
+        data,
+    }
+}
+
+fn verify(env: &mut TimeIndexTestEnv, tx: TransactionView) {
+    let tx = env.context.complete_tx(tx);
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_build_create_tx() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let lock = env.lock_script.clone();
+    let input_out_point = env.create_plain_cell();
+
+    let tx = build_create_tx(
+        &contract,
+        input_out_point.clone(),
+        lock,
+        TIME_INDEX_CELL_DATA_N,
+    )
+    .expect("create tx");
+
+    let output = tx.output(0).expect("output");
+    let type_script = output.type_().to_opt().expect("type script");
+    assert_eq!(type_script.args().raw_data(), input_out_point.as_bytes());
+    assert_eq!(
+        tx.outputs_data().get(0).expect("data").raw_data(),
+        build_cell_data(0, TIME_INDEX_CELL_DATA_N)
+    );
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_build_create_and_update_tx_with_interval() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let lock = env.lock_script.clone();
+    let input_out_point = env.create_plain_cell();
+
+    let tx = build_create_tx_with_mode(
+        &contract,
+        input_out_point.clone(),
+        lock,
+        TIME_INDEX_CELL_DATA_N,
+        Some(ClockMode::Timestamp(UPDATE_INTERVAL)),
+    )
+    .expect("create tx");
+    let output = tx.output(0).expect("output");
+    let type_script = output.type_().to_opt().expect("type script");
+    assert_eq!(
+        type_script.args().raw_data(),
+        build_args_with_interval(&input_out_point, UPDATE_INTERVAL)
+    );
+    assert_eq!(
+        clock_mode(&type_script),
+        Some(ClockMode::Timestamp(UPDATE_INTERVAL))
+    );
+    let data: Bytes = tx.outputs_data().get(0).expect("data").unpack();
+    let out_point = OutPoint::new(tx.hash(), 0);
+    verify(&mut env, tx);
+
+    // the update waits for the interval of the clock
+    env.context
+        .create_cell_with_out_point(out_point.clone(), output.clone(), data.clone());
+    let current_cell = TimeIndexCell {
+        out_point,
+        output,
+        data,
+    };
+    let tx = build_update_tx(&contract, &current_cell).expect("update tx");
+    let since: u64 = tx.inputs().get(0).expect("input").since().unpack();
+    assert_eq!(since, SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL);
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_error_create_zero_interval() {
+    let env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let lock = env.lock_script.clone();
+    assert_eq!(
+        build_create_tx_with_mode(
+            &contract,
+            random_out_point(),
+            lock.clone(),
+            TIME_INDEX_CELL_DATA_N,
+            Some(ClockMode::Timestamp(0)),
+        )
+        .err(),
+        Some(Error::InvalidInterval)
+    );
+    assert_eq!(
+        build_create_tx_with_mode(
+            &contract,
+            random_out_point(),
+            lock,
+            TIME_INDEX_CELL_DATA_N,
+            Some(ClockMode::BlockNumber(0)),
+        )
+        .err(),
+        Some(Error::InvalidInterval)
+    );
+}
+
+#[test]
+fn test_build_create_and_update_tx_with_block_number_clock() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let lock = env.lock_script.clone();
+    let input_out_point = env.create_plain_cell();
+
+    let mode = ClockMode::BlockNumber(BLOCK_INTERVAL);
+    let tx = build_create_tx_with_mode(
+        &contract,
+        input_out_point.clone(),
+        lock,
+        TIME_INDEX_CELL_DATA_N,
+        Some(mode),
+    )
+    .expect("create tx");
+    let output = tx.output(0).expect("output");
+    let type_script = output.type_().to_opt().expect("type script");
+    assert_eq!(
+        type_script.args().raw_data(),
+        build_args_with_mode(&input_out_point, mode)
+    );
+    assert_eq!(clock_mode(&type_script), Some(mode));
+    let data: Bytes = tx.outputs_data().get(0).expect("data").unpack();
+    let out_point = OutPoint::new(tx.hash(), 0);
+    verify(&mut env, tx);
+
+    // the time index cell is committed in a block known to the update
+    let block = HeaderBuilder::default().number(1000u64.pack()).build();
+    env.context.insert_header(block.clone());
+    env.context
+        .create_cell_with_out_point(out_point.clone(), output.clone(), data.clone());
+    env.context
+        .link_cell_with_block(out_point.clone(), block.hash(), 0);
+    let current_cell = TimeIndexCell {
+        out_point,
+        output,
+        data,
+    };
+    assert_eq!(
+        build_update_tx(&contract, &current_cell).err(),
+        Some(Error::HeaderRequired)
+    );
+    let tx = build_update_tx_in_block(&contract, &current_cell, &block).expect("update tx");
+    let since: u64 = tx.inputs().get(0).expect("input").since().unpack();
+    assert_eq!(since, 1000 + BLOCK_INTERVAL);
+    assert_eq!(tx.header_deps().get(0), Some(block.hash()));
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_build_create_and_update_tx_with_epoch_clock() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let lock = env.lock_script.clone();
+    let input_out_point = env.create_plain_cell();
+
+    let mode = ClockMode::Epoch(1);
+    assert_eq!(
+        build_create_tx_with_mode(
+            &contract,
+            input_out_point.clone(),
+            lock.clone(),
+            TIME_INDEX_CELL_DATA_N,
+            Some(mode),
+        )
+        .err(),
+        Some(Error::HeaderRequired)
+    );
+    // the clock starts at the epoch of the block
+    let epoch = EpochNumberWithFraction::new(5, 10, 1800);
+    let block = HeaderBuilder::default()
+        .epoch(epoch.full_value().pack())
+        .build();
+    env.context.insert_header(block.clone());
+    let tx = build_create_tx_in_block(
+        &contract,
+        input_out_point,
+        lock,
+        TIME_INDEX_CELL_DATA_N,
+        Some(mode),
+        &block,
+    )
+    .expect("create tx");
+    let output = tx.output(0).expect("output");
+    let data: Bytes = tx.outputs_data().get(0).expect("data").unpack();
+    assert_eq!(
+        data,
+        build_cell_data_with_epoch(0, TIME_INDEX_CELL_DATA_N, epoch.full_value())
+    );
+    assert_eq!(tx.header_deps().get(0), Some(block.hash()));
+    let out_point = OutPoint::new(tx.hash(), 0);
+    verify(&mut env, tx);
+
+    // the update records the epoch of a later block
+    env.context
+        .create_cell_with_out_point(out_point.clone(), output.clone(), data.clone());
+    let current_cell = TimeIndexCell {
+        out_point,
+        output,
+        data,
+    };
+    let next_epoch = EpochNumberWithFraction::new(6, 3, 1800);
+    let block = HeaderBuilder::default()
+        .epoch(next_epoch.full_value().pack())
+        .build();
+    env.context.insert_header(block.clone());
+    let tx = build_update_tx_in_block(&contract, &current_cell, &block).expect("update tx");
+    let data = tx.outputs_data().get(0).expect("data").raw_data();
+    assert_eq!(data[0], 1);
+    let recorded = cell_data_epoch(&data).expect("epoch");
+    assert_eq!((recorded.number, recorded.index), (6, 3));
+    assert_eq!(tx.header_deps().get(0), Some(block.hash()));
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_build_update_tx() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let current_cell = create_time_index_cell(&mut env, build_cell_data(0, TIME_INDEX_CELL_DATA_N));
+
+    let tx = build_update_tx(&contract, &current_cell).expect("update tx");
+
+    assert_eq!(tx.output(0).expect("output"), current_cell.output);
+    assert_eq!(
+        tx.outputs_data().get(0).expect("data").raw_data(),
+        build_cell_data(1, TIME_INDEX_CELL_DATA_N)
+    );
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_build_update_tx_wraparound() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let current_cell = create_time_index_cell(
+        &mut env,
+        build_cell_data(TIME_INDEX_CELL_DATA_N - 1, TIME_INDEX_CELL_DATA_N),
+    );
+
+    let tx = build_update_tx(&contract, &current_cell).expect("update tx");
+
+    // the next lap starts
+    assert_eq!(
+        tx.outputs_data().get(0).expect("data").raw_data(),
+        build_cell_data_with_lap(0, TIME_INDEX_CELL_DATA_N, 1)
+    );
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_build_update_tx_keep_legacy_data() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let current_cell =
+        create_time_index_cell(&mut env, Bytes::from(vec![5, TIME_INDEX_CELL_DATA_N]));
+
+    let tx = build_update_tx(&contract, &current_cell).expect("update tx");
+
+    assert_eq!(
+        tx.outputs_data().get(0).expect("data").raw_data(),
+        Bytes::from(vec![6, TIME_INDEX_CELL_DATA_N])
+    );
+    verify(&mut env, tx);
+}
+
+#[test]
+fn test_error_update_invalid_cell_data() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let current_cell = create_time_index_cell(
+        &mut env,
+        Bytes::from(vec![TIME_INDEX_CELL_DATA_N, TIME_INDEX_CELL_DATA_N]),
+    );
+    assert_eq!(
+        build_update_tx(&contract, &current_cell).err(),
+        Some(Error::InvalidCellData)
+    );
+
+    let current_cell =
+        create_time_index_cell(&mut env, Bytes::from(vec![0, TIME_INDEX_CELL_DATA_N, 3]));
+    assert_eq!(
+        build_update_tx(&contract, &current_cell).err(),
+        Some(Error::InvalidDataVersion)
+    );
+}
+
+#[test]
+fn test_error_update_other_type_script() {
+    let env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let lock = env.lock_script.clone();
+    let current_cell = TimeIndexCell {
+        out_point: random_out_point(),
+        output: CellOutput::new_builder()
+            .lock(lock.clone())
+            .type_(Some(lock).pack())
+            .build(),
+        data: build_cell_data(0, TIME_INDEX_CELL_DATA_N),
+    };
+
+    assert_eq!(
+        build_update_tx(&contract, &current_cell).err(),
+        Some(Error::InvalidTypeScript)
+    );
+}
+
+#[test]
+fn test_error_update_since_overflow() {
+    let env = TimeIndexTestEnv::new();
+    let contract = env.contract();
+    let current_cell = |mode| TimeIndexCell {
+        out_point: random_out_point(),
+        output: CellOutput::new_builder()
+            .type_(
+                Some(contract.type_script(build_args_with_mode(&random_out_point(), mode))).pack(),
+            )
+            .build(),
+        data: build_cell_data(0, TIME_INDEX_CELL_DATA_N),
+    };
+
+    // a timestamp interval spilling into the flags of the since
+    assert_eq!(
+        build_update_tx(&contract, &current_cell(ClockMode::Timestamp(1 << 56))).err(),
+        Some(Error::SinceOverflow)
+    );
+    // a block number past u64::MAX or the value of the since
+    let block = HeaderBuilder::default().number(1000u64.pack()).build();
+    for interval in &[u64::MAX, (1 << 56) - 1000] {
+        assert_eq!(
+            build_update_tx_in_block(
+                &contract,
+                &current_cell(ClockMode::BlockNumber(*interval)),
+                &block
+            )
+            .err(),
+            Some(Error::SinceOverflow)
+        );
+    }
+    let tx = build_update_tx_in_block(
+        &contract,
+        &current_cell(ClockMode::BlockNumber((1 << 56) - 1001)),
+        &block,
+    )
+    .expect("update tx");
+    let since: u64 = tx.inputs().get(0).expect("input").since().unpack();
+    assert_eq!(since, (1 << 56) - 1);
+}
@@ -1,350 +1,301 @@
-use ckb_testtool::{builtin::ALWAYS_SUCCESS, context::Context};
 use ckb_tool::{
     ckb_error::assert_error_eq,
     ckb_script::ScriptError,
-    ckb_types::bytes::BufMut,
     ckb_types::{
-        bytes::{Bytes, BytesMut},
-        core::TransactionBuilder,
-        packed::*,
+        bytes::Bytes,
+        core::{EpochNumberWithFraction, HeaderBuilder, HeaderView},
         prelude::*,
     },
 };
+use tx_builder::ClockMode;
 
-use crate::*;
-
-const MAX_CYCLES: u64 = 10_000_000;
-const TIME_INDEX_CELL_DATA_LEN: usize = 2;
-const TIME_INDEX_CELL_DATA_N: u8 = 12;
+use super::*;
 
 // error numbers
 const TIME_INDEX_INVALID_ARGS: i8 = 5;
 const TIME_INDEX_INVALID_INPUT: i8 = 6;
 const TIME_INDEX_INVALID_OUTPUT: i8 = 7;
 const TIME_INDEX_INVALID_CELL_DATA: i8 = 8;
+const TIME_INDEX_INVALID_SINCE: i8 = 10;
+const TIME_INDEX_INVALID_BLOCK_NUMBER: i8 = 11;
+const TIME_INDEX_INVALID_EPOCH: i8 = 12;
+const TIME_INDEX_INVALID_LAP: i8 = 13;
+
+const UPDATE_INTERVAL: u64 = 3600;
+const BLOCK_INTERVAL: u64 = 100;
+const LAST_UPDATE_BLOCK_NUMBER: u64 = 1000;
+const EPOCH_INTERVAL: u64 = 1;
+// relative since measured by the median timestamp, in seconds
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = 0xc000_0000_0000_0000;
+
+// args of a clock waiting `UPDATE_INTERVAL` seconds between two updates
+fn args_with_interval(env: &TimeIndexTestEnv) -> Bytes {
+    tx_builder::build_args_with_interval(&env.contract_out_point, UPDATE_INTERVAL)
+}
 
-fn build_time_index_cell_data(index: u8) -> Bytes {
-    let mut time_buf = BytesMut::with_capacity(TIME_INDEX_CELL_DATA_LEN);
-    time_buf.put_u8(index);
-    time_buf.put_u8(TIME_INDEX_CELL_DATA_N);
-    Bytes::from(time_buf.to_vec())
+// args of a clock waiting `BLOCK_INTERVAL` blocks between two updates
+fn args_with_block_interval(env: &TimeIndexTestEnv) -> Bytes {
+    tx_builder::build_args_with_mode(
+        &env.contract_out_point,
+        ClockMode::BlockNumber(BLOCK_INTERVAL),
+    )
 }
 
-#[test]
-fn test_success() {
-    // deploy contract
-    let mut context = Context::default();
-    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
-    let out_point = context.deploy_cell(contract_bin);
-    let type_script = context
-        .build_script(&out_point, out_point.as_bytes())
-        .expect("script");
-    let type_script_dep = CellDep::new_builder().out_point(out_point).build();
-
-    // deploy always_success script
-    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
-    // prepare lock scripts
-    let lock_script = context
-        .build_script(&always_success_out_point, Default::default())
-        .expect("script");
-    let lock_script_dep = CellDep::new_builder()
-        .out_point(always_success_out_point)
-        .build();
+fn block(number: u64) -> HeaderView {
+    HeaderBuilder::default().number(number.pack()).build()
+}
 
-    let time_index = 0;
+fn block_in_epoch(epoch: EpochNumberWithFraction) -> HeaderView {
+    HeaderBuilder::default()
+        .epoch(epoch.full_value().pack())
+        .build()
+}
 
-    // prepare cells
-    let input_out_point = context.create_cell(
-        CellOutput::new_builder()
-            .capacity(1000u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-        build_time_index_cell_data(time_index),
-    );
+fn epoch_cell_data(index: u8, epoch: EpochNumberWithFraction) -> Bytes {
+    tx_builder::build_cell_data_with_epoch(index, TIME_INDEX_CELL_DATA_N, epoch.full_value())
+}
 
-    let input = CellInput::new_builder()
-        .previous_output(input_out_point)
-        .build();
-    let outputs = vec![CellOutput::new_builder()
-        .capacity(500u64.pack())
-        .lock(lock_script.clone())
-        .type_(Some(type_script.clone()).pack())
-        .build()];
-
-    let outputs_data = vec![build_time_index_cell_data(time_index + 1)];
-    // build transaction
-    let tx = TransactionBuilder::default()
-        .input(input)
-        .outputs(outputs)
-        .outputs_data(outputs_data.pack())
-        .cell_dep(lock_script_dep)
-        .cell_dep(type_script_dep)
-        .build();
-    let tx = context.complete_tx(tx);
+#[test]
+fn test_success() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).build();
 
     // run
-    let cycles = context
-        .verify_tx(&tx, MAX_CYCLES)
-        .expect("pass verification");
+    let cycles = env.verify_tx(&tx).expect("pass verification");
     println!("consume cycles: {}", cycles);
 }
 
 #[test]
 fn test_error_invalid_input() {
-    // deploy contract
-    let mut context = Context::default();
-    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
-    let out_point = context.deploy_cell(contract_bin);
-    let type_script = context
-        .build_script(&out_point, out_point.as_bytes())
-        .expect("script");
-    let type_script_dep = CellDep::new_builder().out_point(out_point).build();
-
-    // deploy always_success script
-    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
-    // prepare lock scripts
-    let lock_script = context
-        .build_script(&always_success_out_point, Default::default())
-        .expect("script");
-    let lock_script_dep = CellDep::new_builder()
-        .out_point(always_success_out_point)
-        .build();
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).input_count(2).build();
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_INPUT).input_type_script(0)
+    );
+}
 
-    let time_index = 0;
+#[test]
+fn test_error_invalid_output() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).output_count(2).build();
 
-    // prepare cells
-    let input_out_point = context.create_cell(
-        CellOutput::new_builder()
-            .capacity(1000u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-        build_time_index_cell_data(time_index),
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_OUTPUT).input_type_script(0)
     );
+}
 
-    let inputs = vec![
-        CellInput::new_builder()
-            .previous_output(input_out_point.clone())
-            .build(),
-        CellInput::new_builder()
-            .previous_output(input_out_point.clone())
-            .build(),
-    ];
-    let outputs = vec![CellOutput::new_builder()
-        .capacity(500u64.pack())
-        .lock(lock_script.clone())
-        .type_(Some(type_script.clone()).pack())
-        .build()];
-
-    let outputs_data = vec![build_time_index_cell_data(time_index + 1)];
-    // build transaction
-    let tx = TransactionBuilder::default()
-        .inputs(inputs)
-        .outputs(outputs)
-        .outputs_data(outputs_data.pack())
-        .cell_dep(lock_script_dep)
-        .cell_dep(type_script_dep)
-        .build();
-    let tx = context.complete_tx(tx);
+#[test]
+fn test_error_invalid_time_index() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 0).build();
 
     // run
-    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let err = env.verify_tx(&tx).unwrap_err();
     assert_error_eq!(
         err,
-        ScriptError::ValidationFailure(TIME_INDEX_INVALID_INPUT).input_type_script(0)
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_CELL_DATA).input_type_script(0)
     );
 }
 
 #[test]
-fn test_error_invalid_output() {
-    // deploy contract
-    let mut context = Context::default();
-    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
-    let out_point = context.deploy_cell(contract_bin);
-    let type_script = context
-        .build_script(&out_point, out_point.as_bytes())
-        .expect("script");
-    let type_script_dep = CellDep::new_builder().out_point(out_point).build();
-
-    // deploy always_success script
-    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
-    // prepare lock scripts
-    let lock_script = context
-        .build_script(&always_success_out_point, Default::default())
-        .expect("script");
-    let lock_script_dep = CellDep::new_builder()
-        .out_point(always_success_out_point)
+fn test_success_with_lap() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env
+        .update_tx(11, 0)
+        .input_data(tx_builder::build_cell_data_with_lap(
+            11,
+            TIME_INDEX_CELL_DATA_N,
+            2,
+        ))
+        .output_data(tx_builder::build_cell_data_with_lap(
+            0,
+            TIME_INDEX_CELL_DATA_N,
+            3,
+        ))
         .build();
+    env.verify_tx(&tx).expect("pass verification");
+}
 
-    let time_index = 0;
+#[test]
+fn test_error_invalid_lap() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env
+        .update_tx(11, 0)
+        .input_data(tx_builder::build_cell_data_with_lap(
+            11,
+            TIME_INDEX_CELL_DATA_N,
+            2,
+        ))
+        .output_data(tx_builder::build_cell_data_with_lap(
+            0,
+            TIME_INDEX_CELL_DATA_N,
+            2,
+        ))
+        .build();
 
-    // prepare cells
-    let input_out_point = context.create_cell(
-        CellOutput::new_builder()
-            .capacity(1000u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-        build_time_index_cell_data(time_index),
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_LAP).input_type_script(0)
     );
+}
 
-    let inputs = vec![CellInput::new_builder()
-        .previous_output(input_out_point.clone())
-        .build()];
-    let outputs = vec![
-        CellOutput::new_builder()
-            .capacity(500u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-        CellOutput::new_builder()
-            .capacity(500u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-    ];
-
-    let outputs_data = vec![
-        build_time_index_cell_data(time_index + 1),
-        build_time_index_cell_data(time_index + 1),
-    ];
-    // build transaction
-    let tx = TransactionBuilder::default()
-        .inputs(inputs)
-        .outputs(outputs)
-        .outputs_data(outputs_data.pack())
-        .cell_dep(lock_script_dep)
-        .cell_dep(type_script_dep)
-        .build();
-    let tx = context.complete_tx(tx);
+#[test]
+fn test_error_empty_args() {
+    let mut env = TimeIndexTestEnv::new();
+    let tx = env.update_tx(0, 1).args(Bytes::default()).build();
 
     // run
-    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let err = env.verify_tx(&tx).unwrap_err();
     assert_error_eq!(
         err,
-        ScriptError::ValidationFailure(TIME_INDEX_INVALID_OUTPUT).input_type_script(0)
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_ARGS).input_type_script(0)
     );
 }
 
 #[test]
-fn test_error_invalid_time_index() {
-    // deploy contract
-    let mut context = Context::default();
-    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
-    let out_point = context.deploy_cell(contract_bin);
-    let type_script = context
-        .build_script(&out_point, out_point.as_bytes())
-        .expect("script");
-    let type_script_dep = CellDep::new_builder().out_point(out_point).build();
-
-    // deploy always_success script
-    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
-    // prepare lock scripts
-    let lock_script = context
-        .build_script(&always_success_out_point, Default::default())
-        .expect("script");
-    let lock_script_dep = CellDep::new_builder()
-        .out_point(always_success_out_point)
-        .build();
+fn test_error_malformed_interval_args() {
+    // a zero interval, an unknown metric and a truncated interval, the cell is updated as often as it likes otherwise
+    let mut env = TimeIndexTestEnv::new();
+    let mut zero_interval = tx_builder::build_args(&env.contract_out_point).to_vec();
+    zero_interval.extend_from_slice(&0u64.to_le_bytes());
+    let mut unknown_metric = args_with_block_interval(&env).to_vec();
+    unknown_metric[44] = 9;
+    let truncated = unknown_metric[..40].to_vec();
+    for args in &[zero_interval, unknown_metric, truncated] {
+        let tx = env.update_tx(0, 1).args(Bytes::from(args.clone())).build();
+
+        // run
+        let err = env.verify_tx(&tx).unwrap_err();
+        assert_error_eq!(
+            err,
+            ScriptError::ValidationFailure(TIME_INDEX_INVALID_ARGS).input_type_script(0)
+        );
+    }
+}
 
-    let time_index = 0;
+#[test]
+fn test_success_with_interval() {
+    let mut env = TimeIndexTestEnv::new();
+    let args = args_with_interval(&env);
+    let tx = env
+        .update_tx(0, 1)
+        .args(args)
+        .since(SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL)
+        .build();
 
-    // prepare cells
-    let input_out_point = context.create_cell(
-        CellOutput::new_builder()
-            .capacity(1000u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-        build_time_index_cell_data(time_index),
-    );
+    // run
+    env.verify_tx(&tx).expect("pass verification");
+}
 
-    let inputs = vec![CellInput::new_builder()
-        .previous_output(input_out_point.clone())
-        .build()];
-    let outputs = vec![CellOutput::new_builder()
-        .capacity(500u64.pack())
-        .lock(lock_script.clone())
-        .type_(Some(type_script.clone()).pack())
-        .build()];
-
-    let outputs_data = vec![build_time_index_cell_data(time_index)];
-    // build transaction
-    let tx = TransactionBuilder::default()
-        .inputs(inputs)
-        .outputs(outputs)
-        .outputs_data(outputs_data.pack())
-        .cell_dep(lock_script_dep)
-        .cell_dep(type_script_dep)
+#[test]
+fn test_error_interval_not_passed() {
+    let mut env = TimeIndexTestEnv::new();
+    let args = args_with_interval(&env);
+    let tx = env
+        .update_tx(0, 1)
+        .args(args)
+        .since(SINCE_RELATIVE_TIMESTAMP_FLAG | (UPDATE_INTERVAL - 1))
         .build();
-    let tx = context.complete_tx(tx);
 
     // run
-    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let err = env.verify_tx(&tx).unwrap_err();
     assert_error_eq!(
         err,
-        ScriptError::ValidationFailure(TIME_INDEX_INVALID_CELL_DATA).input_type_script(0)
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_SINCE).input_type_script(0)
     );
 }
 
 #[test]
-fn test_error_empty_args() {
-    // deploy contract
-    let mut context = Context::default();
-    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
-    let out_point = context.deploy_cell(contract_bin);
-    let type_script = context
-        .build_script(&out_point, Bytes::default())
-        .expect("script");
-    let type_script_dep = CellDep::new_builder().out_point(out_point).build();
-
-    // deploy always_success script
-    let always_success_out_point = context.deploy_cell(ALWAYS_SUCCESS.clone());
-    // prepare lock scripts
-    let lock_script = context
-        .build_script(&always_success_out_point, Default::default())
-        .expect("script");
-    let lock_script_dep = CellDep::new_builder()
-        .out_point(always_success_out_point)
+fn test_success_with_block_interval() {
+    let mut env = TimeIndexTestEnv::new();
+    let args = args_with_block_interval(&env);
+    let tx = env
+        .update_tx(0, 1)
+        .args(args.clone())
+        .input_block(block(LAST_UPDATE_BLOCK_NUMBER))
+        .since(LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL)
         .build();
+    env.verify_tx(&tx).expect("pass verification");
+
+    // a header dep reaching the target proves the interval too
+    let tx = env
+        .update_tx(0, 1)
+        .args(args)
+        .input_block(block(LAST_UPDATE_BLOCK_NUMBER))
+        .header_dep(block(LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL))
+        .build();
+    env.verify_tx(&tx).expect("pass verification");
+}
 
-    let time_index = 0;
+#[test]
+fn test_error_block_interval_not_passed() {
+    let mut env = TimeIndexTestEnv::new();
+    let args = args_with_block_interval(&env);
+    let tx = env
+        .update_tx(0, 1)
+        .args(args)
+        .input_block(block(LAST_UPDATE_BLOCK_NUMBER))
+        .header_dep(block(LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL - 1))
+        .since(LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL - 1)
+        .build();
 
-    // prepare cells
-    let input_out_point = context.create_cell(
-        CellOutput::new_builder()
-            .capacity(1000u64.pack())
-            .lock(lock_script.clone())
-            .type_(Some(type_script.clone()).pack())
-            .build(),
-        build_time_index_cell_data(time_index),
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_BLOCK_NUMBER).input_type_script(0)
     );
+}
+
+#[test]
+fn test_success_with_epoch_interval() {
+    let mut env = TimeIndexTestEnv::new();
+    let args =
+        tx_builder::build_args_with_mode(&env.contract_out_point, ClockMode::Epoch(EPOCH_INTERVAL));
+    let next_epoch = EpochNumberWithFraction::new(6, 0, 1800);
+    let tx = env
+        .update_tx(0, 1)
+        .args(args)
+        .input_data(epoch_cell_data(
+            0,
+            EpochNumberWithFraction::new(5, 10, 1800),
+        ))
+        .output_data(epoch_cell_data(1, next_epoch))
+        .header_dep(block_in_epoch(next_epoch))
+        .build();
+    env.verify_tx(&tx).expect("pass verification");
+}
 
-    let inputs = vec![CellInput::new_builder()
-        .previous_output(input_out_point.clone())
-        .build()];
-    let outputs = vec![CellOutput::new_builder()
-        .capacity(500u64.pack())
-        .lock(lock_script.clone())
-        .type_(Some(type_script.clone()).pack())
-        .build()];
-
-    let outputs_data = vec![build_time_index_cell_data(time_index + 1)];
-    // build transaction
-    let tx = TransactionBuilder::default()
-        .inputs(inputs)
-        .outputs(outputs)
-        .outputs_data(outputs_data.pack())
-        .cell_dep(lock_script_dep)
-        .cell_dep(type_script_dep)
+#[test]
+fn test_error_epoch_interval_not_passed() {
+    let mut env = TimeIndexTestEnv::new();
+    let args =
+        tx_builder::build_args_with_mode(&env.contract_out_point, ClockMode::Epoch(EPOCH_INTERVAL));
+    let next_epoch = EpochNumberWithFraction::new(5, 1799, 1800);
+    let tx = env
+        .update_tx(0, 1)
+        .args(args)
+        .input_data(epoch_cell_data(
+            0,
+            EpochNumberWithFraction::new(5, 10, 1800),
+        ))
+        .output_data(epoch_cell_data(1, next_epoch))
+        .header_dep(block_in_epoch(next_epoch))
         .build();
-    let tx = context.complete_tx(tx);
 
     // run
-    let err = context.verify_tx(&tx, MAX_CYCLES).unwrap_err();
+    let err = env.verify_tx(&tx).unwrap_err();
     assert_error_eq!(
         err,
-        ScriptError::ValidationFailure(TIME_INDEX_INVALID_ARGS).input_type_script(0)
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_EPOCH).input_type_script(0)
     );
 }
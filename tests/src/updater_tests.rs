@@ -0,0 +1,486 @@
+use std::{collections::HashMap, env, fs, path::PathBuf, process};
+
+use ckb_testtool::context::Context;
+use ckb_tool::{
+    ckb_crypto::secp::Generator,
+    ckb_types::bytes::BufMut,
+    ckb_types::{
+        bytes::{Bytes, BytesMut},
+        core::{EpochNumberWithFraction, HeaderBuilder, HeaderView, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+};
+use time_index_state_validation::helper::TIME_INFO_TIMESTAMP_TOLERANCE;
+use tx_builder::{build_args_with_mode, build_cell_data_with_epoch, cell_data_epoch, ClockMode};
+use updater::{
+    rpc::{CellStatus, Rpc},
+    signer::Signer,
+    Error, Updater, UpdaterConfig, UpdaterState,
+};
+
+use super::*;
+
+const SIGNATURE_SIZE: usize = 65;
+const UPDATE_FEE: u64 = 10;
+const START_TIMESTAMP: u64 = 1_600_000_000;
+const UPDATE_INTERVAL: u64 = 60;
+const BLOCK_INTERVAL: u64 = 100;
+const EPOCH_INTERVAL: u64 = 2;
+const EPOCH_LENGTH: u64 = 1800;
+// relative since measured by the median timestamp, in seconds
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = 0xc000_0000_0000_0000;
+// absolute since measured by the median timestamp, in seconds
+const SINCE_TIMESTAMP_FLAG: u64 = 0x4000_0000_0000_0000;
+
+// chain in memory, every transaction sent is verified and committed at once in a block at the tip
+struct MockChain {
+    context: Context,
+    dead_cells: Vec<OutPoint>,
+    timestamp: u64,
+    tip_number: u64,
+    // epoch with fraction of the tip
+    tip_epoch: u64,
+    // header of the block committing each transaction
+    blocks: HashMap<Byte32, HeaderView>,
+    sent_txs: Vec<TransactionView>,
+}
+
+impl MockChain {
+    fn new(context: Context) -> Self {
+        MockChain {
+            context,
+            dead_cells: Vec::new(),
+            timestamp: START_TIMESTAMP,
+            tip_number: 0,
+            tip_epoch: EpochNumberWithFraction::new(0, 0, EPOCH_LENGTH).full_value(),
+            blocks: HashMap::new(),
+            sent_txs: Vec::new(),
+        }
+    }
+
+    // commit the cell in a block at the tip, its header can be loaded by scripts through header deps
+    fn commit_cell(&mut self, out_point: OutPoint) {
+        let header = self.get_tip_header().expect("tip");
+        self.context.insert_header(header.clone());
+        self.context
+            .link_cell_with_block(out_point.clone(), header.hash(), 0);
+        self.blocks.insert(out_point.tx_hash(), header);
+    }
+}
+
+impl Rpc for MockChain {
+    fn get_cell(&self, out_point: &OutPoint) -> Result<CellStatus, Error> {
+        if self.dead_cells.contains(out_point) {
+            return Ok(CellStatus::Dead);
+        }
+        Ok(match self.context.get_cell(out_point) {
+            Some((output, data)) => CellStatus::Live(output, data),
+            None => CellStatus::Unknown,
+        })
+    }
+
+    fn get_tip_header(&self) -> Result<HeaderView, Error> {
+        Ok(HeaderBuilder::default()
+            .number(self.tip_number.pack())
+            .epoch(self.tip_epoch.pack())
+            .timestamp((self.timestamp * 1000).pack())
+            .build())
+    }
+
+    fn get_cell_header(&self, out_point: &OutPoint) -> Result<Option<HeaderView>, Error> {
+        Ok(self.blocks.get(&out_point.tx_hash()).cloned())
+    }
+
+    fn send_transaction(&mut self, tx: &TransactionView) -> Result<Byte32, Error> {
+        for input in tx.inputs().into_iter() {
+            if self.get_live_cell(&input.previous_output())?.is_none() {
+                return Err(Error::Rpc("dead input".to_string()));
+            }
+        }
+        // the tip is known to the chain, so it can be a header dep
+        let tip = self.get_tip_header()?;
+        self.context.insert_header(tip);
+        self.context
+            .verify_tx(tx, MAX_CYCLES)
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        for input in tx.inputs().into_iter() {
+            self.dead_cells.push(input.previous_output());
+        }
+        for (i, (output, data)) in tx.outputs_with_data_iter().enumerate() {
+            let out_point = OutPoint::new(tx.hash(), i as u32);
+            self.context
+                .create_cell_with_out_point(out_point.clone(), output, data);
+            self.commit_cell(out_point);
+        }
+        self.sent_txs.push(tx.clone());
+        Ok(tx.hash())
+    }
+}
+
+fn build_time_info_cell_data(index: u8, timestamp: u64) -> Bytes {
+    let mut time_buf = BytesMut::with_capacity(9);
+    time_buf.put_u8(index);
+    time_buf.put_u64_le(timestamp);
+    Bytes::from(time_buf.to_vec())
+}
+
+#[derive(Default)]
+struct UpdaterSetup {
+    // the time index cell is locked by the owner lock
+    locked_by_owner: bool,
+    // the mode of the clock in the args of the type script
+    mode: Option<ClockMode>,
+    // the file the updater saves its cells to
+    state_file: Option<PathBuf>,
+}
+
+fn build_updater(locked_by_owner: bool) -> Updater<MockChain> {
+    build_updater_with(UpdaterSetup {
+        locked_by_owner,
+        ..Default::default()
+    })
+}
+
+// deploys the contracts and creates a time index cell at index 0 with its time info cells
+fn build_updater_with(setup: UpdaterSetup) -> Updater<MockChain> {
+    let mut env = TimeIndexTestEnv::new();
+    let out_point = env.contract_out_point.clone();
+    let args = match setup.mode {
+        Some(mode) => build_args_with_mode(&out_point, mode),
+        None => out_point.as_bytes(),
+    };
+    let type_script = env.build_type_script(args);
+    let lock_out_point = env.deploy_binary("time_index_state_lock_script");
+    let always_success_out_point = env.always_success_out_point.clone();
+    let updater_lock_script = env.build_always_success_script(Bytes::from("updater"));
+    let time_index_lock_script = if setup.locked_by_owner {
+        env.context
+            .build_script(
+                &lock_out_point,
+                updater_lock_script.calc_script_hash().as_bytes(),
+            )
+            .expect("script")
+    } else {
+        updater_lock_script.clone()
+    };
+
+    // an epoch clock starts at the epoch of the tip
+    let time_index_data = match setup.mode {
+        Some(ClockMode::Epoch(_)) => build_cell_data_with_epoch(
+            0,
+            TIME_INDEX_CELL_DATA_N,
+            EpochNumberWithFraction::new(0, 0, EPOCH_LENGTH).full_value(),
+        ),
+        _ => build_time_index_cell_data(0),
+    };
+    let time_index_cell = env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(time_index_lock_script)
+            .type_(Some(type_script).pack())
+            .build(),
+        time_index_data,
+    );
+    let time_info_cells = (0..TIME_INDEX_CELL_DATA_N)
+        .map(|index| {
+            env.context.create_cell(
+                CellOutput::new_builder()
+                    .capacity(500u64.pack())
+                    .lock(updater_lock_script.clone())
+                    .build(),
+                build_time_info_cell_data(index, 0),
+            )
+        })
+        .collect();
+    let cell_deps = vec![out_point, lock_out_point, always_success_out_point]
+        .into_iter()
+        .map(|out_point| CellDep::new_builder().out_point(out_point).build())
+        .collect();
+
+    let signer = Signer::new(Generator::random_privkey());
+    let mut chain = MockChain::new(env.context);
+    chain.commit_cell(time_index_cell.clone());
+    let config = UpdaterConfig {
+        time_index_cell,
+        time_info_cells,
+        cell_deps,
+        fee: UPDATE_FEE,
+        state_file: setup.state_file,
+    };
+    Updater::new(chain, signer, config)
+}
+
+fn get_live_cell_data(updater: &Updater<MockChain>, out_point: &OutPoint) -> Bytes {
+    let (_, data) = updater
+        .rpc()
+        .get_live_cell(out_point)
+        .expect("rpc")
+        .expect("live cell");
+    data
+}
+
+#[test]
+fn test_update_across_laps() {
+    let mut updater = build_updater(false);
+
+    // 13 updates pass the index 11 and wrap around to 0, then 1
+    for tick in 1..=13u64 {
+        updater.rpc_mut().timestamp = START_TIMESTAMP + tick * 60;
+        let tx_hash = updater.tick().expect("tick").expect("tx hash");
+        let index = (tick % TIME_INDEX_CELL_DATA_N as u64) as u8;
+
+        assert_eq!(
+            updater.time_index_cell(),
+            &OutPoint::new(tx_hash.clone(), 0)
+        );
+        assert_eq!(
+            get_live_cell_data(&updater, updater.time_index_cell()),
+            build_time_index_cell_data(index)
+        );
+        let info_out_point = updater.time_info_cells()[index as usize].clone();
+        assert_eq!(info_out_point, OutPoint::new(tx_hash, 1));
+        assert_eq!(
+            get_live_cell_data(&updater, &info_out_point),
+            build_time_info_cell_data(index, START_TIMESTAMP + tick * 60)
+        );
+    }
+
+    let (output, _) = updater
+        .rpc()
+        .get_live_cell(updater.time_index_cell())
+        .expect("rpc")
+        .expect("live cell");
+    let capacity: u64 = output.capacity().unpack();
+    assert_eq!(capacity, 1000 - 13 * UPDATE_FEE);
+}
+
+#[test]
+fn test_update_locked_by_owner() {
+    let mut updater = build_updater(true);
+
+    for _ in 0..3 {
+        updater.tick().expect("tick").expect("tx hash");
+    }
+    assert_eq!(
+        get_live_cell_data(&updater, updater.time_index_cell()),
+        build_time_index_cell_data(3)
+    );
+
+    // only the time info cell is signed by the updater key
+    let tx = updater.rpc().sent_txs.last().expect("tx").clone();
+    let witness = WitnessArgs::from_slice(&tx.witnesses().get(1).expect("witness").raw_data())
+        .expect("witness args");
+    let lock: Bytes = witness.lock().to_opt().expect("lock").unpack();
+    assert_eq!(lock.len(), SIGNATURE_SIZE);
+    assert!(tx.witnesses().get(0).expect("witness").is_empty());
+}
+
+#[test]
+fn test_sign_whole_group() {
+    let mut updater = build_updater(false);
+    updater.tick().expect("tick").expect("tx hash");
+
+    let tx = updater.rpc().sent_txs.last().expect("tx").clone();
+    assert_eq!(tx.witnesses().len(), 2);
+    let witness = WitnessArgs::from_slice(&tx.witnesses().get(0).expect("witness").raw_data())
+        .expect("witness args");
+    let lock: Bytes = witness.lock().to_opt().expect("lock").unpack();
+    assert_eq!(lock.len(), SIGNATURE_SIZE);
+    assert!(tx.witnesses().get(1).expect("witness").is_empty());
+}
+
+#[test]
+fn test_wait_when_time_index_cell_pending() {
+    let mut updater = build_updater(false);
+    updater.tick().expect("tick").expect("tx hash");
+    // the last update is still in the pool, its outputs are unknown
+    let time_index_cell = updater.time_index_cell().clone();
+    updater.rpc_mut().context.cells.remove(&time_index_cell);
+
+    assert!(updater.tick().expect("tick").is_none());
+    assert_eq!(updater.rpc().sent_txs.len(), 1);
+}
+
+#[test]
+fn test_error_time_index_cell_spent() {
+    let mut updater = build_updater(false);
+    let time_index_cell = updater.time_index_cell().clone();
+    updater.rpc_mut().dead_cells.push(time_index_cell);
+
+    match updater.tick() {
+        Err(Error::CellDead(out_point)) => assert_eq!(&out_point, updater.time_index_cell()),
+        _ => panic!("expect cell dead"),
+    }
+    assert!(updater.rpc().sent_txs.is_empty());
+}
+
+#[test]
+fn test_error_time_info_cell_spent() {
+    let mut updater = build_updater(false);
+    let time_info_cell = updater.time_info_cells()[1].clone();
+    updater.rpc_mut().dead_cells.push(time_info_cell);
+
+    match updater.tick() {
+        Err(Error::CellNotFound(out_point)) => {
+            assert_eq!(&out_point, &updater.time_info_cells()[1])
+        }
+        _ => panic!("expect cell not found"),
+    }
+}
+
+#[test]
+fn test_save_state_after_update() {
+    let dir = env::temp_dir().join(format!("time-index-updater-{}", process::id()));
+    let _ = fs::remove_dir_all(&dir);
+    let path = dir.join("state.toml");
+    let mut updater = build_updater_with(UpdaterSetup {
+        state_file: Some(path.clone()),
+        ..Default::default()
+    });
+    assert_eq!(UpdaterState::load(&path).expect("load"), None);
+    updater.tick().expect("tick").expect("tx hash");
+    updater.rpc_mut().timestamp += UPDATE_INTERVAL;
+    updater.tick().expect("tick").expect("tx hash");
+
+    let state = UpdaterState::load(&path).expect("load").expect("state");
+    let _ = fs::remove_dir_all(&dir);
+    assert_eq!(
+        state.time_index_cell,
+        updater.time_index_cell().clone().into()
+    );
+    let time_info_cells: Vec<OutPoint> =
+        state.time_info_cells.into_iter().map(Into::into).collect();
+    assert_eq!(time_info_cells, updater.time_info_cells());
+}
+
+#[test]
+fn test_update_timestamp_clock() {
+    let mut updater = build_updater_with(UpdaterSetup {
+        mode: Some(ClockMode::Timestamp(UPDATE_INTERVAL)),
+        ..Default::default()
+    });
+    updater.tick().expect("tick").expect("tx hash");
+
+    let tx = updater.rpc().sent_txs.last().expect("tx").clone();
+    let since: u64 = tx.inputs().get(0).expect("input").since().unpack();
+    assert_eq!(since, SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL);
+}
+
+#[test]
+fn test_time_info_since_behind_tip() {
+    let mut updater = build_updater(false);
+    updater.tick().expect("tick").expect("tx hash");
+
+    // the timestamp of the tip is recorded once the median time is within the tolerance
+    let tx = updater.rpc().sent_txs.last().expect("tx").clone();
+    let since: u64 = tx.inputs().get(1).expect("input").since().unpack();
+    assert_eq!(
+        since,
+        SINCE_TIMESTAMP_FLAG | (START_TIMESTAMP - TIME_INFO_TIMESTAMP_TOLERANCE)
+    );
+    let data: Bytes = tx.outputs_data().get(1).expect("data").unpack();
+    assert_eq!(data, build_time_info_cell_data(1, START_TIMESTAMP));
+}
+
+#[test]
+fn test_update_block_number_clock() {
+    let mut updater = build_updater_with(UpdaterSetup {
+        mode: Some(ClockMode::BlockNumber(BLOCK_INTERVAL)),
+        ..Default::default()
+    });
+
+    // wait until the chain is high enough
+    updater.rpc_mut().tip_number = BLOCK_INTERVAL - 1;
+    assert!(updater.tick().expect("tick").is_none());
+
+    for index in 1..3 {
+        updater.rpc_mut().tip_number += BLOCK_INTERVAL;
+        updater.tick().expect("tick").expect("tx hash");
+
+        // the block of the last update is proven by its header, the update can't be committed before the interval
+        let tx = updater.rpc().sent_txs.last().expect("tx").clone();
+        let input = tx.inputs().get(0).expect("input");
+        let block = updater
+            .rpc()
+            .get_cell_header(&input.previous_output())
+            .expect("rpc")
+            .expect("header");
+        assert_eq!(tx.header_deps().get(0), Some(block.hash()));
+        let since: u64 = input.since().unpack();
+        assert_eq!(since, block.number() + BLOCK_INTERVAL);
+        assert_eq!(
+            get_live_cell_data(&updater, updater.time_index_cell()),
+            build_time_index_cell_data(index)
+        );
+    }
+}
+
+#[test]
+fn test_error_block_interval_overflow() {
+    let mut updater = build_updater_with(UpdaterSetup {
+        mode: Some(ClockMode::BlockNumber(u64::MAX)),
+        ..Default::default()
+    });
+    updater.rpc_mut().tip_number = BLOCK_INTERVAL;
+
+    match updater.tick() {
+        Err(Error::Config(_)) => {}
+        _ => panic!("expect config error"),
+    }
+    assert!(updater.rpc().sent_txs.is_empty());
+}
+
+#[test]
+fn test_error_epoch_interval_overflow() {
+    let mut updater = build_updater_with(UpdaterSetup {
+        mode: Some(ClockMode::Epoch(u64::MAX)),
+        ..Default::default()
+    });
+    // the time index cell recorded epoch 1
+    let time_index_cell = updater.time_index_cell().clone();
+    let epoch = EpochNumberWithFraction::new(1, 0, EPOCH_LENGTH).full_value();
+    let cell = updater
+        .rpc_mut()
+        .context
+        .cells
+        .get_mut(&time_index_cell)
+        .expect("cell");
+    cell.1 = build_cell_data_with_epoch(0, TIME_INDEX_CELL_DATA_N, epoch);
+
+    match updater.tick() {
+        Err(Error::Config(_)) => {}
+        _ => panic!("expect config error"),
+    }
+    assert!(updater.rpc().sent_txs.is_empty());
+}
+
+#[test]
+fn test_update_epoch_clock() {
+    let mut updater = build_updater_with(UpdaterSetup {
+        mode: Some(ClockMode::Epoch(EPOCH_INTERVAL)),
+        ..Default::default()
+    });
+
+    // wait until the epoch moved forward by the interval
+    updater.rpc_mut().tip_epoch =
+        EpochNumberWithFraction::new(EPOCH_INTERVAL - 1, 100, EPOCH_LENGTH).full_value();
+    assert!(updater.tick().expect("tick").is_none());
+
+    for index in 1..3 {
+        let tip_epoch =
+            EpochNumberWithFraction::new(EPOCH_INTERVAL * index, 100, EPOCH_LENGTH).full_value();
+        updater.rpc_mut().tip_epoch = tip_epoch;
+        updater.tick().expect("tick").expect("tx hash");
+
+        // the next state records the epoch of the tip, proven by its header
+        let tx = updater.rpc().sent_txs.last().expect("tx").clone();
+        let tip = updater.rpc().get_tip_header().expect("tip");
+        assert_eq!(tx.header_deps().get(0), Some(tip.hash()));
+        let data = get_live_cell_data(&updater, updater.time_index_cell());
+        assert_eq!(data[0], index as u8);
+        let epoch = cell_data_epoch(&data).expect("epoch");
+        assert_eq!(epoch.number, EPOCH_INTERVAL * index);
+        assert_eq!(epoch.index, 100);
+    }
+}
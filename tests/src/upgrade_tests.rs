@@ -0,0 +1,177 @@
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::bytes::BufMut,
+    ckb_types::{
+        bytes::{Bytes, BytesMut},
+        core::{Capacity, TransactionBuilder},
+        packed::*,
+        prelude::*,
+    },
+};
+
+use super::*;
+
+const TIME_INDEX_CELL_DATA_VERSION: u8 = 1;
+// the version with the lap, the latest one known by the contract
+const TIME_INDEX_CELL_DATA_LAP_VERSION: u8 = 2;
+
+// error numbers
+const TIME_INDEX_INVALID_DATA_VERSION: i8 = 9;
+
+fn build_versioned_time_index_cell_data(index: u8, version: u8) -> Bytes {
+    let mut time_buf = BytesMut::from(&build_time_index_cell_data(index)[..]);
+    time_buf.put_u8(version);
+    time_buf.freeze()
+}
+
+// an upgraded binary has a new data hash, trailing bytes are ignored by the ELF loader
+fn build_upgraded_binary(contract_bin: &Bytes) -> Bytes {
+    let mut upgraded_bin = BytesMut::from(&contract_bin[..]);
+    upgraded_bin.put_slice(b"upgraded");
+    upgraded_bin.freeze()
+}
+
+// the code cell is deployed as `deployment.toml` does it, the time index cells reference it by type hash
+#[test]
+fn test_create_with_type_hash() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(contract_bin);
+
+    let tx = env
+        .create_tx()
+        .type_id_contract(type_id_script, code_out_point)
+        .output_data(build_versioned_time_index_cell_data(
+            0,
+            TIME_INDEX_CELL_DATA_VERSION,
+        ))
+        .build();
+
+    // run
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_across_upgraded_binary() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(contract_bin.clone());
+
+    // upgrade the code cell, the type id keeps the script hash of the time index cell unchanged
+    let upgraded_bin = build_upgraded_binary(&contract_bin);
+    let upgraded_code_output = CellOutput::new_builder()
+        .capacity(
+            Capacity::bytes(upgraded_bin.len())
+                .expect("capacity")
+                .pack(),
+        )
+        .lock(env.lock_script.clone())
+        .type_(Some(type_id_script.clone()).pack())
+        .build();
+    let upgrade_tx = TransactionBuilder::default()
+        .input(
+            CellInput::new_builder()
+                .previous_output(code_out_point)
+                .build(),
+        )
+        .output(upgraded_code_output.clone())
+        .output_data(upgraded_bin.pack())
+        .cell_dep(
+            CellDep::new_builder()
+                .out_point(env.always_success_out_point.clone())
+                .build(),
+        )
+        .build();
+    env.verify_tx(&upgrade_tx)
+        .expect("pass upgrade verification");
+    let upgraded_code_out_point = env
+        .context
+        .create_cell(upgraded_code_output, upgraded_bin.clone());
+    assert_ne!(
+        CellOutput::calc_data_hash(&contract_bin),
+        CellOutput::calc_data_hash(&upgraded_bin)
+    );
+
+    // the new binary carries the state written by the old binary, without a version byte,
+    // forward and upgrades its data version
+    let tx = env
+        .update_tx(0, 1)
+        .type_id_contract(type_id_script, upgraded_code_out_point)
+        .output_data(build_versioned_time_index_cell_data(
+            1,
+            TIME_INDEX_CELL_DATA_VERSION,
+        ))
+        .build();
+
+    // run
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_update_keep_legacy_version() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(contract_bin);
+
+    let tx = env
+        .update_tx(TIME_INDEX_CELL_DATA_N - 1, 0)
+        .type_id_contract(type_id_script, code_out_point)
+        .build();
+
+    // run
+    let cycles = env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_error_downgrade_version() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(contract_bin);
+
+    let tx = env
+        .update_tx(0, 1)
+        .type_id_contract(type_id_script, code_out_point)
+        .input_data(build_versioned_time_index_cell_data(
+            0,
+            TIME_INDEX_CELL_DATA_VERSION,
+        ))
+        .build();
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_DATA_VERSION).input_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_unknown_version() {
+    let mut env = TimeIndexTestEnv::new();
+    let contract_bin: Bytes = Loader::default().load_binary("time_index_state_type_script");
+    let (type_id_script, code_out_point) = env.deploy_type_id_cell(contract_bin);
+
+    let tx = env
+        .update_tx(0, 1)
+        .type_id_contract(type_id_script, code_out_point)
+        .input_data(build_versioned_time_index_cell_data(
+            0,
+            TIME_INDEX_CELL_DATA_VERSION,
+        ))
+        .output_data(build_versioned_time_index_cell_data(
+            1,
+            TIME_INDEX_CELL_DATA_LAP_VERSION + 1,
+        ))
+        .build();
+
+    // run
+    let err = env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(TIME_INDEX_INVALID_DATA_VERSION).input_type_script(0)
+    );
+}
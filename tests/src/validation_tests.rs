@@ -0,0 +1,535 @@
+use std::fs;
+
+use time_index_state_validation::{
+    helper::{
+        check_cell_data, CLOCK_METRIC_BLOCK_NUMBER, CLOCK_METRIC_EPOCH, TIME_INDEX_CELL_DATA_N,
+    },
+    verify, CellLoader, Error, Source,
+};
+
+const SCRIPT_HASH: [u8; 32] = [1; 32];
+const OTHER_SCRIPT_HASH: [u8; 32] = [2; 32];
+const INPUT_TX_HASH: [u8; 32] = [3; 32];
+const UPDATE_INTERVAL: u64 = 3600;
+const BLOCK_INTERVAL: u64 = 100;
+const LAST_UPDATE_BLOCK_NUMBER: u64 = 1000;
+const EPOCH_INTERVAL: u64 = 2;
+// relative since measured by the median timestamp, in seconds
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = 0xc000_0000_0000_0000;
+
+struct Cell {
+    type_hash: Option<[u8; 32]>,
+    type_args: Vec<u8>,
+    data: Vec<u8>,
+    // since of an input
+    since: u64,
+    // number of the block committing an input, none if its header is not in header deps
+    block_number: Option<u64>,
+}
+
+// cells of a transaction in memory, the running script is the type script with `SCRIPT_HASH`
+struct MockLoader {
+    script_args: Vec<u8>,
+    inputs: Vec<Cell>,
+    outputs: Vec<Cell>,
+    header_deps: Vec<u64>,
+    header_dep_epochs: Vec<u64>,
+}
+
+impl CellLoader for MockLoader {
+    fn load_script_hash(&self) -> Result<[u8; 32], Error> {
+        Ok(SCRIPT_HASH)
+    }
+
+    fn load_script_args(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.script_args.clone())
+    }
+
+    fn load_cell_type_hashes(&self, source: Source) -> Result<Vec<Option<[u8; 32]>>, Error> {
+        Ok(self
+            .cells(source)
+            .iter()
+            .map(|cell| cell.type_hash)
+            .collect())
+    }
+
+    fn load_cell_type_args(&self, index: usize, source: Source) -> Result<Option<Vec<u8>>, Error> {
+        let cell = self
+            .cells(source)
+            .get(index)
+            .ok_or(Error::IndexOutOfBound)?;
+        Ok(cell.type_hash.map(|_| cell.type_args.clone()))
+    }
+
+    fn load_cell_data(&self, index: usize, source: Source) -> Result<Vec<u8>, Error> {
+        let cell = self
+            .cells(source)
+            .get(index)
+            .ok_or(Error::IndexOutOfBound)?;
+        Ok(cell.data.clone())
+    }
+
+    fn load_input_out_point(&self, index: usize) -> Result<Vec<u8>, Error> {
+        if index >= self.inputs.len() {
+            return Err(Error::IndexOutOfBound);
+        }
+        Ok(input_out_point(index))
+    }
+
+    fn load_input_since(&self, index: usize) -> Result<u64, Error> {
+        let cell = self.inputs.get(index).ok_or(Error::IndexOutOfBound)?;
+        Ok(cell.since)
+    }
+
+    fn load_input_block_number(&self, index: usize) -> Result<u64, Error> {
+        let cell = self.inputs.get(index).ok_or(Error::IndexOutOfBound)?;
+        cell.block_number.ok_or(Error::ItemMissing)
+    }
+
+    fn load_header_dep_block_numbers(&self) -> Result<Vec<u64>, Error> {
+        Ok(self.header_deps.clone())
+    }
+
+    fn load_header_dep_epochs(&self) -> Result<Vec<u64>, Error> {
+        Ok(self.header_dep_epochs.clone())
+    }
+}
+
+impl MockLoader {
+    fn cells(&self, source: Source) -> &Vec<Cell> {
+        match source {
+            Source::Input => &self.inputs,
+            Source::Output => &self.outputs,
+        }
+    }
+
+    // the clock waits `interval` seconds between two updates, the time index cells carry the same args
+    fn with_interval(self, interval: u64) -> Self {
+        let mut args = input_out_point(0);
+        args.extend_from_slice(&interval.to_le_bytes());
+        self.with_args(args)
+    }
+
+    // the clock waits `interval` blocks between two updates
+    fn with_block_interval(self, interval: u64) -> Self {
+        let mut args = input_out_point(0);
+        args.extend_from_slice(&interval.to_le_bytes());
+        args.push(CLOCK_METRIC_BLOCK_NUMBER);
+        self.with_args(args)
+    }
+
+    // the clock waits `interval` epochs between two updates
+    fn with_epoch_interval(self, interval: u64) -> Self {
+        let mut args = input_out_point(0);
+        args.extend_from_slice(&interval.to_le_bytes());
+        args.push(CLOCK_METRIC_EPOCH);
+        self.with_args(args)
+    }
+
+    fn with_args(mut self, args: Vec<u8>) -> Self {
+        self.script_args = args;
+        for cell in self.inputs.iter_mut().chain(self.outputs.iter_mut()) {
+            if cell.type_hash == Some(SCRIPT_HASH) {
+                cell.type_args = self.script_args.clone();
+            }
+        }
+        self
+    }
+}
+
+// epoch with fraction as encoded in headers, `number | index << 24 | length << 40`
+fn epoch(number: u64, index: u64) -> u64 {
+    number | (index << 24) | (1800 << 40)
+}
+
+fn epoch_cell_data(index: u8, epoch: u64) -> Vec<u8> {
+    let mut data = vec![index, TIME_INDEX_CELL_DATA_N, 1];
+    data.extend_from_slice(&epoch.to_le_bytes());
+    data
+}
+
+fn lap_cell_data(index: u8, lap: u64) -> Vec<u8> {
+    let mut data = vec![index, TIME_INDEX_CELL_DATA_N, 2];
+    data.extend_from_slice(&lap.to_le_bytes());
+    data
+}
+
+// out point serialized as `tx_hash | index as u32`
+fn input_out_point(index: usize) -> Vec<u8> {
+    let mut out_point = INPUT_TX_HASH.to_vec();
+    out_point.extend_from_slice(&(index as u32).to_le_bytes());
+    out_point
+}
+
+fn time_index_cell(data: Vec<u8>) -> Cell {
+    Cell {
+        type_hash: Some(SCRIPT_HASH),
+        type_args: input_out_point(0),
+        data,
+        since: 0,
+        block_number: None,
+    }
+}
+
+fn plain_cell() -> Cell {
+    Cell {
+        type_hash: None,
+        type_args: Vec::new(),
+        data: Vec::new(),
+        since: 0,
+        block_number: None,
+    }
+}
+
+fn build_create_loader(outputs: Vec<Cell>) -> MockLoader {
+    MockLoader {
+        script_args: input_out_point(0),
+        inputs: vec![plain_cell()],
+        outputs,
+        header_deps: Vec::new(),
+        header_dep_epochs: Vec::new(),
+    }
+}
+
+fn build_update_loader(input_data: Vec<u8>, output_data: Vec<u8>) -> MockLoader {
+    MockLoader {
+        script_args: input_out_point(0),
+        inputs: vec![plain_cell(), time_index_cell(input_data)],
+        outputs: vec![time_index_cell(output_data), plain_cell()],
+        header_deps: Vec::new(),
+        header_dep_epochs: Vec::new(),
+    }
+}
+
+#[test]
+fn test_check_cell_data() {
+    assert_eq!(check_cell_data(&[0, TIME_INDEX_CELL_DATA_N]), Ok(()));
+    assert_eq!(check_cell_data(&[11, TIME_INDEX_CELL_DATA_N, 1]), Ok(()));
+    assert_eq!(check_cell_data(&[]), Err(Error::InvalidCellData));
+    assert_eq!(
+        check_cell_data(&[12, TIME_INDEX_CELL_DATA_N]),
+        Err(Error::InvalidCellData)
+    );
+    assert_eq!(check_cell_data(&[0, 11]), Err(Error::InvalidCellData));
+    assert_eq!(
+        check_cell_data(&[0, TIME_INDEX_CELL_DATA_N, 0]),
+        Err(Error::InvalidDataVersion)
+    );
+    assert_eq!(
+        check_cell_data(&[0, TIME_INDEX_CELL_DATA_N, 1, 0]),
+        Err(Error::InvalidCellData)
+    );
+    assert_eq!(check_cell_data(&lap_cell_data(11, 3)), Ok(()));
+    assert_eq!(
+        check_cell_data(&[0, TIME_INDEX_CELL_DATA_N, 2]),
+        Err(Error::InvalidCellData)
+    );
+    assert_eq!(
+        check_cell_data(&[0, TIME_INDEX_CELL_DATA_N, 3]),
+        Err(Error::InvalidDataVersion)
+    );
+}
+
+#[test]
+fn test_create() {
+    let loader = build_create_loader(vec![time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N])]);
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_create_error_invalid_args() {
+    let mut loader = build_create_loader(vec![time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N])]);
+    loader.script_args = vec![0; 36];
+    assert_eq!(verify(&loader), Err(Error::InvalidArgument));
+}
+
+#[test]
+fn test_create_with_interval() {
+    let loader = build_create_loader(vec![time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N])])
+        .with_interval(UPDATE_INTERVAL);
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_create_error_zero_interval() {
+    let loader = build_create_loader(vec![time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N])])
+        .with_interval(0);
+    assert_eq!(verify(&loader), Err(Error::InvalidArgument));
+}
+
+#[test]
+fn test_create_error_two_outputs() {
+    let loader = build_create_loader(vec![
+        time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N]),
+        time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N]),
+    ]);
+    assert_eq!(verify(&loader), Err(Error::InvalidTimeIndexOutput));
+}
+
+#[test]
+fn test_create_error_index_not_zero() {
+    let loader = build_create_loader(vec![time_index_cell(vec![1, TIME_INDEX_CELL_DATA_N])]);
+    assert_eq!(verify(&loader), Err(Error::InvalidCellData));
+}
+
+#[test]
+fn test_create_error_lap_not_zero() {
+    let loader = build_create_loader(vec![time_index_cell(lap_cell_data(0, 0))]);
+    assert_eq!(verify(&loader), Ok(()));
+
+    let loader = build_create_loader(vec![time_index_cell(lap_cell_data(0, 1))]);
+    assert_eq!(verify(&loader), Err(Error::InvalidLap));
+}
+
+#[test]
+fn test_update() {
+    let loader = build_update_loader(
+        vec![0, TIME_INDEX_CELL_DATA_N],
+        vec![1, TIME_INDEX_CELL_DATA_N],
+    );
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_update_wraparound() {
+    let loader = build_update_loader(
+        vec![TIME_INDEX_CELL_DATA_N - 1, TIME_INDEX_CELL_DATA_N, 1],
+        vec![0, TIME_INDEX_CELL_DATA_N, 1],
+    );
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_update_with_lap() {
+    let loader = build_update_loader(lap_cell_data(3, 2), lap_cell_data(4, 2));
+    assert_eq!(verify(&loader), Ok(()));
+
+    // the next lap starts at index 0
+    let loader = build_update_loader(
+        lap_cell_data(TIME_INDEX_CELL_DATA_N - 1, 2),
+        lap_cell_data(0, 3),
+    );
+    assert_eq!(verify(&loader), Ok(()));
+
+    // the upgraded cell is at lap 0
+    let loader = build_update_loader(vec![3, TIME_INDEX_CELL_DATA_N, 1], lap_cell_data(4, 0));
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_update_error_invalid_lap() {
+    // the state of the last lap at the next index
+    let loader = build_update_loader(
+        lap_cell_data(TIME_INDEX_CELL_DATA_N - 1, 2),
+        lap_cell_data(0, 2),
+    );
+    assert_eq!(verify(&loader), Err(Error::InvalidLap));
+
+    let loader = build_update_loader(lap_cell_data(3, 2), lap_cell_data(4, 3));
+    assert_eq!(verify(&loader), Err(Error::InvalidLap));
+
+    let loader = build_update_loader(vec![3, TIME_INDEX_CELL_DATA_N, 1], lap_cell_data(4, 1));
+    assert_eq!(verify(&loader), Err(Error::InvalidLap));
+
+    // the lap can't overflow back to 0
+    let loader = build_update_loader(
+        lap_cell_data(TIME_INDEX_CELL_DATA_N - 1, u64::MAX),
+        lap_cell_data(0, 0),
+    );
+    assert_eq!(verify(&loader), Err(Error::InvalidLap));
+}
+
+#[test]
+fn test_update_with_interval() {
+    let mut loader = build_update_loader(
+        vec![0, TIME_INDEX_CELL_DATA_N],
+        vec![1, TIME_INDEX_CELL_DATA_N],
+    )
+    .with_interval(UPDATE_INTERVAL);
+    loader.inputs[1].since = SINCE_RELATIVE_TIMESTAMP_FLAG | UPDATE_INTERVAL;
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_update_error_interval_not_passed() {
+    let mut loader = build_update_loader(
+        vec![0, TIME_INDEX_CELL_DATA_N],
+        vec![1, TIME_INDEX_CELL_DATA_N],
+    )
+    .with_interval(UPDATE_INTERVAL);
+    loader.inputs[1].since = SINCE_RELATIVE_TIMESTAMP_FLAG | (UPDATE_INTERVAL - 1);
+    assert_eq!(verify(&loader), Err(Error::InvalidSince));
+
+    // the interval must be measured from the last update, not from an absolute time
+    loader.inputs[1].since = 0x4000_0000_0000_0000 | 1_600_000_000;
+    assert_eq!(verify(&loader), Err(Error::InvalidSince));
+}
+
+#[test]
+fn test_update_with_block_interval() {
+    let mut loader = build_update_loader(
+        vec![0, TIME_INDEX_CELL_DATA_N],
+        vec![1, TIME_INDEX_CELL_DATA_N],
+    )
+    .with_block_interval(BLOCK_INTERVAL);
+    loader.inputs[1].block_number = Some(LAST_UPDATE_BLOCK_NUMBER);
+    loader.header_deps = vec![LAST_UPDATE_BLOCK_NUMBER];
+
+    // an absolute block number since can't be committed before the target
+    loader.inputs[1].since = LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL;
+    assert_eq!(verify(&loader), Ok(()));
+
+    // or a header dep proves the chain reached it
+    loader.inputs[1].since = 0;
+    loader
+        .header_deps
+        .push(LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL);
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_update_error_block_interval_not_passed() {
+    let mut loader = build_update_loader(
+        vec![0, TIME_INDEX_CELL_DATA_N],
+        vec![1, TIME_INDEX_CELL_DATA_N],
+    )
+    .with_block_interval(BLOCK_INTERVAL);
+    loader.inputs[1].block_number = Some(LAST_UPDATE_BLOCK_NUMBER);
+    loader.header_deps = vec![LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL - 1];
+    loader.inputs[1].since = LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL - 1;
+    assert_eq!(verify(&loader), Err(Error::InvalidBlockNumber));
+
+    // a relative since is measured from the commit of the input, the contract only accepts absolute ones
+    loader.inputs[1].since = 0x8000_0000_0000_0000 | BLOCK_INTERVAL;
+    assert_eq!(verify(&loader), Err(Error::InvalidBlockNumber));
+
+    // the block of the last update must be in header deps
+    loader.inputs[1].block_number = None;
+    loader.inputs[1].since = LAST_UPDATE_BLOCK_NUMBER + BLOCK_INTERVAL;
+    assert_eq!(verify(&loader), Err(Error::ItemMissing));
+}
+
+#[test]
+fn test_create_error_unknown_clock_metric() {
+    let mut args = input_out_point(0);
+    args.extend_from_slice(&BLOCK_INTERVAL.to_le_bytes());
+    args.push(CLOCK_METRIC_EPOCH + 1);
+    let loader =
+        build_create_loader(vec![time_index_cell(vec![0, TIME_INDEX_CELL_DATA_N])]).with_args(args);
+    assert_eq!(verify(&loader), Err(Error::InvalidArgument));
+}
+
+#[test]
+fn test_create_with_epoch_interval() {
+    let mut loader = build_create_loader(vec![time_index_cell(epoch_cell_data(0, epoch(5, 10)))])
+        .with_epoch_interval(EPOCH_INTERVAL);
+    loader.header_dep_epochs = vec![epoch(5, 10)];
+    assert_eq!(verify(&loader), Ok(()));
+
+    // the first epoch must be the one of a header dep
+    loader.header_dep_epochs = vec![epoch(5, 9)];
+    assert_eq!(verify(&loader), Err(Error::InvalidEpoch));
+
+    // and recorded by the cell data
+    loader.outputs[0].data = vec![0, TIME_INDEX_CELL_DATA_N, 1];
+    assert_eq!(verify(&loader), Err(Error::InvalidCellData));
+}
+
+#[test]
+fn test_create_error_epoch_without_epoch_clock() {
+    let mut loader = build_create_loader(vec![time_index_cell(epoch_cell_data(0, epoch(5, 10)))]);
+    loader.header_dep_epochs = vec![epoch(5, 10)];
+    assert_eq!(verify(&loader), Err(Error::InvalidCellData));
+}
+
+#[test]
+fn test_update_with_epoch_interval() {
+    let mut loader = build_update_loader(
+        epoch_cell_data(0, epoch(5, 10)),
+        epoch_cell_data(1, epoch(7, 0)),
+    )
+    .with_epoch_interval(EPOCH_INTERVAL);
+    loader.header_dep_epochs = vec![epoch(7, 0)];
+    assert_eq!(verify(&loader), Ok(()));
+}
+
+#[test]
+fn test_update_error_epoch_interval_not_passed() {
+    let mut loader = build_update_loader(
+        epoch_cell_data(0, epoch(5, 10)),
+        epoch_cell_data(1, epoch(6, 1799)),
+    )
+    .with_epoch_interval(EPOCH_INTERVAL);
+    loader.header_dep_epochs = vec![epoch(6, 1799)];
+    assert_eq!(verify(&loader), Err(Error::InvalidEpoch));
+
+    // the recorded epoch must be reached by a header dep
+    loader.outputs[0].data = epoch_cell_data(1, epoch(7, 0));
+    assert_eq!(verify(&loader), Err(Error::InvalidEpoch));
+
+    // the epoch can't be dropped from the cell data
+    loader.outputs[0].data = vec![1, TIME_INDEX_CELL_DATA_N, 1];
+    assert_eq!(verify(&loader), Err(Error::InvalidCellData));
+}
+
+#[test]
+fn test_update_error_skip_index() {
+    let loader = build_update_loader(
+        vec![3, TIME_INDEX_CELL_DATA_N],
+        vec![5, TIME_INDEX_CELL_DATA_N],
+    );
+    assert_eq!(verify(&loader), Err(Error::InvalidCellData));
+}
+
+#[test]
+fn test_update_error_downgrade_version() {
+    let loader = build_update_loader(
+        vec![3, TIME_INDEX_CELL_DATA_N, 1],
+        vec![4, TIME_INDEX_CELL_DATA_N],
+    );
+    assert_eq!(verify(&loader), Err(Error::InvalidDataVersion));
+}
+
+#[test]
+fn test_update_error_drop_output() {
+    let mut loader = build_update_loader(
+        vec![3, TIME_INDEX_CELL_DATA_N],
+        vec![4, TIME_INDEX_CELL_DATA_N],
+    );
+    loader.outputs[0].type_hash = Some(OTHER_SCRIPT_HASH);
+    assert_eq!(verify(&loader), Err(Error::InvalidTimeIndexOutput));
+}
+
+#[test]
+fn test_update_error_two_inputs() {
+    let mut loader = build_update_loader(
+        vec![3, TIME_INDEX_CELL_DATA_N],
+        vec![4, TIME_INDEX_CELL_DATA_N],
+    );
+    loader
+        .inputs
+        .push(time_index_cell(vec![3, TIME_INDEX_CELL_DATA_N]));
+    assert_eq!(verify(&loader), Err(Error::InvalidTimeIndexInput));
+}
+
+#[test]
+fn test_update_error_empty_args() {
+    let mut loader = build_update_loader(
+        vec![3, TIME_INDEX_CELL_DATA_N],
+        vec![4, TIME_INDEX_CELL_DATA_N],
+    );
+    loader.script_args = Vec::new();
+    assert_eq!(verify(&loader), Err(Error::InvalidArgument));
+}
+
+#[test]
+fn test_cell_data_fuzz_corpus() {
+    // replay the regression corpus of the cell_data fuzz target, no input may panic
+    let corpus = fs::read_dir("../validation/fuzz/corpus/cell_data").expect("corpus");
+    for entry in corpus {
+        let data = fs::read(entry.expect("entry").path()).expect("data");
+        if check_cell_data(&data).is_ok() {
+            assert_eq!(data[1], TIME_INDEX_CELL_DATA_N);
+        }
+    }
+}
@@ -0,0 +1,528 @@
+use ckb_tool::{
+    ckb_error::assert_error_eq,
+    ckb_script::ScriptError,
+    ckb_types::{
+        bytes::Bytes,
+        core::{TransactionBuilder, TransactionView},
+        packed::*,
+        prelude::*,
+    },
+};
+
+use tx_builder::{build_dep_group_data, build_time_cell_deps, build_time_info_since};
+
+use super::*;
+
+// error numbers
+const VAULT_INVALID_ARGS: i8 = 5;
+const VAULT_TIME_INDEX_NOT_FOUND: i8 = 6;
+const VAULT_TIME_INFO_NOT_FOUND: i8 = 7;
+const VAULT_LOCKED: i8 = 8;
+
+const UNLOCK_TIMESTAMP: u64 = 1_600_000_000;
+const UPDATE_INTERVAL: u64 = 60;
+
+struct Cell {
+    out_point: OutPoint,
+    output: CellOutput,
+    data: Bytes,
+}
+
+impl Cell {
+    fn dep(&self) -> CellDep {
+        CellDep::new_builder()
+            .out_point(self.out_point.clone())
+            .build()
+    }
+}
+
+fn build_time_info_cell_data(index: u8, timestamp: u64) -> Bytes {
+    let mut data = vec![index];
+    data.extend_from_slice(&timestamp.to_le_bytes());
+    Bytes::from(data)
+}
+
+// put the output of a verified transaction in the context as a live cell
+fn commit_output(env: &mut TimeIndexTestEnv, tx: &TransactionView, index: usize) -> Cell {
+    let out_point = OutPoint::new(tx.hash(), index as u32);
+    let output = tx.outputs().get(index).expect("output");
+    let data: Bytes = tx.outputs_data().get(index).expect("output data").unpack();
+    env.context
+        .create_cell_with_out_point(out_point.clone(), output.clone(), data.clone());
+    Cell {
+        out_point,
+        output,
+        data,
+    }
+}
+
+// the time index cell and N time info cells, advanced by update transactions
+struct Oracle {
+    time_index_cell: Cell,
+    time_info_cells: Vec<Cell>,
+    time_info_lock: Script,
+    time_info_type_script: Script,
+}
+
+impl Oracle {
+    fn create(env: &mut TimeIndexTestEnv) -> Self {
+        let tx = env.create_tx().build();
+        env.verify_tx(&tx).expect("create time index cell");
+        let time_index_cell = commit_output(env, &tx, 0);
+
+        let time_info_lock = env
+            .lock_script
+            .clone()
+            .as_builder()
+            .args(Bytes::from("oracle").pack())
+            .build();
+        let time_index_type_script = time_index_cell
+            .output
+            .type_()
+            .to_opt()
+            .expect("type script");
+        let time_info_type_script = env.build_time_info_type_script(&time_index_type_script);
+        let time_info_cells = (0..TIME_INDEX_CELL_DATA_N)
+            .map(|index| {
+                let output = CellOutput::new_builder()
+                    .capacity(1000u64.pack())
+                    .lock(time_info_lock.clone())
+                    .type_(Some(time_info_type_script.clone()).pack())
+                    .build();
+                let data = build_time_info_cell_data(index, 0);
+                let out_point = env.context.create_cell(output.clone(), data.clone());
+                Cell {
+                    out_point,
+                    output,
+                    data,
+                }
+            })
+            .collect();
+        Oracle {
+            time_index_cell,
+            time_info_cells,
+            time_info_lock,
+            time_info_type_script,
+        }
+    }
+
+    fn current_index(&self) -> usize {
+        self.time_index_cell.data[0] as usize
+    }
+
+    fn current_time_info_cell(&self) -> &Cell {
+        &self.time_info_cells[self.current_index()]
+    }
+
+    // move the time index cell to the next index, and record `timestamp` in its time info cell
+    fn advance(&mut self, env: &mut TimeIndexTestEnv, timestamp: u64) {
+        let next_index = (self.current_index() + 1) % TIME_INDEX_CELL_DATA_N as usize;
+        let next_index_data = build_time_index_cell_data(next_index as u8);
+        let info_cell = &self.time_info_cells[next_index];
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(self.time_index_cell.out_point.clone(), 0))
+            .input(CellInput::new(
+                info_cell.out_point.clone(),
+                build_time_info_since(timestamp),
+            ))
+            .output(self.time_index_cell.output.clone())
+            .output(info_cell.output.clone())
+            .output_data(next_index_data.pack())
+            .output_data(build_time_info_cell_data(next_index as u8, timestamp).pack())
+            .build();
+        let tx = env.context.complete_tx(tx);
+        env.verify_tx(&tx).expect("update time index cell");
+
+        self.time_index_cell = commit_output(env, &tx, 0);
+        self.time_info_cells[next_index] = commit_output(env, &tx, 1);
+    }
+
+    fn time_index_type_script(&self) -> Script {
+        self.time_index_cell
+            .output
+            .type_()
+            .to_opt()
+            .expect("type script")
+    }
+}
+
+struct VaultEnv {
+    env: TimeIndexTestEnv,
+    oracle: Oracle,
+    vault_out_point: OutPoint,
+}
+
+fn setup() -> VaultEnv {
+    let mut env = TimeIndexTestEnv::new();
+    let vault_out_point = env.deploy_binary("time_locked_vault");
+    let oracle = Oracle::create(&mut env);
+    VaultEnv {
+        env,
+        oracle,
+        vault_out_point,
+    }
+}
+
+impl VaultEnv {
+    // vault_args = time_index_code_hash | time_index_hash_type | time_index_args | time_info_type_hash | unlock_timestamp
+    fn vault_args(&self, unlock_timestamp: u64) -> Bytes {
+        let time_index_type_script = self.oracle.time_index_type_script();
+        let mut args = time_index_type_script.code_hash().as_bytes().to_vec();
+        args.push(time_index_type_script.hash_type().into());
+        args.extend_from_slice(&time_index_type_script.args().raw_data());
+        args.extend_from_slice(
+            &self
+                .oracle
+                .time_info_type_script
+                .calc_script_hash()
+                .as_bytes(),
+        );
+        args.extend_from_slice(&unlock_timestamp.to_le_bytes());
+        Bytes::from(args)
+    }
+
+    fn create_vault_tx(&mut self, args: Bytes) -> TransactionView {
+        let vault_script = self
+            .env
+            .context
+            .build_script(&self.vault_out_point, args)
+            .expect("script");
+        let input_out_point = self.env.context.create_cell(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(self.env.lock_script.clone())
+                .build(),
+            Bytes::new(),
+        );
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(input_out_point, 0))
+            .output(
+                CellOutput::new_builder()
+                    .capacity(1000u64.pack())
+                    .lock(self.env.lock_script.clone())
+                    .type_(Some(vault_script).pack())
+                    .build(),
+            )
+            .output_data(Bytes::new().pack())
+            .build();
+        self.env.context.complete_tx(tx)
+    }
+
+    fn create_vault(&mut self, unlock_timestamp: u64) -> Cell {
+        let tx = self.create_vault_tx(self.vault_args(unlock_timestamp));
+        self.env.verify_tx(&tx).expect("create vault");
+        commit_output(&mut self.env, &tx, 0)
+    }
+
+    fn withdraw_tx(&mut self, vault: &Cell, cell_deps: Vec<CellDep>) -> TransactionView {
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(vault.out_point.clone(), 0))
+            .output(
+                CellOutput::new_builder()
+                    .capacity(1000u64.pack())
+                    .lock(self.env.lock_script.clone())
+                    .build(),
+            )
+            .output_data(Bytes::new().pack())
+            .cell_deps(cell_deps)
+            .build();
+        self.env.context.complete_tx(tx)
+    }
+
+    fn oracle_deps(&self) -> Vec<CellDep> {
+        vec![
+            self.oracle.time_index_cell.dep(),
+            self.oracle.current_time_info_cell().dep(),
+        ]
+    }
+}
+
+#[test]
+fn test_create_vault() {
+    let mut vault_env = setup();
+    let tx = vault_env.create_vault_tx(vault_env.vault_args(UNLOCK_TIMESTAMP));
+
+    let cycles = vault_env.env.verify_tx(&tx).expect("pass verification");
+    println!("consume cycles: {}", cycles);
+}
+
+#[test]
+fn test_error_invalid_args() {
+    let mut vault_env = setup();
+    let tx = vault_env.create_vault_tx(Bytes::from("test args"));
+
+    let err = vault_env.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_INVALID_ARGS).output_type_script(0)
+    );
+}
+
+#[test]
+fn test_withdraw_after_unlock_timestamp() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+
+    // the oracle passes the unlock timestamp and wraps around the ring on the way
+    let start = UNLOCK_TIMESTAMP - UPDATE_INTERVAL * TIME_INDEX_CELL_DATA_N as u64;
+    for step in 0..=TIME_INDEX_CELL_DATA_N as u64 + 2 {
+        let timestamp = start + step * UPDATE_INTERVAL;
+        vault_env.oracle.advance(&mut vault_env.env, timestamp);
+
+        let cell_deps = vault_env.oracle_deps();
+        let tx = vault_env.withdraw_tx(&vault, cell_deps);
+        let result = vault_env.env.verify_tx(&tx);
+        if timestamp < UNLOCK_TIMESTAMP {
+            assert_error_eq!(
+                result.unwrap_err(),
+                ScriptError::ValidationFailure(VAULT_LOCKED).input_type_script(0)
+            );
+        } else {
+            result.expect("pass verification");
+        }
+    }
+}
+
+#[test]
+fn test_error_withdraw_with_stale_time_info() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP - 1);
+
+    // the time info cell of the last index, not the current one
+    let cell_deps = vec![
+        vault_env.oracle.time_index_cell.dep(),
+        vault_env.oracle.time_info_cells[1].dep(),
+    ];
+    let tx = vault_env.withdraw_tx(&vault, cell_deps);
+
+    let err = vault_env.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_TIME_INFO_NOT_FOUND).input_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_withdraw_with_forged_time_info() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP - 1);
+
+    // a cell with the data of a time info cell, but not typed by time_info_type_script
+    let index = vault_env.oracle.current_index() as u8;
+    let forged_out_point = vault_env.env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(vault_env.env.lock_script.clone())
+            .build(),
+        build_time_info_cell_data(index, UNLOCK_TIMESTAMP),
+    );
+    let cell_deps = vec![
+        vault_env.oracle.time_index_cell.dep(),
+        CellDep::new_builder().out_point(forged_out_point).build(),
+    ];
+    let tx = vault_env.withdraw_tx(&vault, cell_deps);
+
+    let err = vault_env.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_TIME_INFO_NOT_FOUND).input_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_withdraw_with_forged_time_info_under_oracle_lock() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP - 1);
+
+    // anyone can create a cell under the lock of the oracle, the forged one is attached before the real one
+    let index = vault_env.oracle.current_index() as u8;
+    let forged_out_point = vault_env.env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(vault_env.oracle.time_info_lock.clone())
+            .build(),
+        build_time_info_cell_data(index, u64::MAX),
+    );
+    let cell_deps = vec![
+        vault_env.oracle.time_index_cell.dep(),
+        CellDep::new_builder().out_point(forged_out_point).build(),
+        vault_env.oracle.current_time_info_cell().dep(),
+    ];
+    let tx = vault_env.withdraw_tx(&vault, cell_deps);
+
+    let err = vault_env.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_LOCKED).input_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_withdraw_without_time_index() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP);
+
+    let cell_deps = vec![vault_env.oracle.current_time_info_cell().dep()];
+    let tx = vault_env.withdraw_tx(&vault, cell_deps);
+
+    let err = vault_env.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_TIME_INDEX_NOT_FOUND).input_type_script(0)
+    );
+}
+
+#[test]
+fn test_error_withdraw_with_impostor_time_index() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP);
+
+    // a cell with the data of the time index cell, typed by the same contract with other args
+    let impostor_type_script = vault_env
+        .oracle
+        .time_index_type_script()
+        .as_builder()
+        .args(Bytes::from(vec![0u8; 36]).pack())
+        .build();
+    let impostor_out_point = vault_env.env.context.create_cell(
+        vault_env
+            .oracle
+            .time_index_cell
+            .output
+            .clone()
+            .as_builder()
+            .type_(Some(impostor_type_script).pack())
+            .build(),
+        vault_env.oracle.time_index_cell.data.clone(),
+    );
+    let cell_deps = vec![
+        CellDep::new_builder().out_point(impostor_out_point).build(),
+        vault_env.oracle.current_time_info_cell().dep(),
+    ];
+    let tx = vault_env.withdraw_tx(&vault, cell_deps);
+
+    let err = vault_env.env.verify_tx(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_TIME_INDEX_NOT_FOUND).input_type_script(0)
+    );
+}
+
+// builds a dep group cell listing `out_points`
+fn create_dep_group(vault_env: &mut VaultEnv, out_points: &[OutPoint]) -> OutPoint {
+    let output = CellOutput::new_builder()
+        .capacity(100_000_000_000u64.pack())
+        .lock(vault_env.env.lock_script.clone())
+        .build();
+    vault_env
+        .env
+        .context
+        .create_cell(output, build_dep_group_data(out_points))
+}
+
+// withdraws the vault to the lock of the test env with `cell_deps`
+fn build_withdraw_tx(
+    vault_env: &VaultEnv,
+    vault: &Cell,
+    cell_deps: Vec<CellDep>,
+) -> TransactionView {
+    TransactionBuilder::default()
+        .input(CellInput::new(vault.out_point.clone(), 0))
+        .output(
+            CellOutput::new_builder()
+                .capacity(1000u64.pack())
+                .lock(vault_env.env.lock_script.clone())
+                .build(),
+        )
+        .output_data(Bytes::new().pack())
+        .cell_deps(cell_deps)
+        .build()
+}
+
+#[test]
+fn test_withdraw_with_code_dep_group() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+
+    // the dep group lists the immutable code cells only, it is published once and outlives the updates
+    let code_cells = vec![
+        vault_env.vault_out_point.clone(),
+        vault_env.env.always_success_out_point.clone(),
+    ];
+    let dep_group = create_dep_group(&mut vault_env, &code_cells);
+
+    for timestamp in [UNLOCK_TIMESTAMP - 1, UNLOCK_TIMESTAMP] {
+        vault_env.oracle.advance(&mut vault_env.env, timestamp);
+        let cell_deps = build_time_cell_deps(
+            dep_group.clone(),
+            vault_env.oracle.time_index_cell.out_point.clone(),
+            vault_env.oracle.current_time_info_cell().out_point.clone(),
+        );
+        let tx = build_withdraw_tx(&vault_env, &vault, cell_deps);
+        let result = vault_env.env.verify_tx_with_dep_groups(&tx);
+        if timestamp < UNLOCK_TIMESTAMP {
+            assert_error_eq!(
+                result.unwrap_err(),
+                ScriptError::ValidationFailure(VAULT_LOCKED).input_type_script(0)
+            );
+        } else {
+            result.expect("pass verification");
+        }
+    }
+}
+
+#[test]
+fn test_error_withdraw_with_forged_dep_group_member() {
+    let mut vault_env = setup();
+    let vault = vault_env.create_vault(UNLOCK_TIMESTAMP);
+    vault_env
+        .oracle
+        .advance(&mut vault_env.env, UNLOCK_TIMESTAMP - 1);
+
+    // anyone can publish a dep group, this one lists a forged time info cell under the lock of the oracle
+    // ahead of the real one
+    let index = vault_env.oracle.current_index() as u8;
+    let forged_out_point = vault_env.env.context.create_cell(
+        CellOutput::new_builder()
+            .capacity(1000u64.pack())
+            .lock(vault_env.oracle.time_info_lock.clone())
+            .build(),
+        build_time_info_cell_data(index, u64::MAX),
+    );
+    let members = vec![
+        vault_env.vault_out_point.clone(),
+        vault_env.env.always_success_out_point.clone(),
+        forged_out_point,
+    ];
+    let dep_group = create_dep_group(&mut vault_env, &members);
+    let cell_deps = build_time_cell_deps(
+        dep_group,
+        vault_env.oracle.time_index_cell.out_point.clone(),
+        vault_env.oracle.current_time_info_cell().out_point.clone(),
+    );
+    let tx = build_withdraw_tx(&vault_env, &vault, cell_deps);
+
+    let err = vault_env.env.verify_tx_with_dep_groups(&tx).unwrap_err();
+    assert_error_eq!(
+        err,
+        ScriptError::ValidationFailure(VAULT_LOCKED).input_type_script(0)
+    );
+}
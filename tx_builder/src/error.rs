@@ -0,0 +1,33 @@
+use std::fmt;
+
+/// Error
+#[derive(Debug, PartialEq)]
+pub enum Error {
+    InvalidCellData,
+    InvalidDataVersion,
+    InvalidTypeScript,
+    InvalidInterval,
+    HeaderRequired,
+    Capacity,
+    SinceOverflow,
+    InvalidHex(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::InvalidCellData => write!(f, "invalid time index cell data"),
+            Self::InvalidDataVersion => write!(f, "unknown time index cell data version"),
+            Self::InvalidTypeScript => write!(f, "cell is not a time index cell of the contract"),
+            Self::InvalidInterval => write!(f, "update interval should be more than 0"),
+            Self::HeaderRequired => {
+                write!(f, "block number or epoch clock needs a header of the chain")
+            }
+            Self::Capacity => write!(f, "capacity overflow"),
+            Self::SinceOverflow => write!(f, "update interval doesn't fit the since of the input"),
+            Self::InvalidHex(err) => write!(f, "hex error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,387 @@
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::{Capacity, DepType, HeaderView, ScriptHashType, TransactionBuilder, TransactionView},
+    packed::{Byte32, CellDep, CellInput, CellOutput, OutPoint, OutPointVec, Script},
+    prelude::*,
+};
+use time_index_state_validation::helper::{
+    get_cell_data_epoch, get_cell_data_lap, get_clock_mode, CLOCK_METRIC_BLOCK_NUMBER,
+    CLOCK_METRIC_EPOCH, SINCE_METRIC_TIMESTAMP, SINCE_RELATIVE_FLAG,
+    TIME_INDEX_CELL_DATA_VERSIONED_LEN, TIME_INDEX_CELL_DATA_WITH_LAP_LEN,
+    TIME_INFO_TIMESTAMP_TOLERANCE,
+};
+
+mod error;
+
+pub use error::Error;
+pub use time_index_state_validation::helper::{
+    ClockMode, Epoch, SINCE_VALUE_MASK, TIME_INDEX_CELL_DATA_N, TIME_INDEX_CELL_DATA_VERSION,
+};
+
+// relative since measured by the median timestamp, in seconds
+const SINCE_RELATIVE_TIMESTAMP_FLAG: u64 = SINCE_RELATIVE_FLAG | SINCE_METRIC_TIMESTAMP;
+
+/// A deployed contract, time_index_state_type_script or time_info_type_script
+pub struct Contract {
+    pub code_hash: Byte32,
+    pub hash_type: ScriptHashType,
+    pub cell_dep: CellDep,
+}
+
+impl Contract {
+    pub fn type_script(&self, args: Bytes) -> Script {
+        Script::new_builder()
+            .code_hash(self.code_hash.clone())
+            .hash_type(self.hash_type.into())
+            .args(args.pack())
+            .build()
+    }
+
+    fn is_type_of(&self, output: &CellOutput) -> bool {
+        match output.type_().to_opt() {
+            Some(script) => {
+                script.code_hash() == self.code_hash && script.hash_type() == self.hash_type.into()
+            }
+            None => false,
+        }
+    }
+}
+
+/// A live time index cell
+#[derive(Clone)]
+pub struct TimeIndexCell {
+    pub out_point: OutPoint,
+    pub output: CellOutput,
+    pub data: Bytes,
+}
+
+/// Args of the type script, the out point of the first input of the create transaction
+pub fn build_args(input_out_point: &OutPoint) -> Bytes {
+    input_out_point.as_bytes()
+}
+
+/// Args of a clock updated at most once every `interval` seconds,
+/// `out point | interval as u64 in little endian`
+pub fn build_args_with_interval(input_out_point: &OutPoint, interval: u64) -> Bytes {
+    build_args_with_mode(input_out_point, ClockMode::Timestamp(interval))
+}
+
+/// Args of a clock updated at most once every interval of `mode`, `out point | interval as u64 in little endian`
+/// followed by the metric for a block number or epoch clock
+pub fn build_args_with_mode(input_out_point: &OutPoint, mode: ClockMode) -> Bytes {
+    let mut args = input_out_point.as_slice().to_vec();
+    match mode {
+        ClockMode::Timestamp(interval) => args.extend_from_slice(&interval.to_le_bytes()),
+        ClockMode::BlockNumber(interval) => {
+            args.extend_from_slice(&interval.to_le_bytes());
+            args.push(CLOCK_METRIC_BLOCK_NUMBER);
+        }
+        ClockMode::Epoch(interval) => {
+            args.extend_from_slice(&interval.to_le_bytes());
+            args.push(CLOCK_METRIC_EPOCH);
+        }
+    }
+    Bytes::from(args)
+}
+
+/// Args of time_info_type_script, the type hash of the time index cell the time info cells follow
+pub fn build_time_info_args(time_index_type_script: &Script) -> Bytes {
+    time_index_type_script.calc_script_hash().as_bytes()
+}
+
+/// Mode of the clock carried by the args of the type script, none for a clock without interval
+pub fn clock_mode(type_script: &Script) -> Option<ClockMode> {
+    get_clock_mode(&type_script.args().raw_data()).unwrap_or(None)
+}
+
+/// Since of the time index cell input and header deps proving the interval of the clock passed
+#[derive(Debug, Clone, PartialEq)]
+pub struct UpdateWait {
+    pub since: u64,
+    pub header_deps: Vec<Byte32>,
+}
+
+/// Wait of the update for the clock of `type_script`, `block` is the header of the block committing the
+/// time index cell for a block number clock, or a block of the epoch to record for an epoch clock
+pub fn build_update_wait(
+    type_script: &Script,
+    block: Option<&HeaderView>,
+) -> Result<UpdateWait, Error> {
+    match clock_mode(type_script) {
+        None => Ok(UpdateWait {
+            since: 0,
+            header_deps: Vec::new(),
+        }),
+        Some(ClockMode::Timestamp(interval)) => {
+            if interval > SINCE_VALUE_MASK {
+                return Err(Error::SinceOverflow);
+            }
+            Ok(UpdateWait {
+                since: SINCE_RELATIVE_TIMESTAMP_FLAG | interval,
+                header_deps: Vec::new(),
+            })
+        }
+        // an absolute block number since, the block of the time index cell proves the last update
+        Some(ClockMode::BlockNumber(interval)) => {
+            let block = block.ok_or(Error::HeaderRequired)?;
+            let since = block
+                .number()
+                .checked_add(interval)
+                .filter(|since| *since <= SINCE_VALUE_MASK)
+                .ok_or(Error::SinceOverflow)?;
+            Ok(UpdateWait {
+                since,
+                header_deps: vec![block.hash()],
+            })
+        }
+        // the header dep proves the epoch recorded by the cell data
+        Some(ClockMode::Epoch(_)) => {
+            let block = block.ok_or(Error::HeaderRequired)?;
+            Ok(UpdateWait {
+                since: 0,
+                header_deps: vec![block.hash()],
+            })
+        }
+    }
+}
+
+/// Since of the time info cell input of an update recording `timestamp` in seconds, an absolute timestamp
+/// the tolerance of time_info_type_script behind it, so the update is committed once the median time catches up
+pub fn build_time_info_since(timestamp: u64) -> u64 {
+    SINCE_METRIC_TIMESTAMP
+        | (timestamp.saturating_sub(TIME_INFO_TIMESTAMP_TOLERANCE) & SINCE_VALUE_MASK)
+}
+
+/// Cell data of the current version in the first lap, `index | N | version | lap as u64 in little endian`
+pub fn build_cell_data(index: u8, n: u8) -> Bytes {
+    build_cell_data_with_lap(index, n, 0)
+}
+
+/// Cell data of the current version in `lap`
+pub fn build_cell_data_with_lap(index: u8, n: u8, lap: u64) -> Bytes {
+    let mut data = vec![index, n, TIME_INDEX_CELL_DATA_VERSION];
+    data.extend_from_slice(&lap.to_le_bytes());
+    Bytes::from(data)
+}
+
+/// Cell data of an epoch clock in the first lap, `index | N | version | lap | epoch with fraction as u64`
+/// in little endian
+pub fn build_cell_data_with_epoch(index: u8, n: u8, epoch: u64) -> Bytes {
+    let mut data = build_cell_data(index, n).to_vec();
+    data.extend_from_slice(&epoch.to_le_bytes());
+    Bytes::from(data)
+}
+
+/// Epoch recorded by the cell data of an epoch clock
+pub fn cell_data_epoch(data: &[u8]) -> Option<Epoch> {
+    get_cell_data_epoch(data).map(Epoch::from_u64)
+}
+
+/// Lap recorded by the cell data, none for cell data older than the lap.
+/// `(lap, index)` tells apart the states of a time index cell, the index alone repeats every N updates.
+pub fn cell_data_lap(data: &[u8]) -> Option<u64> {
+    get_cell_data_lap(data)
+}
+
+/// Cell data of an epoch clock recording `epoch` instead
+pub fn replace_cell_data_epoch(data: &[u8], epoch: u64) -> Result<Bytes, Error> {
+    if get_cell_data_epoch(data).is_none() {
+        return Err(Error::InvalidCellData);
+    }
+    let mut data = data.to_vec();
+    let offset = data.len() - 8;
+    data[offset..].copy_from_slice(&epoch.to_le_bytes());
+    Ok(Bytes::from(data))
+}
+
+/// Check the cell data with the same rules as the contract
+pub fn check_cell_data(data: &[u8]) -> Result<(), Error> {
+    time_index_state_validation::helper::check_cell_data(data).map_err(|err| match err {
+        time_index_state_validation::Error::InvalidDataVersion => Error::InvalidDataVersion,
+        _ => Error::InvalidCellData,
+    })
+}
+
+/// Cell data of the next state, the index moves to `index + 1` and goes back to 0 after N - 1,
+/// when the lap moves to `lap + 1`. N, the version and the epoch of an epoch clock are kept
+pub fn next_cell_data(data: &[u8]) -> Result<Bytes, Error> {
+    check_cell_data(data)?;
+    let mut next_data = data.to_vec();
+    next_data[0] = (data[0] + 1) % data[1];
+    if let Some(lap) = get_cell_data_lap(data) {
+        if next_data[0] == 0 {
+            let lap = lap.checked_add(1).ok_or(Error::InvalidCellData)?;
+            next_data[TIME_INDEX_CELL_DATA_VERSIONED_LEN as usize
+                ..TIME_INDEX_CELL_DATA_WITH_LAP_LEN as usize]
+                .copy_from_slice(&lap.to_le_bytes());
+        }
+    }
+    Ok(Bytes::from(next_data))
+}
+
+/// Build the unsigned transaction creating a time index cell at index 0 with `lock`.
+/// The output holds the minimal capacity, the caller adds outputs for the change of the input.
+pub fn build_create_tx(
+    contract: &Contract,
+    input_out_point: OutPoint,
+    lock: Script,
+    n: u8,
+) -> Result<TransactionView, Error> {
+    build_create_tx_with_mode(contract, input_out_point, lock, n, None)
+}
+
+/// Same as `build_create_tx`, the clock can't be updated more than once every interval of `mode` if any.
+/// An epoch clock needs a block of its first epoch, see `build_create_tx_in_block`.
+pub fn build_create_tx_with_mode(
+    contract: &Contract,
+    input_out_point: OutPoint,
+    lock: Script,
+    n: u8,
+    mode: Option<ClockMode>,
+) -> Result<TransactionView, Error> {
+    build_create_tx_with_block(contract, input_out_point, lock, n, mode, None)
+}
+
+/// Same as `build_create_tx_with_mode`, an epoch clock records the epoch of `block`
+pub fn build_create_tx_in_block(
+    contract: &Contract,
+    input_out_point: OutPoint,
+    lock: Script,
+    n: u8,
+    mode: Option<ClockMode>,
+    block: &HeaderView,
+) -> Result<TransactionView, Error> {
+    build_create_tx_with_block(contract, input_out_point, lock, n, mode, Some(block))
+}
+
+fn build_create_tx_with_block(
+    contract: &Contract,
+    input_out_point: OutPoint,
+    lock: Script,
+    n: u8,
+    mode: Option<ClockMode>,
+    block: Option<&HeaderView>,
+) -> Result<TransactionView, Error> {
+    if n != TIME_INDEX_CELL_DATA_N {
+        return Err(Error::InvalidCellData);
+    }
+    let args = match mode {
+        Some(ClockMode::Timestamp(0))
+        | Some(ClockMode::BlockNumber(0))
+        | Some(ClockMode::Epoch(0)) => return Err(Error::InvalidInterval),
+        Some(mode) => build_args_with_mode(&input_out_point, mode),
+        None => build_args(&input_out_point),
+    };
+    let (data, header_deps) = match mode {
+        Some(ClockMode::Epoch(_)) => {
+            let block = block.ok_or(Error::HeaderRequired)?;
+            (
+                build_cell_data_with_epoch(0, n, block.epoch().full_value()),
+                vec![block.hash()],
+            )
+        }
+        _ => (build_cell_data(0, n), Vec::new()),
+    };
+    let output = CellOutput::new_builder()
+        .lock(lock)
+        .type_(Some(contract.type_script(args)).pack())
+        .build();
+    let capacity = output
+        .occupied_capacity(Capacity::bytes(data.len()).map_err(|_| Error::Capacity)?)
+        .map_err(|_| Error::Capacity)?;
+    let output = output.as_builder().capacity(capacity.pack()).build();
+
+    Ok(TransactionBuilder::default()
+        .input(CellInput::new(input_out_point, 0))
+        .output(output)
+        .output_data(data.pack())
+        .cell_dep(contract.cell_dep.clone())
+        .header_deps(header_deps)
+        .build())
+}
+
+/// Build the unsigned transaction updating `current_cell` to the next state.
+/// The output keeps the lock, type script and capacity of the current cell,
+/// the input waits for the interval of the clock if its args carry one.
+/// A block number or epoch clock needs a block, see `build_update_tx_in_block`.
+pub fn build_update_tx(
+    contract: &Contract,
+    current_cell: &TimeIndexCell,
+) -> Result<TransactionView, Error> {
+    build_update_tx_with_block(contract, current_cell, None)
+}
+
+/// Same as `build_update_tx`, `block` is the header of the block committing `current_cell` for a block number
+/// clock, or a block of the epoch to record for an epoch clock
+pub fn build_update_tx_in_block(
+    contract: &Contract,
+    current_cell: &TimeIndexCell,
+    block: &HeaderView,
+) -> Result<TransactionView, Error> {
+    build_update_tx_with_block(contract, current_cell, Some(block))
+}
+
+fn build_update_tx_with_block(
+    contract: &Contract,
+    current_cell: &TimeIndexCell,
+    block: Option<&HeaderView>,
+) -> Result<TransactionView, Error> {
+    let type_script = match current_cell.output.type_().to_opt() {
+        Some(type_script) if contract.is_type_of(&current_cell.output) => type_script,
+        _ => return Err(Error::InvalidTypeScript),
+    };
+    let wait = build_update_wait(&type_script, block)?;
+    let mut data = next_cell_data(&current_cell.data)?;
+    if let (Some(ClockMode::Epoch(_)), Some(block)) = (clock_mode(&type_script), block) {
+        data = replace_cell_data_epoch(&data, block.epoch().full_value())?;
+    }
+
+    Ok(TransactionBuilder::default()
+        .input(CellInput::new(current_cell.out_point.clone(), wait.since))
+        .output(current_cell.output.clone())
+        .output_data(data.pack())
+        .cell_dep(contract.cell_dep.clone())
+        .header_deps(wait.header_deps)
+        .build())
+}
+
+/// Decode a hex string, with or without the `0x` prefix
+pub fn decode_hex(hex: &str) -> Result<Vec<u8>, Error> {
+    let hex = hex.trim_start_matches("0x");
+    let mut bytes = vec![0u8; hex.len() / 2];
+    faster_hex::hex_decode(hex.as_bytes(), &mut bytes)
+        .map_err(|err| Error::InvalidHex(err.to_string()))?;
+    Ok(bytes)
+}
+
+/// Data of a dep group cell, the node expands the cell deps of `out_points` in this order
+pub fn build_dep_group_data(out_points: &[OutPoint]) -> Bytes {
+    OutPointVec::new_builder()
+        .set(out_points.to_vec())
+        .build()
+        .as_bytes()
+}
+
+/// Cell deps of a consumer: the dep group of the immutable `code_cells`, then the time index cell and the
+/// time info cell of its index as `Code` deps. Every update replaces both time cells, so they are never
+/// listed in a dep group, which would go stale after the next update.
+pub fn build_time_cell_deps(
+    code_dep_group: OutPoint,
+    time_index_cell: OutPoint,
+    time_info_cell: OutPoint,
+) -> Vec<CellDep> {
+    vec![
+        CellDep::new_builder()
+            .out_point(code_dep_group)
+            .dep_type(DepType::DepGroup.into())
+            .build(),
+        CellDep::new_builder()
+            .out_point(time_index_cell)
+            .dep_type(DepType::Code.into())
+            .build(),
+        CellDep::new_builder()
+            .out_point(time_info_cell)
+            .dep_type(DepType::Code.into())
+            .build(),
+    ]
+}
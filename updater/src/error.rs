@@ -0,0 +1,31 @@
+use std::fmt;
+
+use ckb_tool::ckb_types::packed::OutPoint;
+
+/// Error
+#[derive(Debug)]
+pub enum Error {
+    Rpc(String),
+    CellNotFound(OutPoint),
+    CellDead(OutPoint),
+    InvalidCellData(OutPoint),
+    Sign(String),
+    Config(String),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Rpc(err) => write!(f, "rpc error: {}", err),
+            Self::CellNotFound(out_point) => write!(f, "cell {} is not live", out_point),
+            Self::CellDead(out_point) => {
+                write!(f, "cell {} was spent by another transaction", out_point)
+            }
+            Self::InvalidCellData(out_point) => write!(f, "cell {} has invalid data", out_point),
+            Self::Sign(err) => write!(f, "sign error: {}", err),
+            Self::Config(err) => write!(f, "config error: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
@@ -0,0 +1,9 @@
+mod error;
+pub mod rpc;
+pub mod signer;
+mod state;
+mod updater;
+
+pub use error::Error;
+pub use state::UpdaterState;
+pub use updater::{Updater, UpdaterConfig};
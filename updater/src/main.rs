@@ -0,0 +1,74 @@
+use std::{env, fs, path::PathBuf, process, time::Duration};
+
+use ckb_tool::{
+    ckb_crypto::secp::Privkey,
+    ckb_jsonrpc_types::{CellDep, OutPoint},
+    ckb_types::H256,
+};
+use serde::Deserialize;
+use updater::{rpc::HttpRpc, signer::Signer, Error, Updater, UpdaterConfig, UpdaterState};
+
+const DEFAULT_INTERVAL: u64 = 60;
+
+#[derive(Deserialize)]
+struct Config {
+    rpc_url: String,
+    private_key: H256,
+    time_index_cell: OutPoint,
+    time_info_cells: Vec<OutPoint>,
+    cell_deps: Vec<CellDep>,
+    fee: u64,
+    interval: Option<u64>,
+    state_file: Option<PathBuf>,
+}
+
+fn load_config(path: &str) -> Result<Config, Error> {
+    let content = fs::read_to_string(path).map_err(|err| Error::Config(err.to_string()))?;
+    toml::from_str(&content).map_err(|err| Error::Config(err.to_string()))
+}
+
+fn load_state(config: &mut UpdaterConfig) -> Result<(), Error> {
+    let path = match config.state_file.as_ref() {
+        Some(path) => path,
+        None => return Ok(()),
+    };
+    match UpdaterState::load(path)? {
+        Some(state) => state.apply(config),
+        None => Ok(()),
+    }
+}
+
+fn main() {
+    let path = match env::args().nth(1) {
+        Some(path) => path,
+        None => {
+            eprintln!("usage: updater <config.toml>");
+            process::exit(1);
+        }
+    };
+    let config = match load_config(&path) {
+        Ok(config) => config,
+        Err(err) => {
+            eprintln!("{}", err);
+            process::exit(1);
+        }
+    };
+
+    let signer = Signer::new(Privkey::from(config.private_key));
+    let mut updater_config = UpdaterConfig {
+        time_index_cell: config.time_index_cell.into(),
+        time_info_cells: config.time_info_cells.into_iter().map(Into::into).collect(),
+        cell_deps: config.cell_deps.into_iter().map(Into::into).collect(),
+        fee: config.fee,
+        state_file: config.state_file,
+    };
+    //the cells of the config were spent by the updates saved to the state file
+    if let Err(err) = load_state(&mut updater_config) {
+        eprintln!("{}", err);
+        process::exit(1);
+    }
+    let mut updater = Updater::new(HttpRpc::new(&config.rpc_url), signer, updater_config);
+    updater.run(Duration::from_secs(
+        config.interval.unwrap_or(DEFAULT_INTERVAL),
+    ));
+}
@@ -0,0 +1,119 @@
+use ckb_tool::{
+    ckb_types::{
+        bytes::Bytes,
+        core::{HeaderView, TransactionView},
+        packed::{Byte32, CellOutput, OutPoint},
+        prelude::*,
+    },
+    rpc_client::RpcClient,
+};
+
+use crate::error::Error;
+
+const LIVE_CELL_STATUS: &str = "live";
+const DEAD_CELL_STATUS: &str = "dead";
+
+/// Status of a cell known by the node
+pub enum CellStatus {
+    /// Cell is live, with its output and data
+    Live(CellOutput, Bytes),
+    /// Cell was spent
+    Dead,
+    /// Cell is unknown, e.g. its transaction is still pending
+    Unknown,
+}
+
+/// Chain access used by the updater, implemented by `HttpRpc` for a CKB node
+pub trait Rpc {
+    /// Return status of the cell
+    fn get_cell(&self, out_point: &OutPoint) -> Result<CellStatus, Error>;
+
+    /// Return output and data of the cell, or none if the cell is not live
+    fn get_live_cell(&self, out_point: &OutPoint) -> Result<Option<(CellOutput, Bytes)>, Error> {
+        match self.get_cell(out_point)? {
+            CellStatus::Live(output, data) => Ok(Some((output, data))),
+            CellStatus::Dead | CellStatus::Unknown => Ok(None),
+        }
+    }
+
+    /// Return header of the tip block
+    fn get_tip_header(&self) -> Result<HeaderView, Error>;
+
+    /// Return header of the block committing the cell, or none if the cell is not committed
+    fn get_cell_header(&self, out_point: &OutPoint) -> Result<Option<HeaderView>, Error>;
+
+    /// Submit the transaction to the pool, return the transaction hash
+    fn send_transaction(&mut self, tx: &TransactionView) -> Result<Byte32, Error>;
+}
+
+/// JSON-RPC client of a CKB node
+pub struct HttpRpc {
+    client: RpcClient,
+}
+
+impl HttpRpc {
+    pub fn new(uri: &str) -> Self {
+        HttpRpc {
+            client: RpcClient::new(uri),
+        }
+    }
+}
+
+impl Rpc for HttpRpc {
+    fn get_cell(&self, out_point: &OutPoint) -> Result<CellStatus, Error> {
+        let cell_with_status = self
+            .client
+            .inner()
+            .get_live_cell(out_point.clone().into(), true)
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        if cell_with_status.status == DEAD_CELL_STATUS {
+            return Ok(CellStatus::Dead);
+        }
+        if cell_with_status.status != LIVE_CELL_STATUS {
+            return Ok(CellStatus::Unknown);
+        }
+        Ok(cell_with_status
+            .cell
+            .map(|cell| {
+                let data = cell
+                    .data
+                    .map(|data| data.content.into_bytes())
+                    .unwrap_or_default();
+                CellStatus::Live(cell.output.into(), data)
+            })
+            .unwrap_or(CellStatus::Unknown))
+    }
+
+    fn get_tip_header(&self) -> Result<HeaderView, Error> {
+        self.client
+            .inner()
+            .get_tip_header()
+            .map(Into::into)
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    fn get_cell_header(&self, out_point: &OutPoint) -> Result<Option<HeaderView>, Error> {
+        let tx = self
+            .client
+            .inner()
+            .get_transaction(out_point.tx_hash().unpack())
+            .map_err(|err| Error::Rpc(err.to_string()))?;
+        let block_hash = match tx.and_then(|tx| tx.tx_status.block_hash) {
+            Some(block_hash) => block_hash,
+            None => return Ok(None),
+        };
+        self.client
+            .inner()
+            .get_header(block_hash)
+            .map(|header| header.map(Into::into))
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+
+    fn send_transaction(&mut self, tx: &TransactionView) -> Result<Byte32, Error> {
+        self.client
+            .inner()
+            .send_transaction(tx.data().into())
+            .map(|tx_hash| tx_hash.pack())
+            .map_err(|err| Error::Rpc(err.to_string()))
+    }
+}
@@ -0,0 +1,65 @@
+use ckb_tool::{
+    ckb_crypto::secp::Privkey,
+    ckb_hash::new_blake2b,
+    ckb_types::{bytes::Bytes, core::TransactionView, packed::WitnessArgs, prelude::*, H256},
+};
+
+use crate::error::Error;
+
+const SIGNATURE_SIZE: usize = 65;
+
+/// Signer of the secp256k1 blake160 sighash all lock
+pub struct Signer {
+    privkey: Privkey,
+}
+
+impl Signer {
+    pub fn new(privkey: Privkey) -> Self {
+        Signer { privkey }
+    }
+
+    /// Sign the inputs of `group` as one lock group,
+    /// the signature is put in the lock of the witness of the first input in the group
+    pub fn sign_tx(&self, tx: TransactionView, group: &[usize]) -> Result<TransactionView, Error> {
+        let first = *group
+            .first()
+            .ok_or_else(|| Error::Sign("empty lock group".to_string()))?;
+        let zero_lock = Bytes::from(vec![0u8; SIGNATURE_SIZE]);
+        let witness = WitnessArgs::new_builder()
+            .lock(Some(zero_lock).pack())
+            .build();
+        let witness_bytes = witness.as_bytes();
+
+        let mut blake2b = new_blake2b();
+        blake2b.update(&tx.hash().raw_data());
+        blake2b.update(&(witness_bytes.len() as u64).to_le_bytes());
+        blake2b.update(&witness_bytes);
+        //the rest witnesses of the group are empty
+        for _ in 1..group.len() {
+            blake2b.update(&0u64.to_le_bytes());
+        }
+        let mut message = [0u8; 32];
+        blake2b.finalize(&mut message);
+
+        let message = H256::from_slice(&message).expect("message");
+        let signature = self
+            .privkey
+            .sign_recoverable(&message)
+            .map_err(|err| Error::Sign(err.to_string()))?;
+        let witness = witness
+            .as_builder()
+            .lock(Some(Bytes::from(signature.serialize())).pack())
+            .build();
+
+        let witnesses = (0..tx.inputs().len())
+            .map(|i| {
+                if i == first {
+                    witness.as_bytes().pack()
+                } else {
+                    Bytes::new().pack()
+                }
+            })
+            .collect::<Vec<_>>();
+        Ok(tx.as_advanced_builder().set_witnesses(witnesses).build())
+    }
+}
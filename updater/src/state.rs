@@ -0,0 +1,65 @@
+use std::{fs, io, path::Path};
+
+use ckb_tool::ckb_jsonrpc_types::OutPoint;
+use serde::{Deserialize, Serialize};
+
+use crate::{error::Error, updater::UpdaterConfig};
+
+/// Out points of the cells moved by the updater, saved after every update
+/// so a restart picks up the latest cells instead of the spent ones in the config
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct UpdaterState {
+    pub time_index_cell: OutPoint,
+    pub time_info_cells: Vec<OutPoint>,
+}
+
+impl UpdaterState {
+    pub fn new(config: &UpdaterConfig) -> Self {
+        UpdaterState {
+            time_index_cell: config.time_index_cell.clone().into(),
+            time_info_cells: config
+                .time_info_cells
+                .iter()
+                .cloned()
+                .map(Into::into)
+                .collect(),
+        }
+    }
+
+    /// Load the state, return none if the file does not exist yet
+    pub fn load(path: &Path) -> Result<Option<Self>, Error> {
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(err) => return Err(Error::Config(err.to_string())),
+        };
+        toml::from_str(&content)
+            .map(Some)
+            .map_err(|err| Error::Config(err.to_string()))
+    }
+
+    /// Write the state to a temporary file then move it to `path`, a crash never leaves half a file
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        let content = toml::to_string(self).map_err(|err| Error::Config(err.to_string()))?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir).map_err(|err| Error::Config(err.to_string()))?;
+        }
+        let tmp_path = path.with_extension("tmp");
+        fs::write(&tmp_path, content).map_err(|err| Error::Config(err.to_string()))?;
+        fs::rename(&tmp_path, path).map_err(|err| Error::Config(err.to_string()))
+    }
+
+    /// Replace the out points of the config by the saved ones
+    pub fn apply(self, config: &mut UpdaterConfig) -> Result<(), Error> {
+        if self.time_info_cells.len() != config.time_info_cells.len() {
+            return Err(Error::Config(format!(
+                "state has {} time info cells, config has {}",
+                self.time_info_cells.len(),
+                config.time_info_cells.len()
+            )));
+        }
+        config.time_index_cell = self.time_index_cell.into();
+        config.time_info_cells = self.time_info_cells.into_iter().map(Into::into).collect();
+        Ok(())
+    }
+}
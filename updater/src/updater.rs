@@ -0,0 +1,204 @@
+use std::{path::PathBuf, thread, time::Duration};
+
+use ckb_tool::ckb_types::{
+    bytes::Bytes,
+    core::TransactionBuilder,
+    packed::{Byte32, CellDep, CellInput, OutPoint},
+    prelude::*,
+};
+
+use tx_builder::{
+    build_time_info_since, build_update_wait, cell_data_epoch, clock_mode, next_cell_data,
+    replace_cell_data_epoch, ClockMode, SINCE_VALUE_MASK,
+};
+
+use crate::{
+    error::Error,
+    rpc::{CellStatus, Rpc},
+    signer::Signer,
+    state::UpdaterState,
+};
+
+const TIME_INDEX_CELL_DATA_MIN_LEN: usize = 2;
+
+/// Cells tracked and referenced by the updater
+pub struct UpdaterConfig {
+    /// Out point of the current time index cell
+    pub time_index_cell: OutPoint,
+    /// Out points of the time info cells, ordered by index
+    pub time_info_cells: Vec<OutPoint>,
+    /// Cell deps of the scripts, e.g. the dep group of the contracts and the secp256k1 lock
+    pub cell_deps: Vec<CellDep>,
+    /// Fee in shannons, paid by the time index cell
+    pub fee: u64,
+    /// File the out points are saved to after every update, none to keep them in memory only
+    pub state_file: Option<PathBuf>,
+}
+
+/// Updater advances the time index cell and its time info cell once per tick
+pub struct Updater<R: Rpc> {
+    rpc: R,
+    signer: Signer,
+    config: UpdaterConfig,
+}
+
+impl<R: Rpc> Updater<R> {
+    pub fn new(rpc: R, signer: Signer, config: UpdaterConfig) -> Self {
+        Updater {
+            rpc,
+            signer,
+            config,
+        }
+    }
+
+    pub fn rpc(&self) -> &R {
+        &self.rpc
+    }
+
+    pub fn rpc_mut(&mut self) -> &mut R {
+        &mut self.rpc
+    }
+
+    /// Out point of the time index cell tracked by the updater
+    pub fn time_index_cell(&self) -> &OutPoint {
+        &self.config.time_index_cell
+    }
+
+    /// Out points of the time info cells tracked by the updater
+    pub fn time_info_cells(&self) -> &[OutPoint] {
+        &self.config.time_info_cells
+    }
+
+    /// Build, sign and send the next update transaction.
+    /// Return none when the tracked time index cell is not live yet, e.g. last update is still pending,
+    /// or the chain is not high enough for a block number clock.
+    /// Return `Error::CellDead` when the tracked time index cell was spent by another transaction.
+    pub fn tick(&mut self) -> Result<Option<Byte32>, Error> {
+        let index_out_point = self.config.time_index_cell.clone();
+        let (index_output, index_data) = match self.rpc.get_cell(&index_out_point)? {
+            CellStatus::Live(output, data) => (output, data),
+            CellStatus::Dead => return Err(Error::CellDead(index_out_point)),
+            CellStatus::Unknown => return Ok(None),
+        };
+        if index_data.len() < TIME_INDEX_CELL_DATA_MIN_LEN
+            || index_data[1] == 0
+            || index_data[0] >= index_data[1]
+            || self.config.time_info_cells.len() != index_data[1] as usize
+        {
+            return Err(Error::InvalidCellData(index_out_point));
+        }
+        //keep N and the version byte as they are, the lap moves on when the index goes back to 0
+        let mut next_index_data = next_cell_data(&index_data)
+            .map_err(|_| Error::InvalidCellData(index_out_point.clone()))?;
+        let next_index = next_index_data[0];
+
+        let info_out_point = self.config.time_info_cells[next_index as usize].clone();
+        let (info_output, _) = self
+            .rpc
+            .get_live_cell(&info_out_point)?
+            .ok_or_else(|| Error::CellNotFound(info_out_point.clone()))?;
+
+        let tip = self.rpc.get_tip_header()?;
+        let timestamp = tip.timestamp() / 1000;
+        let mut next_info_data = vec![next_index];
+        next_info_data.extend_from_slice(&timestamp.to_le_bytes());
+
+        let index_capacity: u64 = index_output.capacity().unpack();
+        let index_output = index_output
+            .as_builder()
+            .capacity(index_capacity.saturating_sub(self.config.fee).pack())
+            .build();
+        let signer_lock = info_output.lock();
+        //a clock with an interval in its args only accepts the update once the interval passed
+        let type_script = index_output
+            .type_()
+            .to_opt()
+            .ok_or_else(|| Error::InvalidCellData(index_out_point.clone()))?;
+        let block = match clock_mode(&type_script) {
+            Some(ClockMode::BlockNumber(interval)) => {
+                //the cell was just live, no block means a reorg sent its transaction back to the pool
+                let block = match self.rpc.get_cell_header(&index_out_point)? {
+                    Some(block) => block,
+                    None => return Ok(None),
+                };
+                //the block to wait for is also the absolute since of the update
+                let ready_number = block
+                    .number()
+                    .checked_add(interval)
+                    .filter(|number| *number <= SINCE_VALUE_MASK)
+                    .ok_or_else(|| {
+                        Error::Config(format!("block interval {} overflows the since", interval))
+                    })?;
+                if tip.number() < ready_number {
+                    return Ok(None);
+                }
+                Some(block)
+            }
+            //the tip proves the epoch recorded by the next state
+            Some(ClockMode::Epoch(interval)) => {
+                let epoch = cell_data_epoch(&index_data)
+                    .ok_or_else(|| Error::InvalidCellData(index_out_point.clone()))?;
+                let ready_epoch = epoch.number.checked_add(interval).ok_or_else(|| {
+                    Error::Config(format!("epoch interval {} overflows", interval))
+                })?;
+                if tip.epoch().number() < ready_epoch {
+                    return Ok(None);
+                }
+                next_index_data =
+                    replace_cell_data_epoch(&next_index_data, tip.epoch().full_value())
+                        .map_err(|_| Error::InvalidCellData(index_out_point.clone()))?;
+                Some(tip)
+            }
+            _ => None,
+        };
+        let wait = build_update_wait(&type_script, block.as_ref())
+            .map_err(|_| Error::InvalidCellData(index_out_point.clone()))?;
+
+        let tx = TransactionBuilder::default()
+            .input(CellInput::new(index_out_point, wait.since))
+            .input(CellInput::new(
+                info_out_point,
+                build_time_info_since(timestamp),
+            ))
+            .output(index_output.clone())
+            .output(info_output)
+            .output_data(next_index_data.pack())
+            .output_data(Bytes::from(next_info_data).pack())
+            .cell_deps(self.config.cell_deps.clone())
+            .header_deps(wait.header_deps)
+            .build();
+        //the time info cell is locked by the key of the updater,
+        //so is the time index cell if it doesn't use time_index_state_lock_script
+        let group: Vec<usize> = if index_output.lock() == signer_lock {
+            vec![0, 1]
+        } else {
+            vec![1]
+        };
+        let tx = self.signer.sign_tx(tx, &group)?;
+        let tx_hash = self.rpc.send_transaction(&tx)?;
+
+        self.config.time_index_cell = OutPoint::new(tx_hash.clone(), 0);
+        self.config.time_info_cells[next_index as usize] = OutPoint::new(tx_hash.clone(), 1);
+        self.save_state()?;
+        Ok(Some(tx_hash))
+    }
+
+    fn save_state(&self) -> Result<(), Error> {
+        match self.config.state_file.as_ref() {
+            Some(path) => UpdaterState::new(&self.config).save(path),
+            None => Ok(()),
+        }
+    }
+
+    /// Tick forever, wait `interval` between two ticks
+    pub fn run(&mut self, interval: Duration) {
+        loop {
+            match self.tick() {
+                Ok(Some(tx_hash)) => println!("sent update transaction {}", tx_hash),
+                Ok(None) => println!("waiting for the last update to be committed"),
+                Err(err) => eprintln!("failed to update: {}", err),
+            }
+            thread::sleep(interval);
+        }
+    }
+}
@@ -0,0 +1,14 @@
+#![no_main]
+use libfuzzer_sys::fuzz_target;
+use time_index_state_validation::helper::{
+    check_cell_data, get_cell_data_version, TIME_INDEX_CELL_DATA_N, TIME_INDEX_CELL_DATA_VERSION,
+};
+
+fuzz_target!(|data: &[u8]| {
+    if check_cell_data(data).is_ok() {
+        assert!(matches!(data.len(), 2 | 3 | 11 | 19));
+        assert!(data[0] < TIME_INDEX_CELL_DATA_N);
+        assert_eq!(data[1], TIME_INDEX_CELL_DATA_N);
+        assert!(get_cell_data_version(data) <= TIME_INDEX_CELL_DATA_VERSION);
+    }
+});
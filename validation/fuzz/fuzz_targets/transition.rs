@@ -0,0 +1,127 @@
+#![no_main]
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use time_index_state_validation::{
+    helper::TIME_INDEX_CELL_DATA_N, verify, CellLoader, Error, Source,
+};
+
+const SCRIPT_HASH: [u8; 32] = [1; 32];
+const OTHER_SCRIPT_HASH: [u8; 32] = [2; 32];
+
+#[derive(Arbitrary, Debug)]
+enum TypeScript {
+    None,
+    This,
+    Other,
+}
+
+#[derive(Arbitrary, Debug)]
+struct Cell {
+    type_script: TypeScript,
+    type_args: Vec<u8>,
+    data: Vec<u8>,
+}
+
+// a transaction of arbitrary shape, the running script is the type script with `SCRIPT_HASH`
+#[derive(Arbitrary, Debug)]
+struct Transaction {
+    script_args: Vec<u8>,
+    input_out_points: Vec<Vec<u8>>,
+    input_sinces: Vec<u64>,
+    input_block_numbers: Vec<Option<u64>>,
+    header_dep_block_numbers: Vec<u64>,
+    header_dep_epochs: Vec<u64>,
+    inputs: Vec<Cell>,
+    outputs: Vec<Cell>,
+}
+
+impl Transaction {
+    fn cell(&self, index: usize, source: Source) -> Result<&Cell, Error> {
+        match source {
+            Source::Input => self.inputs.get(index),
+            Source::Output => self.outputs.get(index),
+        }
+        .ok_or(Error::IndexOutOfBound)
+    }
+}
+
+impl CellLoader for Transaction {
+    fn load_script_hash(&self) -> Result<[u8; 32], Error> {
+        Ok(SCRIPT_HASH)
+    }
+
+    fn load_script_args(&self) -> Result<Vec<u8>, Error> {
+        Ok(self.script_args.clone())
+    }
+
+    fn load_cell_type_hashes(&self, source: Source) -> Result<Vec<Option<[u8; 32]>>, Error> {
+        let cells = match source {
+            Source::Input => &self.inputs,
+            Source::Output => &self.outputs,
+        };
+        Ok(cells
+            .iter()
+            .map(|cell| match cell.type_script {
+                TypeScript::None => None,
+                TypeScript::This => Some(SCRIPT_HASH),
+                TypeScript::Other => Some(OTHER_SCRIPT_HASH),
+            })
+            .collect())
+    }
+
+    fn load_cell_type_args(&self, index: usize, source: Source) -> Result<Option<Vec<u8>>, Error> {
+        let cell = self.cell(index, source)?;
+        Ok(match cell.type_script {
+            TypeScript::None => None,
+            TypeScript::This => Some(self.script_args.clone()),
+            TypeScript::Other => Some(cell.type_args.clone()),
+        })
+    }
+
+    fn load_cell_data(&self, index: usize, source: Source) -> Result<Vec<u8>, Error> {
+        Ok(self.cell(index, source)?.data.clone())
+    }
+
+    fn load_input_out_point(&self, index: usize) -> Result<Vec<u8>, Error> {
+        self.cell(index, Source::Input)?;
+        Ok(self
+            .input_out_points
+            .get(index)
+            .cloned()
+            .unwrap_or_else(|| vec![0; 36]))
+    }
+
+    fn load_input_since(&self, index: usize) -> Result<u64, Error> {
+        self.cell(index, Source::Input)?;
+        Ok(self.input_sinces.get(index).cloned().unwrap_or(0))
+    }
+
+    fn load_input_block_number(&self, index: usize) -> Result<u64, Error> {
+        self.cell(index, Source::Input)?;
+        self.input_block_numbers
+            .get(index)
+            .cloned()
+            .flatten()
+            .ok_or(Error::ItemMissing)
+    }
+
+    fn load_header_dep_block_numbers(&self) -> Result<Vec<u64>, Error> {
+        Ok(self.header_dep_block_numbers.clone())
+    }
+
+    fn load_header_dep_epochs(&self) -> Result<Vec<u64>, Error> {
+        Ok(self.header_dep_epochs.clone())
+    }
+}
+
+fuzz_target!(|tx: Transaction| {
+    // never panics, and an accepted update always moves the index one step around the ring
+    let result = verify(&tx);
+    let this = |cell: &&Cell| matches!(cell.type_script, TypeScript::This);
+    if result.is_ok() {
+        if let Some(input) = tx.inputs.iter().find(this) {
+            let output = tx.outputs.iter().find(this).expect("output");
+            assert_eq!(output.data[0], (input.data[0] + 1) % TIME_INDEX_CELL_DATA_N);
+        }
+    }
+});
@@ -0,0 +1,35 @@
+use crate::error::*;
+use crate::helper::{
+    check_args_when_create_cell, check_cell_data, check_epoch, get_cell_data_epoch,
+    get_cell_data_lap, get_script_hash_cell_count, ClockMode,
+};
+use crate::loader::{CellLoader, Source};
+
+pub fn create<L: CellLoader>(loader: &L, script_hash: [u8; 32]) -> Result<(), Error> {
+    //should only one time index cell in output
+    if get_script_hash_cell_count(loader, script_hash, Source::Output)? != 1 {
+        return Err(Error::InvalidTimeIndexOutput);
+    }
+
+    //the args of output script should equal the output point of the first input
+    let mode = check_args_when_create_cell(loader)?;
+
+    let output_cell_data = crate::helper::load_cell_data(loader, script_hash, Source::Output)?;
+    check_cell_data(&output_cell_data)?;
+
+    //index should equal 0 when create
+    if output_cell_data[0] != 0 {
+        return Err(Error::InvalidCellData);
+    }
+    //the first lap is 0
+    if get_cell_data_lap(&output_cell_data).unwrap_or(0) != 0 {
+        return Err(Error::InvalidLap);
+    }
+
+    //an epoch clock starts at the epoch of a header dep
+    match mode {
+        Some(ClockMode::Epoch(_)) => check_epoch(loader, &output_cell_data),
+        _ if get_cell_data_epoch(&output_cell_data).is_some() => Err(Error::InvalidCellData),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,18 @@
+/// Error
+#[derive(Debug, PartialEq)]
+#[repr(i8)]
+pub enum Error {
+    IndexOutOfBound = 1,
+    ItemMissing,
+    LengthNotEnough,
+    Encoding,
+    InvalidArgument,
+    InvalidTimeIndexInput,
+    InvalidTimeIndexOutput,
+    InvalidCellData,
+    InvalidDataVersion,
+    InvalidSince,
+    InvalidBlockNumber,
+    InvalidEpoch,
+    InvalidLap,
+}
@@ -0,0 +1,317 @@
+use alloc::vec::Vec;
+use core::convert::TryInto;
+
+use crate::error::Error;
+use crate::loader::{CellLoader, Source};
+
+pub const TIME_INDEX_CELL_DATA_LEN: u8 = 2;
+pub const TIME_INDEX_CELL_DATA_N: u8 = 12;
+//cell data written by the first release has no version byte, it is treated as version 0
+pub const TIME_INDEX_CELL_DATA_LEGACY_VERSION: u8 = 0;
+pub const TIME_INDEX_CELL_DATA_VERSION: u8 = 2;
+pub const TIME_INDEX_CELL_DATA_VERSIONED_LEN: u8 = 3;
+//from version 2 the version is followed by the lap, the turns of the index around the ring, as u64 in little endian
+pub const TIME_INDEX_CELL_DATA_LAP_VERSION: u8 = 2;
+pub const TIME_INDEX_CELL_DATA_WITH_LAP_LEN: u8 = 11;
+//the cell data of an epoch clock ends with the recorded epoch with fraction as u64 in little endian
+pub const TIME_INDEX_CELL_DATA_EPOCH_LEN: u8 = 8;
+//args is the out point of the first input of the create transaction, optionally followed by
+//the update interval as u64 in little endian and the metric of the interval, seconds if omitted
+pub const TIME_INDEX_ARGS_LEN: usize = 36;
+pub const TIME_INDEX_ARGS_WITH_INTERVAL_LEN: usize = 44;
+pub const TIME_INDEX_ARGS_WITH_METRIC_LEN: usize = 45;
+pub const CLOCK_METRIC_BLOCK_NUMBER: u8 = 1;
+pub const CLOCK_METRIC_EPOCH: u8 = 2;
+
+pub const SINCE_RELATIVE_FLAG: u64 = 0x8000_0000_0000_0000;
+pub const SINCE_METRIC_MASK: u64 = 0x6000_0000_0000_0000;
+pub const SINCE_METRIC_BLOCK_NUMBER: u64 = 0;
+pub const SINCE_METRIC_TIMESTAMP: u64 = 0x4000_0000_0000_0000;
+pub const SINCE_RESERVED_MASK: u64 = 0x1f00_0000_0000_0000;
+pub const SINCE_VALUE_MASK: u64 = 0x00ff_ffff_ffff_ffff;
+//seconds a time info timestamp can be ahead of the absolute timestamp since of its input,
+//the median time the since is checked against lags the tip by a few minutes
+pub const TIME_INFO_TIMESTAMP_TOLERANCE: u64 = 300;
+
+pub fn get_script_hash_cell_count<L: CellLoader>(
+    loader: &L,
+    script_hash: [u8; 32],
+    source: Source,
+) -> Result<usize, Error> {
+    Ok(loader
+        .load_cell_type_hashes(source)?
+        .iter()
+        .filter(|type_hash| match type_hash {
+            Some(type_script_hash) => *type_script_hash == script_hash,
+            None => false,
+        })
+        .count())
+}
+
+pub fn check_args_when_create_cell<L: CellLoader>(loader: &L) -> Result<Option<ClockMode>, Error> {
+    let script_args = loader.load_script_args()?;
+    let mode = get_clock_mode(&script_args)?;
+    let input_out_point = loader.load_input_out_point(0)?;
+    if input_out_point[..] != script_args[..TIME_INDEX_ARGS_LEN] {
+        return Err(Error::InvalidArgument);
+    }
+    Ok(mode)
+}
+
+/// How often the clock can be updated, selected by the args at create
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ClockMode {
+    /// Once every `interval` seconds, measured by relative timestamp since
+    Timestamp(u64),
+    /// Once every `interval` blocks, measured by the block numbers of header deps or absolute block number since
+    BlockNumber(u64),
+    /// Once every `interval` epochs, the cell data records the epoch of a header dep
+    Epoch(u64),
+}
+
+/// Epoch with fraction of a header, `number` in the lowest 24 bits, then `index` and `length` in 16 bits each
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Epoch {
+    pub number: u64,
+    pub index: u64,
+    pub length: u64,
+}
+
+impl Epoch {
+    pub fn from_u64(value: u64) -> Self {
+        Epoch {
+            number: value & 0xff_ffff,
+            index: (value >> 24) & 0xffff,
+            length: (value >> 40) & 0xffff,
+        }
+    }
+}
+
+/// Mode of the clock carried by the args, none for the args of a clock without interval
+pub fn get_clock_mode(script_args: &[u8]) -> Result<Option<ClockMode>, Error> {
+    let interval = match script_args.len() {
+        TIME_INDEX_ARGS_LEN => return Ok(None),
+        TIME_INDEX_ARGS_WITH_INTERVAL_LEN | TIME_INDEX_ARGS_WITH_METRIC_LEN => u64::from_le_bytes(
+            script_args[TIME_INDEX_ARGS_LEN..TIME_INDEX_ARGS_WITH_INTERVAL_LEN]
+                .try_into()
+                .unwrap(),
+        ),
+        _ => return Err(Error::InvalidArgument),
+    };
+    //a zero interval would be the same clock as the args without interval
+    if interval == 0 {
+        return Err(Error::InvalidArgument);
+    }
+    match script_args.get(TIME_INDEX_ARGS_WITH_INTERVAL_LEN) {
+        None => Ok(Some(ClockMode::Timestamp(interval))),
+        Some(&CLOCK_METRIC_BLOCK_NUMBER) => Ok(Some(ClockMode::BlockNumber(interval))),
+        Some(&CLOCK_METRIC_EPOCH) => Ok(Some(ClockMode::Epoch(interval))),
+        Some(_) => Err(Error::InvalidArgument),
+    }
+}
+
+pub fn check_since_interval<L: CellLoader>(
+    loader: &L,
+    script_hash: [u8; 32],
+    interval: u64,
+) -> Result<(), Error> {
+    //the time index input should be older than interval seconds, measured by relative timestamp since
+    let cell_index =
+        match get_position_of_cell_with_type_script(loader, script_hash, Source::Input)? {
+            Some(position) => position,
+            None => return Err(Error::InvalidTimeIndexInput),
+        };
+    let since = loader.load_input_since(cell_index)?;
+    if since & SINCE_RELATIVE_FLAG == 0
+        || since & SINCE_METRIC_MASK != SINCE_METRIC_TIMESTAMP
+        || since & SINCE_RESERVED_MASK != 0
+        || since & SINCE_VALUE_MASK < interval
+    {
+        return Err(Error::InvalidSince);
+    }
+    Ok(())
+}
+
+pub fn check_block_number_interval<L: CellLoader>(
+    loader: &L,
+    script_hash: [u8; 32],
+    interval: u64,
+) -> Result<(), Error> {
+    let cell_index =
+        match get_position_of_cell_with_type_script(loader, script_hash, Source::Input)? {
+            Some(position) => position,
+            None => return Err(Error::InvalidTimeIndexInput),
+        };
+    //the block of the last update, proven by its header in header deps
+    let last_update = loader.load_input_block_number(cell_index)?;
+    let target = last_update
+        .checked_add(interval)
+        .ok_or(Error::InvalidBlockNumber)?;
+
+    //the chain reached the target, either the transaction can't be committed before it,
+    //or a header dep is at least as high
+    let since = loader.load_input_since(cell_index)?;
+    if since & (SINCE_RELATIVE_FLAG | SINCE_METRIC_MASK | SINCE_RESERVED_MASK)
+        == SINCE_METRIC_BLOCK_NUMBER
+        && since & SINCE_VALUE_MASK >= target
+    {
+        return Ok(());
+    }
+    if loader
+        .load_header_dep_block_numbers()?
+        .iter()
+        .any(|block_number| *block_number >= target)
+    {
+        return Ok(());
+    }
+    Err(Error::InvalidBlockNumber)
+}
+
+/// The recorded epoch should move forward by interval epochs at least
+pub fn check_epoch_interval(
+    input_cell_data: &[u8],
+    output_cell_data: &[u8],
+    interval: u64,
+) -> Result<(), Error> {
+    let input_epoch = get_cell_data_epoch(input_cell_data).ok_or(Error::InvalidCellData)?;
+    let output_epoch = get_cell_data_epoch(output_cell_data).ok_or(Error::InvalidCellData)?;
+    let target = Epoch::from_u64(input_epoch)
+        .number
+        .checked_add(interval)
+        .ok_or(Error::InvalidEpoch)?;
+    if Epoch::from_u64(output_epoch).number < target {
+        return Err(Error::InvalidEpoch);
+    }
+    Ok(())
+}
+
+pub fn check_epoch<L: CellLoader>(loader: &L, output_cell_data: &[u8]) -> Result<(), Error> {
+    let output_epoch = get_cell_data_epoch(output_cell_data).ok_or(Error::InvalidCellData)?;
+    //the recorded epoch should be the epoch of a header dep, so the chain reached it
+    if !loader.load_header_dep_epochs()?.contains(&output_epoch) {
+        return Err(Error::InvalidEpoch);
+    }
+    Ok(())
+}
+
+pub fn get_position_of_cell_with_type_script<L: CellLoader>(
+    loader: &L,
+    script_hash: [u8; 32],
+    source: Source,
+) -> Result<Option<usize>, Error> {
+    Ok(loader.load_cell_type_hashes(source)?.iter().position(
+        |type_script_op| match type_script_op {
+            Some(type_script) => *type_script == script_hash,
+            None => false,
+        },
+    ))
+}
+
+pub fn check_args_when_update_cell<L: CellLoader>(
+    loader: &L,
+    script_hash: [u8; 32],
+) -> Result<Vec<u8>, Error> {
+    let script_args = loader.load_script_args()?;
+    if script_args.is_empty() {
+        return Err(Error::InvalidArgument);
+    }
+
+    let cell_index =
+        match get_position_of_cell_with_type_script(loader, script_hash, Source::Input)? {
+            Some(position) => position,
+            None => return Err(Error::InvalidTimeIndexInput),
+        };
+    let input_script_args = match loader.load_cell_type_args(cell_index, Source::Input)? {
+        Some(args) => args,
+        None => return Err(Error::InvalidTimeIndexInput),
+    };
+
+    if input_script_args[..] != script_args[..] {
+        return Err(Error::InvalidArgument);
+    }
+    Ok(script_args)
+}
+
+pub fn load_cell_data<L: CellLoader>(
+    loader: &L,
+    script_hash: [u8; 32],
+    source: Source,
+) -> Result<Vec<u8>, Error> {
+    let cell_index = match get_position_of_cell_with_type_script(loader, script_hash, source)? {
+        Some(position) => position,
+        None => {
+            return match source {
+                Source::Input => Err(Error::InvalidTimeIndexInput),
+                Source::Output => Err(Error::InvalidTimeIndexOutput),
+            };
+        }
+    };
+    loader.load_cell_data(cell_index, source)
+}
+
+pub fn check_cell_data(data: &[u8]) -> Result<(), Error> {
+    if data.len() < TIME_INDEX_CELL_DATA_LEN as usize
+        || data[0] >= TIME_INDEX_CELL_DATA_N
+        || data[1] != TIME_INDEX_CELL_DATA_N
+    {
+        return Err(Error::InvalidCellData);
+    }
+    //a version byte must name a version this binary knows, legacy data never carries one
+    let version = get_cell_data_version(data);
+    if data.len() > TIME_INDEX_CELL_DATA_LEN as usize
+        && (version == TIME_INDEX_CELL_DATA_LEGACY_VERSION
+            || version > TIME_INDEX_CELL_DATA_VERSION)
+    {
+        return Err(Error::InvalidDataVersion);
+    }
+    //the fields of the version, followed by the epoch of an epoch clock only
+    let fields_len = get_cell_data_fields_len(version);
+    if data.len() != fields_len
+        && data.len() != fields_len + TIME_INDEX_CELL_DATA_EPOCH_LEN as usize
+    {
+        return Err(Error::InvalidCellData);
+    }
+    Ok(())
+}
+
+// length of the fields of the version, without the epoch of an epoch clock
+fn get_cell_data_fields_len(version: u8) -> usize {
+    if version == TIME_INDEX_CELL_DATA_LEGACY_VERSION {
+        TIME_INDEX_CELL_DATA_LEN as usize
+    } else if version < TIME_INDEX_CELL_DATA_LAP_VERSION {
+        TIME_INDEX_CELL_DATA_VERSIONED_LEN as usize
+    } else {
+        TIME_INDEX_CELL_DATA_WITH_LAP_LEN as usize
+    }
+}
+
+pub fn get_cell_data_version(data: &[u8]) -> u8 {
+    if data.len() <= TIME_INDEX_CELL_DATA_LEN as usize {
+        return TIME_INDEX_CELL_DATA_LEGACY_VERSION;
+    }
+    data[TIME_INDEX_CELL_DATA_LEN as usize]
+}
+
+/// Epoch with fraction recorded by the cell data of an epoch clock, none for other cell data
+pub fn get_cell_data_epoch(data: &[u8]) -> Option<u64> {
+    let fields_len = get_cell_data_fields_len(get_cell_data_version(data));
+    if data.len() != fields_len + TIME_INDEX_CELL_DATA_EPOCH_LEN as usize {
+        return None;
+    }
+    Some(u64::from_le_bytes(data[fields_len..].try_into().unwrap()))
+}
+
+/// Lap of the index recorded by the cell data from version 2, none for older cell data
+pub fn get_cell_data_lap(data: &[u8]) -> Option<u64> {
+    if get_cell_data_version(data) < TIME_INDEX_CELL_DATA_LAP_VERSION
+        || data.len() < TIME_INDEX_CELL_DATA_WITH_LAP_LEN as usize
+    {
+        return None;
+    }
+    Some(u64::from_le_bytes(
+        data[TIME_INDEX_CELL_DATA_VERSIONED_LEN as usize
+            ..TIME_INDEX_CELL_DATA_WITH_LAP_LEN as usize]
+            .try_into()
+            .unwrap(),
+    ))
+}
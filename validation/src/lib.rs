@@ -0,0 +1,33 @@
+//! Validation rules of time_index_state_type_script.
+//!
+//! The rules load cells through `CellLoader`, the contract implements it with syscalls,
+//! and the same rules run on the host with in-memory cells.
+
+#![no_std]
+
+extern crate alloc;
+
+mod create;
+mod error;
+pub mod helper;
+mod loader;
+mod update;
+
+pub use create::create;
+pub use error::Error;
+pub use loader::{CellLoader, Source};
+pub use update::{check_update_cell_data, update};
+
+/// Update if any input has the type script, otherwise create
+pub fn verify<L: CellLoader>(loader: &L) -> Result<(), Error> {
+    let script_hash = loader.load_script_hash()?;
+    if loader
+        .load_cell_type_hashes(Source::Input)?
+        .iter()
+        .any(|type_hash| type_hash.as_ref() == Some(&script_hash))
+    {
+        update(loader, script_hash)
+    } else {
+        create(loader, script_hash)
+    }
+}
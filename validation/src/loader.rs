@@ -0,0 +1,42 @@
+use alloc::vec::Vec;
+
+use crate::error::Error;
+
+/// Where the cells are loaded from
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Source {
+    Input,
+    Output,
+}
+
+/// Loads the running script and the cells of the transaction
+pub trait CellLoader {
+    /// Hash of the running script
+    fn load_script_hash(&self) -> Result<[u8; 32], Error>;
+
+    /// Args of the running script
+    fn load_script_args(&self) -> Result<Vec<u8>, Error>;
+
+    /// Type script hash of every cell in `source`, none for cells without type script
+    fn load_cell_type_hashes(&self, source: Source) -> Result<Vec<Option<[u8; 32]>>, Error>;
+
+    /// Args of the type script of the cell, none if the cell has no type script
+    fn load_cell_type_args(&self, index: usize, source: Source) -> Result<Option<Vec<u8>>, Error>;
+
+    fn load_cell_data(&self, index: usize, source: Source) -> Result<Vec<u8>, Error>;
+
+    /// Serialized out point of the input
+    fn load_input_out_point(&self, index: usize) -> Result<Vec<u8>, Error>;
+
+    /// Since of the input
+    fn load_input_since(&self, index: usize) -> Result<u64, Error>;
+
+    /// Number of the block committing the input, its header should be in header deps
+    fn load_input_block_number(&self, index: usize) -> Result<u64, Error>;
+
+    /// Numbers of the blocks in header deps
+    fn load_header_dep_block_numbers(&self) -> Result<Vec<u64>, Error>;
+
+    /// Epochs with fraction of the blocks in header deps
+    fn load_header_dep_epochs(&self) -> Result<Vec<u64>, Error>;
+}
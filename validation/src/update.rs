@@ -0,0 +1,89 @@
+use crate::error::*;
+use crate::helper::{
+    check_args_when_update_cell, check_block_number_interval, check_cell_data, check_epoch,
+    check_epoch_interval, check_since_interval, get_cell_data_epoch, get_cell_data_lap,
+    get_cell_data_version, get_clock_mode, get_script_hash_cell_count, ClockMode,
+    TIME_INDEX_CELL_DATA_N,
+};
+use crate::loader::{CellLoader, Source};
+
+pub fn update<L: CellLoader>(loader: &L, script_hash: [u8; 32]) -> Result<(), Error> {
+    //should only one time index cell in input
+    if get_script_hash_cell_count(loader, script_hash, Source::Input)? != 1 {
+        return Err(Error::InvalidTimeIndexInput);
+    }
+    //should only one time index cell in output
+    if get_script_hash_cell_count(loader, script_hash, Source::Output)? != 1 {
+        return Err(Error::InvalidTimeIndexOutput);
+    }
+    //check whether args of script of input not empty and equal args of output's
+    let script_args = check_args_when_update_cell(loader, script_hash)?;
+
+    //a clock with an interval can't be updated before the interval passed since the last update
+    let mode = get_clock_mode(&script_args)?;
+    match mode {
+        Some(ClockMode::Timestamp(interval)) => {
+            check_since_interval(loader, script_hash, interval)?
+        }
+        Some(ClockMode::BlockNumber(interval)) => {
+            check_block_number_interval(loader, script_hash, interval)?
+        }
+        _ => {}
+    }
+
+    let input_cell_data = crate::helper::load_cell_data(loader, script_hash, Source::Input)?;
+    let output_cell_data = crate::helper::load_cell_data(loader, script_hash, Source::Output)?;
+    check_update_cell_data(mode, &input_cell_data, &output_cell_data)?;
+
+    //the epoch of an epoch clock is proven by a header dep
+    match mode {
+        Some(ClockMode::Epoch(_)) => check_epoch(loader, &output_cell_data),
+        _ => Ok(()),
+    }
+}
+
+/// Rules of update on the cell data alone, without the since and the header deps of the transaction
+pub fn check_update_cell_data(
+    mode: Option<ClockMode>,
+    input_cell_data: &[u8],
+    output_cell_data: &[u8],
+) -> Result<(), Error> {
+    check_cell_data(input_cell_data)?;
+    check_cell_data(output_cell_data)?;
+
+    let input_time_index = input_cell_data[0];
+    let output_time_index = output_cell_data[0];
+    if (input_time_index != TIME_INDEX_CELL_DATA_N - 1 && output_time_index != input_time_index + 1)
+        || (input_time_index == TIME_INDEX_CELL_DATA_N - 1 && output_time_index != 0)
+    {
+        return Err(Error::InvalidCellData);
+    }
+
+    //a state written by an older binary may be carried forward or upgraded, but never downgraded
+    if get_cell_data_version(output_cell_data) < get_cell_data_version(input_cell_data) {
+        return Err(Error::InvalidDataVersion);
+    }
+
+    //the lap moves forward when the index goes back to 0, so a state is never the same as one of an earlier lap,
+    //a state upgraded from a version without lap starts from lap 0
+    if let Some(output_lap) = get_cell_data_lap(output_cell_data) {
+        let input_lap = get_cell_data_lap(input_cell_data).unwrap_or(0);
+        let expected_lap = if output_time_index == 0 {
+            input_lap.checked_add(1).ok_or(Error::InvalidLap)?
+        } else {
+            input_lap
+        };
+        if output_lap != expected_lap {
+            return Err(Error::InvalidLap);
+        }
+    }
+
+    //only an epoch clock records an epoch
+    match mode {
+        Some(ClockMode::Epoch(interval)) => {
+            check_epoch_interval(input_cell_data, output_cell_data, interval)
+        }
+        _ if get_cell_data_epoch(output_cell_data).is_some() => Err(Error::InvalidCellData),
+        _ => Ok(()),
+    }
+}